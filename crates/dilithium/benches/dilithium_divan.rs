@@ -1,8 +1,11 @@
 use dilithium::{
-    bench_message, default_seed, memory, SignatureScheme, TrackingAllocator,
-    BENCH_MESSAGE_SIZES, ML_DSA_65,
+    default_seed, SecretKeyForm, SignatureScheme, ML_DSA_65,
+    ML_DSA_65_DETERMINISTIC,
 };
 use divan::{black_box, AllocProfiler, Bencher};
+use pq_bench_core::{
+    bench_message, memory, TrackingAllocator, BENCH_MESSAGE_SIZES,
+};
 
 static DIVAN_ALLOC: AllocProfiler = AllocProfiler::system();
 
@@ -18,12 +21,13 @@ fn keygen(bencher: Bencher) {
     let seed = default_seed();
 
     bencher.bench(|| {
-        black_box(scheme.keypair(black_box(&seed)));
+        let keypair = scheme.keypair(black_box(&seed));
+        black_box(scheme.verifying_key(&keypair));
     });
 }
 
 #[divan::bench(args = BENCH_MESSAGE_SIZES)]
-fn sign(bencher: Bencher, message_size: usize) {
+fn sign_hedged(bencher: Bencher, message_size: usize) {
     let scheme = ML_DSA_65;
     let seed = default_seed();
     let keypair = scheme.keypair(&seed);
@@ -33,7 +37,29 @@ fn sign(bencher: Bencher, message_size: usize) {
         black_box(
             scheme
                 .sign(
-                    black_box(&keypair),
+                    black_box(scheme.signing_key(&keypair)),
+                    black_box(&message),
+                    black_box(CONTEXT),
+                )
+                .expect(
+                    "dilithium sign benchmark input should always be valid",
+                ),
+        );
+    });
+}
+
+#[divan::bench(args = BENCH_MESSAGE_SIZES)]
+fn sign_deterministic(bencher: Bencher, message_size: usize) {
+    let scheme = ML_DSA_65_DETERMINISTIC;
+    let seed = default_seed();
+    let keypair = scheme.keypair(&seed);
+    let message = bench_message(message_size);
+
+    bencher.bench(|| {
+        black_box(
+            scheme
+                .sign(
+                    black_box(scheme.signing_key(&keypair)),
                     black_box(&message),
                     black_box(CONTEXT),
                 )
@@ -51,12 +77,17 @@ fn verify(bencher: Bencher, message_size: usize) {
     let keypair = scheme.keypair(&seed);
     let message = bench_message(message_size);
     let signature = scheme
-        .sign(&keypair, &message, CONTEXT)
+        .sign(scheme.signing_key(&keypair), &message, CONTEXT)
         .expect("benchmark setup should sign message");
+    let verifying_key = scheme
+        .decode_verifying_key(
+            &scheme.encode_verifying_key(scheme.verifying_key(&keypair)),
+        )
+        .expect("benchmark setup should decode verifying key");
 
     bencher.bench(|| {
         black_box(scheme.verify(
-            black_box(&keypair),
+            black_box(&verifying_key),
             black_box(&message),
             black_box(CONTEXT),
             black_box(&signature),
@@ -69,18 +100,25 @@ fn print_sizes() {
     let seed = default_seed();
     let keypair = scheme.keypair(&seed);
     println!("{} sizes:", scheme.algorithm_name());
-    println!("  Public key: {} bytes", scheme.public_key_size(&keypair));
-    println!("  Secret key: {} bytes", scheme.secret_key_size(&keypair));
+    println!("  Public key: {} bytes", scheme.public_key_size());
+    println!(
+        "  Secret key (seed form): {} bytes",
+        scheme.secret_key_size(SecretKeyForm::Seed)
+    );
+    println!(
+        "  Secret key (expanded form): {} bytes",
+        scheme.secret_key_size(SecretKeyForm::Expanded)
+    );
 
     for message_size in BENCH_MESSAGE_SIZES {
         let message = bench_message(message_size);
         let signature = scheme
-            .sign(&keypair, &message, CONTEXT)
+            .sign(scheme.signing_key(&keypair), &message, CONTEXT)
             .expect("size measurement should sign message");
         println!(
             "  Signature (message {} bytes): {} bytes",
             message_size,
-            scheme.signature_size(&signature)
+            scheme.encode_signature(&signature).len()
         );
     }
 }
@@ -94,14 +132,19 @@ fn print_memory_usage() {
     for message_size in BENCH_MESSAGE_SIZES {
         let message = bench_message(message_size);
 
-        memory::reset_peak();
+        memory::reset();
         let signature = scheme
-            .sign(&keypair, &message, CONTEXT)
+            .sign(scheme.signing_key(&keypair), &message, CONTEXT)
             .expect("memory measurement should sign message");
         let sign_peak = memory::peak_bytes();
 
-        memory::reset_peak();
-        let _verified = scheme.verify(&keypair, &message, CONTEXT, &signature);
+        memory::reset();
+        let _verified = scheme.verify(
+            scheme.verifying_key(&keypair),
+            &message,
+            CONTEXT,
+            &signature,
+        );
         let verify_peak = memory::peak_bytes();
 
         println!(
@@ -0,0 +1,557 @@
+//! FIPS 204 `ML-DSA.Sign_internal` and `ML-DSA.Verify_internal` for
+//! ML-DSA-65, starting from the message representative `mu`.
+//!
+//! `ml-dsa` 0.0.4 derives `mu` inside its own signing and verification and
+//! keeps the arithmetic private, so this module re-implements both
+//! algorithms over the encoded keys and signatures. Given the same `rnd`,
+//! `sign` produces the bytes `ml-dsa` produces for the message behind `mu`.
+//!
+//! Arithmetic on secret values reduces with masks and a Barrett
+//! multiplication instead of `%`, and `Decompose` is the reference
+//! implementation's division-free form. Every secret or per-attempt
+//! intermediate is zeroized when it goes out of scope. Which attempt is
+//! rejected, and why, still shows in the timing, as in the reference
+//! implementation: rejected attempts are independent of the key.
+
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Shake128, Shake256};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::MU_SIZE;
+
+const Q: u32 = 8_380_417;
+const N: usize = 256;
+const K: usize = 6;
+const L: usize = 5;
+const ETA: u32 = 4;
+const TAU: usize = 49;
+const BETA: u32 = TAU as u32 * ETA;
+const GAMMA1: u32 = 1 << 19;
+const GAMMA2: u32 = (Q - 1) / 32;
+const OMEGA: usize = 55;
+const LAMBDA_BYTES: usize = 48;
+const D: u32 = 13;
+/// `256^-1 mod q`, the final scaling of the inverse NTT.
+const INVERSE_256: u32 = 8_347_681;
+/// `floor(2^64 / q)`, for Barrett reduction.
+const BARRETT: u64 = u64::MAX / Q as u64;
+
+const SEED_BYTES: usize = 32;
+const TR_BYTES: usize = 64;
+const ETA_BITS: usize = 4;
+const T0_BITS: usize = 13;
+const T1_BITS: usize = 10;
+const Z_BITS: usize = 20;
+const W1_BITS: usize = 4;
+
+type Poly = [u32; N];
+
+/// `ZETAS[i] = 1753^BitRev8(i) mod q` (FIPS 204, Appendix B).
+const ZETAS: [u32; N] = {
+    let mut powers = [0_u32; N];
+    let mut power = 1_u64;
+    let mut i = 0;
+    while i < N {
+        powers[i] = power as u32;
+        power = power * 1753 % Q as u64;
+        i += 1;
+    }
+    let mut zetas = [0_u32; N];
+    let mut i = 0;
+    while i < N {
+        zetas[i] = powers[(i as u8).reverse_bits() as usize];
+        i += 1;
+    }
+    zetas
+};
+
+/// The decoded secret parts of an ML-DSA-65 signing key, in the NTT domain.
+struct SecretKey {
+    rho: [u8; SEED_BYTES],
+    key: [u8; SEED_BYTES],
+    s1_hat: [Poly; L],
+    s2_hat: [Poly; K],
+    t0_hat: [Poly; K],
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+        self.s1_hat.zeroize();
+        self.s2_hat.zeroize();
+        self.t0_hat.zeroize();
+    }
+}
+
+/// Algorithm 7, from `mu` on. `signing_key` is the 4032-byte `skEncode`
+/// output; the result is the 3309-byte `sigEncode` output. `None` once
+/// `kappa` would overflow, which FIPS 204 leaves unbounded and which takes
+/// over 13,000 rejected attempts.
+pub(crate) fn sign(
+    signing_key: &[u8],
+    mu: &[u8; MU_SIZE],
+    rnd: &[u8; SEED_BYTES],
+) -> Option<Vec<u8>> {
+    let secret_key = decode_signing_key(signing_key);
+    let a_hat = expand_a(&secret_key.rho);
+    let mut rho_pp = Zeroizing::new([0_u8; 64]);
+    shake256(&[&secret_key.key, rnd, mu], rho_pp.as_mut_slice());
+
+    let mut kappa = 0_u16;
+    loop {
+        let y = Zeroizing::new(expand_mask(&rho_pp, kappa));
+        kappa = kappa.checked_add(L as u16)?;
+        let mut y_hat = Zeroizing::new(*y);
+        y_hat.iter_mut().for_each(ntt);
+        let w = Zeroizing::new(matrix_times(&a_hat, &y_hat));
+        let w1 = Zeroizing::new(w.map(|poly| poly.map(high_bits)));
+
+        let mut c_tilde = [0_u8; LAMBDA_BYTES];
+        shake256(&[mu, &encode_w1(&w1)], &mut c_tilde);
+        let mut c_hat = Zeroizing::new(sample_in_ball(&c_tilde));
+        ntt(&mut c_hat);
+
+        let z: Zeroizing<[Poly; L]> =
+            Zeroizing::new(core::array::from_fn(|i| {
+                add_poly(&y[i], &times_inverse(&c_hat, &secret_key.s1_hat[i]))
+            }));
+        let w_minus_cs2: Zeroizing<[Poly; K]> =
+            Zeroizing::new(core::array::from_fn(|i| {
+                sub_poly(&w[i], &times_inverse(&c_hat, &secret_key.s2_hat[i]))
+            }));
+        if infinity_norm(&z) >= GAMMA1 - BETA
+            || w_minus_cs2.iter().flatten().any(|&r| {
+                low_bits(r).unsigned_abs() >= u64::from(GAMMA2 - BETA)
+            })
+        {
+            continue;
+        }
+
+        let ct0: Zeroizing<[Poly; K]> =
+            Zeroizing::new(core::array::from_fn(|i| {
+                times_inverse(&c_hat, &secret_key.t0_hat[i])
+            }));
+        let hint: Zeroizing<[[bool; N]; K]> =
+            Zeroizing::new(core::array::from_fn(|i| {
+                core::array::from_fn(|j| {
+                    let r = add(w_minus_cs2[i][j], ct0[i][j]);
+                    make_hint(sub(0, ct0[i][j]), r)
+                })
+            }));
+        let hints = hint.iter().flatten().filter(|&&h| h).count();
+        if infinity_norm(&ct0) >= GAMMA2 || hints > OMEGA {
+            continue;
+        }
+
+        return Some(encode_signature(&c_tilde, &z, &hint));
+    }
+}
+
+/// Algorithm 8, from `mu` on. `verifying_key` is the 1952-byte `pkEncode`
+/// output and `signature` the 3309-byte `sigEncode` output.
+pub(crate) fn verify(
+    verifying_key: &[u8],
+    mu: &[u8; MU_SIZE],
+    signature: &[u8],
+) -> bool {
+    let (rho, t1) = verifying_key.split_at(SEED_BYTES);
+    let (c_tilde, rest) = signature.split_at(LAMBDA_BYTES);
+    let (z, hint) = rest.split_at(L * packed_len(Z_BITS));
+    let z: [Poly; L] = core::array::from_fn(|i| {
+        unpack(&z[i * packed_len(Z_BITS)..], Z_BITS).map(|v| sub(GAMMA1, v))
+    });
+    let Some(hint) = decode_hint(hint) else {
+        return false;
+    };
+    if infinity_norm(&z) >= GAMMA1 - BETA {
+        return false;
+    }
+
+    let a_hat = expand_a(rho.try_into().expect("32-byte seed"));
+    let mut z_hat = z;
+    z_hat.iter_mut().for_each(ntt);
+    let az = matrix_times_ntt(&a_hat, &z_hat);
+    let mut c_hat = sample_in_ball(c_tilde);
+    ntt(&mut c_hat);
+
+    let w1: [Poly; K] = core::array::from_fn(|i| {
+        let mut t1_2d = unpack(&t1[i * packed_len(T1_BITS)..], T1_BITS)
+            .map(|v| mul(v, 1 << D));
+        ntt(&mut t1_2d);
+        let mut w = sub_poly(&az[i], &pointwise(&c_hat, &t1_2d));
+        ntt_inverse(&mut w);
+        core::array::from_fn(|j| use_hint(hint[i][j], w[j]))
+    });
+
+    let mut expected = [0_u8; LAMBDA_BYTES];
+    shake256(&[mu, &encode_w1(&w1)], &mut expected);
+    expected[..] == *c_tilde
+}
+
+fn decode_signing_key(bytes: &[u8]) -> SecretKey {
+    let (rho, rest) = bytes.split_at(SEED_BYTES);
+    let (key, rest) = rest.split_at(SEED_BYTES);
+    // `tr` is only needed to compute `mu`, which the caller supplies.
+    let (s1, rest) = rest[TR_BYTES..].split_at(L * packed_len(ETA_BITS));
+    let (s2, t0) = rest.split_at(K * packed_len(ETA_BITS));
+
+    let eta_poly = |bytes: &[u8], i: usize| {
+        let mut poly = unpack(&bytes[i * packed_len(ETA_BITS)..], ETA_BITS)
+            .map(|v| sub(ETA, v));
+        ntt(&mut poly);
+        poly
+    };
+    SecretKey {
+        rho: rho.try_into().expect("32-byte seed"),
+        key: key.try_into().expect("32-byte key"),
+        s1_hat: core::array::from_fn(|i| eta_poly(s1, i)),
+        s2_hat: core::array::from_fn(|i| eta_poly(s2, i)),
+        t0_hat: core::array::from_fn(|i| {
+            let mut poly = unpack(&t0[i * packed_len(T0_BITS)..], T0_BITS)
+                .map(|v| sub(1 << (D - 1), v));
+            ntt(&mut poly);
+            poly
+        }),
+    }
+}
+
+fn encode_signature(
+    c_tilde: &[u8; LAMBDA_BYTES],
+    z: &[Poly; L],
+    hint: &[[bool; N]; K],
+) -> Vec<u8> {
+    let mut bytes = c_tilde.to_vec();
+    for poly in z {
+        pack(&poly.map(|c| sub(GAMMA1, c)), Z_BITS, &mut bytes);
+    }
+    // Algorithm 20 HintBitPack.
+    let mut indices = [0_u8; OMEGA + K];
+    let mut index = 0;
+    for (i, row) in hint.iter().enumerate() {
+        for (j, _) in row.iter().enumerate().filter(|(_, &h)| h) {
+            indices[index] = j as u8;
+            index += 1;
+        }
+        indices[OMEGA + i] = index as u8;
+    }
+    bytes.extend(indices);
+    bytes
+}
+
+// Algorithm 21 HintBitUnpack.
+fn decode_hint(bytes: &[u8]) -> Option<[[bool; N]; K]> {
+    let mut hint = [[false; N]; K];
+    let mut index = 0;
+    for (i, row) in hint.iter_mut().enumerate() {
+        let end = usize::from(bytes[OMEGA + i]);
+        if end < index || end > OMEGA {
+            return None;
+        }
+        let first = index;
+        while index < end {
+            if index > first && bytes[index - 1] >= bytes[index] {
+                return None;
+            }
+            row[usize::from(bytes[index])] = true;
+            index += 1;
+        }
+    }
+    bytes[index..OMEGA].iter().all(|&b| b == 0).then_some(hint)
+}
+
+// Algorithm 28 w1Encode.
+fn encode_w1(w1: &[Poly; K]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(K * packed_len(W1_BITS));
+    for poly in w1 {
+        pack(poly, W1_BITS, &mut bytes);
+    }
+    bytes
+}
+
+// Algorithm 29 SampleInBall.
+fn sample_in_ball(c_tilde: &[u8]) -> Poly {
+    let mut reader = Shake256::default().chain(c_tilde).finalize_xof();
+    let mut signs = [0_u8; 8];
+    reader.read(&mut signs);
+    let signs = u64::from_le_bytes(signs);
+
+    let mut c = [0_u32; N];
+    let mut byte = [0_u8];
+    for i in N - TAU..N {
+        let j = loop {
+            reader.read(&mut byte);
+            if usize::from(byte[0]) <= i {
+                break usize::from(byte[0]);
+            }
+        };
+        c[i] = c[j];
+        c[j] = if signs >> (i + TAU - N) & 1 == 1 {
+            Q - 1
+        } else {
+            1
+        };
+    }
+    c
+}
+
+// Algorithms 30 RejNTTPoly and 32 ExpandA.
+fn expand_a(rho: &[u8; SEED_BYTES]) -> [[Poly; L]; K] {
+    core::array::from_fn(|r| {
+        core::array::from_fn(|s| {
+            let mut reader = Shake128::default()
+                .chain(rho)
+                .chain([s as u8, r as u8])
+                .finalize_xof();
+            let mut poly = [0_u32; N];
+            let mut bytes = [0_u8; 3];
+            let mut j = 0;
+            while j < N {
+                reader.read(&mut bytes);
+                let coefficient = u32::from(bytes[0])
+                    | u32::from(bytes[1]) << 8
+                    | u32::from(bytes[2] & 0x7f) << 16;
+                if coefficient < Q {
+                    poly[j] = coefficient;
+                    j += 1;
+                }
+            }
+            poly
+        })
+    })
+}
+
+// Algorithm 34 ExpandMask.
+fn expand_mask(rho_pp: &[u8; 64], kappa: u16) -> [Poly; L] {
+    core::array::from_fn(|r| {
+        let mut bytes = [0_u8; packed_len(Z_BITS)];
+        shake256(&[rho_pp, &(kappa + r as u16).to_le_bytes()], &mut bytes);
+        unpack(&bytes, Z_BITS).map(|v| sub(GAMMA1, v))
+    })
+}
+
+/// `A * y` for `y` in the NTT domain, returned in the normal domain.
+fn matrix_times(a_hat: &[[Poly; L]; K], y_hat: &[Poly; L]) -> [Poly; K] {
+    matrix_times_ntt(a_hat, y_hat).map(|mut poly| {
+        ntt_inverse(&mut poly);
+        poly
+    })
+}
+
+fn matrix_times_ntt(a_hat: &[[Poly; L]; K], y_hat: &[Poly; L]) -> [Poly; K] {
+    core::array::from_fn(|r| {
+        a_hat[r]
+            .iter()
+            .zip(y_hat)
+            .fold([0; N], |sum, (a, y)| add_poly(&sum, &pointwise(a, y)))
+    })
+}
+
+/// `NTT^-1(c_hat * s_hat)`.
+fn times_inverse(c_hat: &Poly, s_hat: &Poly) -> Poly {
+    let mut product = pointwise(c_hat, s_hat);
+    ntt_inverse(&mut product);
+    product
+}
+
+// Algorithm 41 NTT.
+fn ntt(w: &mut Poly) {
+    let mut m = 0;
+    let mut len = 128;
+    while len >= 1 {
+        for start in (0..N).step_by(2 * len) {
+            m += 1;
+            let zeta = ZETAS[m];
+            for j in start..start + len {
+                let t = mul(zeta, w[j + len]);
+                w[j + len] = sub(w[j], t);
+                w[j] = add(w[j], t);
+            }
+        }
+        len /= 2;
+    }
+}
+
+// Algorithm 42 NTT^-1.
+fn ntt_inverse(w: &mut Poly) {
+    let mut m = N;
+    let mut len = 1;
+    while len < N {
+        for start in (0..N).step_by(2 * len) {
+            m -= 1;
+            let zeta = Q - ZETAS[m];
+            for j in start..start + len {
+                let t = w[j];
+                w[j] = add(t, w[j + len]);
+                w[j + len] = mul(zeta, sub(t, w[j + len]));
+            }
+        }
+        len *= 2;
+    }
+    for coefficient in w.iter_mut() {
+        *coefficient = mul(*coefficient, INVERSE_256);
+    }
+}
+
+// Algorithm 36 Decompose, with `r0` as a signed integer. This is the
+// reference implementation's branch-free form for `gamma2 = (q - 1) / 32`:
+// `r1` is `round(r / 2 gamma2)` by multiply and shift, wrapped to 0 at 16,
+// and `r0` is moved down by `q` when it lands above `(q - 1) / 2`.
+fn decompose(r: u32) -> (u32, i64) {
+    let r = r as i32;
+    let r1 = (((r + 127) >> 7) * 1025 + (1 << 21)) >> 22 & 15;
+    let mut r0 = r - r1 * 2 * GAMMA2 as i32;
+    r0 -= (((Q as i32 - 1) / 2 - r0) >> 31) & Q as i32;
+    (r1 as u32, i64::from(r0))
+}
+
+fn high_bits(r: u32) -> u32 {
+    decompose(r).0
+}
+
+fn low_bits(r: u32) -> i64 {
+    decompose(r).1
+}
+
+// Algorithm 39 MakeHint.
+fn make_hint(z: u32, r: u32) -> bool {
+    high_bits(r) != high_bits(add(r, z))
+}
+
+// Algorithm 40 UseHint.
+fn use_hint(h: bool, r: u32) -> u32 {
+    const M: u32 = (Q - 1) / (2 * GAMMA2);
+    let (r1, r0) = decompose(r);
+    match (h, r0 > 0) {
+        (false, _) => r1,
+        (true, true) => (r1 + 1) % M,
+        (true, false) => (r1 + M - 1) % M,
+    }
+}
+
+fn infinity_norm<const R: usize>(vector: &[Poly; R]) -> u32 {
+    vector
+        .iter()
+        .flatten()
+        .map(|&c| c.min(Q - c))
+        .max()
+        .unwrap_or(0)
+}
+
+const fn packed_len(bits: usize) -> usize {
+    N * bits / 8
+}
+
+// Algorithm 16 SimpleBitPack, on values already mapped to `[0, 2^bits)`.
+fn pack(poly: &Poly, bits: usize, out: &mut Vec<u8>) {
+    let mut buffer = 0_u64;
+    let mut filled = 0;
+    for &value in poly {
+        buffer |= u64::from(value) << filled;
+        filled += bits;
+        while filled >= 8 {
+            out.push(buffer as u8);
+            buffer >>= 8;
+            filled -= 8;
+        }
+    }
+}
+
+// Algorithm 18 SimpleBitUnpack: the raw `bits`-bit values.
+fn unpack(bytes: &[u8], bits: usize) -> Poly {
+    let mut poly = [0_u32; N];
+    let mut buffer = 0_u64;
+    let mut filled = 0;
+    let mut bytes = bytes.iter();
+    for value in &mut poly {
+        while filled < bits {
+            buffer |=
+                u64::from(*bytes.next().expect("length checked")) << filled;
+            filled += 8;
+        }
+        *value = (buffer & ((1 << bits) - 1)) as u32;
+        buffer >>= bits;
+        filled -= bits;
+    }
+    poly
+}
+
+fn pointwise(a: &Poly, b: &Poly) -> Poly {
+    core::array::from_fn(|i| mul(a[i], b[i]))
+}
+
+fn add_poly(a: &Poly, b: &Poly) -> Poly {
+    core::array::from_fn(|i| add(a[i], b[i]))
+}
+
+fn sub_poly(a: &Poly, b: &Poly) -> Poly {
+    core::array::from_fn(|i| sub(a[i], b[i]))
+}
+
+// `x mod q` for `x < 2q`: subtracts `q` and adds it back under a mask
+// taken from the borrow, with no branch on `x`.
+fn reduce_once(x: u32) -> u32 {
+    let t = x.wrapping_sub(Q);
+    t.wrapping_add(Q & 0_u32.wrapping_sub(t >> 31))
+}
+
+// Operands are already reduced, except that `sub` also takes `b == q`.
+fn add(a: u32, b: u32) -> u32 {
+    reduce_once(a + b)
+}
+
+fn sub(a: u32, b: u32) -> u32 {
+    reduce_once(a + Q - b)
+}
+
+// Barrett reduction: the quotient estimate is at most one short, so one
+// masked subtraction finishes it.
+fn mul(a: u32, b: u32) -> u32 {
+    let x = u64::from(a) * u64::from(b);
+    let quotient = ((u128::from(x) * u128::from(BARRETT)) >> 64) as u64;
+    reduce_once((x - quotient * u64::from(Q)) as u32)
+}
+
+fn shake256(parts: &[&[u8]], out: &mut [u8]) {
+    let mut hasher = Shake256::default();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize_xof().read(out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add, decompose, mul, sub, GAMMA2, Q};
+
+    #[test]
+    fn masked_arithmetic_matches_plain_reduction() {
+        let samples = [0, 1, 2, GAMMA2, Q / 2, Q - 2, Q - 1];
+        for a in samples {
+            for b in samples {
+                let (a64, b64, q) = (u64::from(a), u64::from(b), u64::from(Q));
+                assert_eq!(u64::from(add(a, b)), (a64 + b64) % q);
+                assert_eq!(u64::from(sub(a, b)), (a64 + q - b64) % q);
+                assert_eq!(u64::from(mul(a, b)), a64 * b64 % q);
+            }
+        }
+    }
+
+    #[test]
+    fn decompose_matches_fips_204() {
+        // Algorithm 36 as written, with its `r1 = 0, r0 - 1` corner.
+        let spec = |r: u32| {
+            let mut r0 = i64::from(r % (2 * GAMMA2));
+            if r0 > i64::from(GAMMA2) {
+                r0 -= i64::from(2 * GAMMA2);
+            }
+            if i64::from(r) - r0 == i64::from(Q - 1) {
+                (0, r0 - 1)
+            } else {
+                (((i64::from(r) - r0) / i64::from(2 * GAMMA2)) as u32, r0)
+            }
+        };
+        for r in (0..Q).step_by(97).chain(Q - 2 * GAMMA2..Q) {
+            assert_eq!(decompose(r), spec(r), "r = {r}");
+        }
+    }
+}
@@ -1,192 +1,661 @@
-use ml_dsa::{KeyGen, KeyPair, MlDsa65, Signature, B32};
-use std::alloc::{GlobalAlloc, Layout};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::{Duration, Instant};
+use ml_dsa::{
+    EncodedSignature, EncodedSigningKey, EncodedVerifyingKey, KeyGen, KeyPair,
+    MlDsa65, Signature, SigningKey, VerifyingKey, B32,
+};
+#[cfg(feature = "op-count")]
+use pq_bench_core::zkvm::{self, OpCounts, SHAKE128_RATE, SHAKE256_RATE};
+use pq_signature::{
+    AssumptionFamily, Capabilities, Document, Scheme, SchemeSizes,
+    SecurityProfile, Standardization,
+};
+use rand_core::{CryptoRngCore, OsRng, RngCore};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::Shake256;
+use std::error::Error;
+use std::fmt;
+use std::mem::size_of;
+use std::sync::OnceLock;
+use zeroize::{Zeroize, Zeroizing};
+
+mod external_mu;
+
+/// Size of the FIPS 204 message representative `mu` and of `tr`.
+pub const MU_SIZE: usize = 64;
+pub const MAX_CONTEXT_SIZE: usize = 255;
+/// Size of the FIPS 204 key generation seed `xi`.
+pub const SEED_SIZE: usize = 32;
 
-pub const BENCH_MESSAGE_SIZES: [usize; 4] = [32, 256, 1024, 4096];
-pub const BENCH_MESSAGE_BYTE: u8 = 0x42;
+pub trait SignatureScheme {
+    type Seed;
+    type KeyPair;
+    type SigningKey;
+    type VerifyingKey;
+    type Signature;
+    type Error;
+
+    fn algorithm_name(&self) -> &'static str;
+    fn keypair(&self, seed: &Self::Seed) -> Self::KeyPair;
+    fn signing_key<'a>(
+        &self,
+        keypair: &'a Self::KeyPair,
+    ) -> &'a Self::SigningKey;
+    fn verifying_key<'a>(
+        &self,
+        keypair: &'a Self::KeyPair,
+    ) -> &'a Self::VerifyingKey;
+    fn sign(
+        &self,
+        signing_key: &Self::SigningKey,
+        message: &[u8],
+        context: &[u8],
+    ) -> Result<Self::Signature, Self::Error>;
+    fn verify(
+        &self,
+        verifying_key: &Self::VerifyingKey,
+        message: &[u8],
+        context: &[u8],
+        signature: &Self::Signature,
+    ) -> bool;
+
+    fn encode_verifying_key(
+        &self,
+        verifying_key: &Self::VerifyingKey,
+    ) -> Vec<u8>;
+    fn decode_verifying_key(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Self::VerifyingKey, Self::Error>;
+    fn encode_signing_key(&self, signing_key: &Self::SigningKey) -> Vec<u8>;
+    fn decode_signing_key(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Self::SigningKey, Self::Error>;
+    fn encode_signature(&self, signature: &Self::Signature) -> Vec<u8>;
+    fn decode_signature(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Self::Signature, Self::Error>;
 
-static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
-static PEAK_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
-static BASELINE: AtomicUsize = AtomicUsize::new(0);
+    fn public_key_size(&self) -> usize;
+    fn secret_key_size(&self, form: SecretKeyForm) -> usize;
+    fn signature_size(&self) -> usize;
+}
 
-pub struct TrackingAllocator<A: GlobalAlloc + Sync + 'static> {
-    inner: &'static A,
+#[derive(Debug)]
+pub enum DilithiumError {
+    InvalidLength {
+        artifact: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    MalformedSignature,
+    ContextTooLong {
+        actual: usize,
+    },
+    Randomness(rand_core::Error),
+    Signing(ml_dsa::Error),
+    /// External-mu signing rejected every candidate before its counter
+    /// would overflow.
+    RejectionBound,
 }
 
-impl<A: GlobalAlloc + Sync + 'static> TrackingAllocator<A> {
-    pub const fn new(inner: &'static A) -> Self {
-        Self { inner }
+impl fmt::Display for DilithiumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength {
+                artifact,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "invalid {artifact} length: expected {expected}, got {actual}"
+                )
+            }
+            Self::MalformedSignature => {
+                write!(f, "malformed signature encoding")
+            }
+            Self::ContextTooLong { actual } => {
+                write!(
+                    f,
+                    "context too long: at most {MAX_CONTEXT_SIZE} bytes, got {actual}"
+                )
+            }
+            Self::Randomness(err) => {
+                write!(f, "failed to read OS randomness: {err}")
+            }
+            Self::Signing(err) => write!(f, "signing failed: {err}"),
+            Self::RejectionBound => {
+                write!(f, "signing failed: rejection sampling bound reached")
+            }
+        }
     }
 }
 
-unsafe impl<A: GlobalAlloc + Sync + 'static> GlobalAlloc
-    for TrackingAllocator<A>
-{
-    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        let ptr = unsafe { self.inner.alloc(layout) };
-        if !ptr.is_null() {
-            track_alloc(layout.size());
+impl Error for DilithiumError {}
+
+/// How a secret key is stored and exported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecretKeyForm {
+    /// The 32-byte keygen seed `xi`; everything else is re-derived.
+    Seed,
+    /// The FIPS 204 `skEncode` output.
+    Expanded,
+}
+
+/// An ML-DSA-65 key pair held as its 32-byte seed `xi`.
+///
+/// The signing and verifying keys are expanded on first use and cached.
+/// Only the seed is exported, via `seed_bytes`.
+pub struct SeedKeyPair {
+    seed: [u8; SEED_SIZE],
+    // Boxed: the expanded key is tens of kilobytes.
+    expanded: OnceLock<Box<KeyPair<MlDsa65>>>,
+}
+
+impl SeedKeyPair {
+    pub fn from_seed(seed: &B32) -> Self {
+        let mut bytes = [0_u8; SEED_SIZE];
+        bytes.copy_from_slice(seed);
+        Self {
+            seed: bytes,
+            expanded: OnceLock::new(),
         }
-        ptr
     }
 
-    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
-        unsafe { self.inner.dealloc(ptr, layout) };
-        track_dealloc(layout.size());
+    pub fn from_seed_bytes(bytes: &[u8]) -> Result<Self, DilithiumError> {
+        let seed = B32::try_from(bytes)
+            .map_err(|_| invalid_length("seed", bytes, SEED_SIZE))?;
+        Ok(Self::from_seed(&seed))
+    }
+
+    pub fn generate() -> Result<Self, DilithiumError> {
+        Ok(Self::from_seed(&random_seed()?))
+    }
+
+    pub fn seed_bytes(&self) -> [u8; SEED_SIZE] {
+        self.seed
+    }
+
+    pub fn is_expanded(&self) -> bool {
+        self.expanded.get().is_some()
+    }
+
+    pub fn signing_key(&self) -> &SigningKey<MlDsa65> {
+        self.expand().signing_key()
+    }
+
+    /// Re-derives the verifying key from the seed.
+    pub fn verifying_key(&self) -> &VerifyingKey<MlDsa65> {
+        self.expand().verifying_key()
+    }
+
+    fn expand(&self) -> &KeyPair<MlDsa65> {
+        self.expanded.get_or_init(|| {
+            let seed = B32::from(self.seed);
+            Box::new(MlDsa65::key_gen_internal(&seed))
+        })
     }
 }
 
-fn track_alloc(size: usize) {
-    let current = ALLOCATED.fetch_add(size, Ordering::SeqCst) + size;
-    let baseline = BASELINE.load(Ordering::SeqCst);
-    let relative_current = current.saturating_sub(baseline);
-    let mut peak = PEAK_ALLOCATED.load(Ordering::SeqCst);
+impl fmt::Debug for SeedKeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeedKeyPair")
+            .field("seed", &"<redacted>")
+            .field("expanded", &self.is_expanded())
+            .finish()
+    }
+}
 
-    while relative_current > peak {
-        match PEAK_ALLOCATED.compare_exchange_weak(
-            peak,
-            relative_current,
-            Ordering::SeqCst,
-            Ordering::SeqCst,
-        ) {
-            Ok(_) => break,
-            Err(observed) => peak = observed,
+impl Drop for SeedKeyPair {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+    }
+}
+
+/// Source of the per-signature `rnd` input (FIPS 204, ML-DSA.Sign).
+///
+/// There is no caller-RNG variant: `MlDsa65Scheme` is a `Copy` value held in
+/// consts such as [`ML_DSA_65`], so it cannot own a `&mut` RNG. Pass one to
+/// [`MlDsa65Scheme::sign_with_rng`] instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SigningMode {
+    /// `rnd` is all zeros, so equal inputs give equal signatures.
+    Deterministic,
+    /// `rnd` is drawn from the OS RNG. FIPS 204 recommends this mode for
+    /// fault and side-channel resistance.
+    #[default]
+    Hedged,
+}
+
+impl SigningMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Deterministic => "deterministic",
+            Self::Hedged => "hedged",
         }
     }
 }
 
-fn track_dealloc(size: usize) {
-    ALLOCATED.fetch_sub(size, Ordering::SeqCst);
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MlDsa65Scheme {
+    signing_mode: SigningMode,
 }
 
-pub mod memory {
-    use super::{Ordering, ALLOCATED, BASELINE, PEAK_ALLOCATED};
+const REJECTION_PITFALL: &str = "Signing loops until a candidate passes \
+    rejection sampling; leaking why a candidate was rejected leaks the key.";
+const POWER_PITFALL: &str = "Power and EM traces of the NTTs over the \
+    secret vectors recover them unless the implementation is masked.";
 
-    pub fn reset_peak() {
-        let current = ALLOCATED.load(Ordering::SeqCst);
-        BASELINE.store(current, Ordering::SeqCst);
-        PEAK_ALLOCATED.store(0, Ordering::SeqCst);
+pub const ML_DSA_65: MlDsa65Scheme = MlDsa65Scheme::new(SigningMode::Hedged);
+pub const ML_DSA_65_DETERMINISTIC: MlDsa65Scheme =
+    MlDsa65Scheme::new(SigningMode::Deterministic);
+
+impl MlDsa65Scheme {
+    pub const fn new(signing_mode: SigningMode) -> Self {
+        Self { signing_mode }
     }
 
-    pub fn peak_bytes() -> usize {
-        PEAK_ALLOCATED.load(Ordering::SeqCst)
+    pub fn signing_mode(&self) -> SigningMode {
+        self.signing_mode
     }
-}
 
-pub trait SignatureScheme {
-    type Seed;
-    type KeyPair;
-    type Signature;
-    type Error;
+    /// Primitive operations of one verify with an empty context, starting
+    /// from the encoded public key as a zkVM guest would: that includes
+    /// `ExpandA`, hashing the key into `tr` and transforming `t1`, which
+    /// `VerifyingKey` caches on the host. Rejection sampling is assumed to
+    /// need no extra squeezes.
+    #[cfg(feature = "op-count")]
+    pub fn verify_ops(&self, message_len: usize) -> OpCounts {
+        const K: usize = 6;
+        const L: usize = 5;
+        const N: usize = 256;
+        const LAMBDA_BYTES: usize = 48;
+        const TAU: usize = 49;
+        let public_key_bytes = 32 + K * N * 10 / 8;
+        let w1_bytes = K * N * 4 / 8;
+
+        let expand_a =
+            OpCounts::shake(34, SHAKE128_RATE, 5 * SHAKE128_RATE).times(K * L);
+        let tr = OpCounts::shake(public_key_bytes, SHAKE256_RATE, MU_SIZE);
+        let mu =
+            OpCounts::shake(MU_SIZE + 2 + message_len, SHAKE256_RATE, MU_SIZE);
+        let sample_in_ball =
+            OpCounts::shake(LAMBDA_BYTES, SHAKE256_RATE, 8 + TAU);
+        let challenge =
+            OpCounts::shake(MU_SIZE + w1_bytes, SHAKE256_RATE, LAMBDA_BYTES);
+        // Forward NTTs of z, c and t1 * 2^d, and an inverse NTT per row.
+        let ntts = OpCounts::ntt(N, L + 1 + K + K);
+        // A * z, c * t1, t1 * 2^d and the inverse NTTs' final scaling.
+        let products = OpCounts::modmuls(K * L * N + 3 * K * N);
+        expand_a + tr + mu + sample_in_ball + challenge + ntts + products
+    }
 
-    fn algorithm_name(&self) -> &'static str;
-    fn keypair(&self, seed: &Self::Seed) -> Self::KeyPair;
-    fn sign(
+    pub const fn security(&self) -> SecurityProfile {
+        SecurityProfile {
+            nist_category: 3,
+            classical_bits: 182,
+            quantum_bits: 165,
+            family: AssumptionFamily::Lattice,
+            assumption: "Module-LWE and Module-SIS",
+            standardization: Standardization::Standardized(Document::Fips(204)),
+            pitfalls: match self.signing_mode {
+                SigningMode::Hedged => &[REJECTION_PITFALL, POWER_PITFALL],
+                SigningMode::Deterministic => &[
+                    REJECTION_PITFALL,
+                    POWER_PITFALL,
+                    "Deterministic signing lets one induced fault on a \
+                     repeated message recover the key.",
+                ],
+            },
+        }
+    }
+
+    /// Hedged signing with a caller-supplied RNG, regardless of
+    /// `signing_mode`. This is a method rather than a [`SigningMode`]
+    /// variant because the RNG is borrowed mutably for one call.
+    pub fn sign_with_rng(
         &self,
-        keypair: &Self::KeyPair,
+        signing_key: &SigningKey<MlDsa65>,
         message: &[u8],
         context: &[u8],
-    ) -> Result<Self::Signature, Self::Error>;
-    fn verify(
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Signature<MlDsa65>, DilithiumError> {
+        signing_key
+            .sign_randomized(message, context, rng)
+            .map_err(DilithiumError::Signing)
+    }
+
+    /// Computes the message representative `mu = H(tr || M')` of FIPS 204,
+    /// where `tr = H(pk, 64)` and `M' = 0 || |ctx| || ctx || M`.
+    ///
+    /// This is the half of an external-mu split that runs without the
+    /// signing key; [`Self::sign_mu`] and [`Self::verify_mu`] consume it.
+    pub fn compute_mu(
         &self,
-        keypair: &Self::KeyPair,
+        verifying_key: &VerifyingKey<MlDsa65>,
         message: &[u8],
         context: &[u8],
-        signature: &Self::Signature,
-    ) -> bool;
-    fn public_key_size(&self, keypair: &Self::KeyPair) -> usize;
-    fn secret_key_size(&self, keypair: &Self::KeyPair) -> usize;
-    fn signature_size(&self, signature: &Self::Signature) -> usize;
-}
+    ) -> Result<[u8; MU_SIZE], DilithiumError> {
+        if context.len() > MAX_CONTEXT_SIZE {
+            return Err(DilithiumError::ContextTooLong {
+                actual: context.len(),
+            });
+        }
 
-#[derive(Clone, Copy, Debug, Default)]
-pub struct MlDsa65Scheme;
+        let tr = shake256(&[&verifying_key.encode()]);
+        Ok(shake256(&[
+            &tr,
+            &[0, context.len() as u8],
+            context,
+            message,
+        ]))
+    }
+
+    /// Signs a precomputed `mu` (see [`Self::compute_mu`]). The result is
+    /// the signature `sign` would give for the message and context behind
+    /// `mu`, with `rnd` taken from `signing_mode`.
+    pub fn sign_mu(
+        &self,
+        signing_key: &SigningKey<MlDsa65>,
+        mu: &[u8; MU_SIZE],
+    ) -> Result<Signature<MlDsa65>, DilithiumError> {
+        match self.signing_mode {
+            SigningMode::Deterministic => {
+                self.sign_mu_with_rnd(signing_key, mu, &[0; SEED_SIZE])
+            }
+            SigningMode::Hedged => {
+                self.sign_mu_with_rng(signing_key, mu, &mut OsRng)
+            }
+        }
+    }
+
+    /// Hedged [`Self::sign_mu`] with a caller-supplied RNG, regardless of
+    /// `signing_mode`.
+    pub fn sign_mu_with_rng(
+        &self,
+        signing_key: &SigningKey<MlDsa65>,
+        mu: &[u8; MU_SIZE],
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Signature<MlDsa65>, DilithiumError> {
+        let mut rnd = Zeroizing::new([0_u8; SEED_SIZE]);
+        rng.try_fill_bytes(rnd.as_mut_slice())
+            .map_err(DilithiumError::Randomness)?;
+        self.sign_mu_with_rnd(signing_key, mu, &rnd)
+    }
+
+    fn sign_mu_with_rnd(
+        &self,
+        signing_key: &SigningKey<MlDsa65>,
+        mu: &[u8; MU_SIZE],
+        rnd: &[u8; SEED_SIZE],
+    ) -> Result<Signature<MlDsa65>, DilithiumError> {
+        let mut encoded = signing_key.encode();
+        let signature = external_mu::sign(&encoded, mu, rnd);
+        encoded.zeroize();
+        self.decode_signature(&signature.ok_or(DilithiumError::RejectionBound)?)
+    }
 
-pub const ML_DSA_65: MlDsa65Scheme = MlDsa65Scheme;
+    /// Verifies `signature` against a precomputed `mu`. Accepts exactly
+    /// the signatures `verify` accepts for the message and context behind
+    /// `mu`.
+    pub fn verify_mu(
+        &self,
+        verifying_key: &VerifyingKey<MlDsa65>,
+        mu: &[u8; MU_SIZE],
+        signature: &Signature<MlDsa65>,
+    ) -> bool {
+        external_mu::verify(&verifying_key.encode(), mu, &signature.encode())
+    }
+}
 
 impl SignatureScheme for MlDsa65Scheme {
     type Seed = B32;
-    type KeyPair = KeyPair<MlDsa65>;
+    type KeyPair = SeedKeyPair;
+    type SigningKey = SigningKey<MlDsa65>;
+    type VerifyingKey = VerifyingKey<MlDsa65>;
     type Signature = Signature<MlDsa65>;
-    type Error = ml_dsa::Error;
+    type Error = DilithiumError;
 
     fn algorithm_name(&self) -> &'static str {
         "ML-DSA-65"
     }
 
     fn keypair(&self, seed: &Self::Seed) -> Self::KeyPair {
-        MlDsa65::key_gen_internal(seed)
+        SeedKeyPair::from_seed(seed)
+    }
+
+    fn signing_key<'a>(
+        &self,
+        keypair: &'a Self::KeyPair,
+    ) -> &'a Self::SigningKey {
+        keypair.signing_key()
+    }
+
+    fn verifying_key<'a>(
+        &self,
+        keypair: &'a Self::KeyPair,
+    ) -> &'a Self::VerifyingKey {
+        keypair.verifying_key()
     }
 
     fn sign(
         &self,
-        keypair: &Self::KeyPair,
+        signing_key: &Self::SigningKey,
         message: &[u8],
         context: &[u8],
     ) -> Result<Self::Signature, Self::Error> {
-        keypair.signing_key().sign_deterministic(message, context)
+        match self.signing_mode {
+            SigningMode::Deterministic => signing_key
+                .sign_deterministic(message, context)
+                .map_err(DilithiumError::Signing),
+            SigningMode::Hedged => {
+                self.sign_with_rng(signing_key, message, context, &mut OsRng)
+            }
+        }
     }
 
     fn verify(
         &self,
-        keypair: &Self::KeyPair,
+        verifying_key: &Self::VerifyingKey,
         message: &[u8],
         context: &[u8],
         signature: &Self::Signature,
     ) -> bool {
-        keypair
-            .verifying_key()
-            .verify_with_context(message, context, signature)
+        verifying_key.verify_with_context(message, context, signature)
     }
 
-    fn public_key_size(&self, keypair: &Self::KeyPair) -> usize {
-        keypair.verifying_key().encode().len()
+    fn encode_verifying_key(
+        &self,
+        verifying_key: &Self::VerifyingKey,
+    ) -> Vec<u8> {
+        verifying_key.encode().to_vec()
+    }
+
+    fn decode_verifying_key(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Self::VerifyingKey, Self::Error> {
+        let encoded =
+            EncodedVerifyingKey::<MlDsa65>::try_from(bytes).map_err(|_| {
+                invalid_length("verifying key", bytes, self.public_key_size())
+            })?;
+        Ok(VerifyingKey::decode(&encoded))
     }
 
-    fn secret_key_size(&self, keypair: &Self::KeyPair) -> usize {
-        keypair.signing_key().encode().len()
+    fn encode_signing_key(&self, signing_key: &Self::SigningKey) -> Vec<u8> {
+        signing_key.encode().to_vec()
+    }
+
+    fn decode_signing_key(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Self::SigningKey, Self::Error> {
+        let encoded =
+            EncodedSigningKey::<MlDsa65>::try_from(bytes).map_err(|_| {
+                invalid_length(
+                    "signing key",
+                    bytes,
+                    self.secret_key_size(SecretKeyForm::Expanded),
+                )
+            })?;
+        Ok(SigningKey::decode(&encoded))
+    }
+
+    fn encode_signature(&self, signature: &Self::Signature) -> Vec<u8> {
+        signature.encode().to_vec()
+    }
+
+    fn decode_signature(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Self::Signature, Self::Error> {
+        let encoded =
+            EncodedSignature::<MlDsa65>::try_from(bytes).map_err(|_| {
+                invalid_length("signature", bytes, self.signature_size())
+            })?;
+        Signature::decode(&encoded).ok_or(DilithiumError::MalformedSignature)
     }
 
-    fn signature_size(&self, signature: &Self::Signature) -> usize {
-        signature.encode().len()
+    fn public_key_size(&self) -> usize {
+        size_of::<EncodedVerifyingKey<MlDsa65>>()
+    }
+
+    fn secret_key_size(&self, form: SecretKeyForm) -> usize {
+        match form {
+            SecretKeyForm::Seed => SEED_SIZE,
+            SecretKeyForm::Expanded => size_of::<EncodedSigningKey<MlDsa65>>(),
+        }
+    }
+
+    fn signature_size(&self) -> usize {
+        size_of::<EncodedSignature<MlDsa65>>()
+    }
+}
+
+fn shake256(parts: &[&[u8]]) -> [u8; MU_SIZE] {
+    let mut hasher = Shake256::default();
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut out = [0_u8; MU_SIZE];
+    hasher.finalize_xof().read(&mut out);
+    out
+}
+
+fn invalid_length(
+    artifact: &'static str,
+    bytes: &[u8],
+    expected: usize,
+) -> DilithiumError {
+    DilithiumError::InvalidLength {
+        artifact,
+        expected,
+        actual: bytes.len(),
     }
 }
 
+/// Fixed keygen seed for the deterministic bench mode.
 pub fn default_seed() -> B32 {
     [7_u8; 32].into()
 }
 
-pub fn bench_message(size: usize) -> Vec<u8> {
-    vec![BENCH_MESSAGE_BYTE; size]
+/// Signs with an empty context. The secret key is held in seed form.
+impl Scheme for MlDsa65Scheme {
+    type PublicKey = VerifyingKey<MlDsa65>;
+    type SecretKey = SeedKeyPair;
+    type Signature = Signature<MlDsa65>;
+    type Error = DilithiumError;
+
+    fn algorithm_name(&self) -> &'static str {
+        "ML-DSA"
+    }
+
+    fn param_set_name(&self) -> &'static str {
+        match self.signing_mode {
+            SigningMode::Deterministic => "ML-DSA-65-deterministic",
+            SigningMode::Hedged => "ML-DSA-65",
+        }
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "ml-dsa"
+    }
+
+    fn sizes(&self) -> SchemeSizes {
+        SchemeSizes {
+            public_key_bytes: self.public_key_size(),
+            secret_key_bytes: self.secret_key_size(SecretKeyForm::Seed),
+            signature_bytes: self.signature_size(),
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::STATELESS
+    }
+
+    fn security(&self) -> SecurityProfile {
+        MlDsa65Scheme::security(self)
+    }
+
+    fn keypair(
+        &self,
+    ) -> Result<(Self::PublicKey, Self::SecretKey), Self::Error> {
+        let keypair = SeedKeyPair::generate()?;
+        Ok((keypair.verifying_key().clone(), keypair))
+    }
+
+    fn sign(
+        &self,
+        message: &[u8],
+        secret_key: &mut Self::SecretKey,
+    ) -> Result<Self::Signature, Self::Error> {
+        SignatureScheme::sign(self, secret_key.signing_key(), message, &[])
+    }
+
+    fn verify(
+        &self,
+        message: &[u8],
+        signature: &Self::Signature,
+        public_key: &Self::PublicKey,
+    ) -> Result<bool, Self::Error> {
+        #[cfg(feature = "op-count")]
+        zkvm::estimate(self.verify_ops(message.len()));
+        Ok(SignatureScheme::verify(
+            self,
+            public_key,
+            message,
+            &[],
+            signature,
+        ))
+    }
+
+    fn signature_len(&self, _signature: &Self::Signature) -> usize {
+        self.signature_size()
+    }
+}
+
+/// Fresh keygen seed from the OS RNG.
+pub fn random_seed() -> Result<B32, DilithiumError> {
+    let mut seed = B32::default();
+    OsRng
+        .try_fill_bytes(&mut seed)
+        .map_err(DilithiumError::Randomness)?;
+    Ok(seed)
 }
 
 pub fn signed_message_size(message_len: usize, signature_len: usize) -> usize {
     message_len.saturating_add(signature_len)
 }
 
-pub fn measure_time<T, F>(operation: F) -> (T, Duration)
-where
-    F: FnOnce() -> T,
-{
-    let start = Instant::now();
-    let value = operation();
-    (value, start.elapsed())
-}
-
 #[cfg(test)]
 mod tests {
     use super::{
-        bench_message, default_seed, signed_message_size, SignatureScheme,
-        BENCH_MESSAGE_BYTE, ML_DSA_65,
+        default_seed, signed_message_size, DilithiumError, SecretKeyForm,
+        SeedKeyPair, SignatureScheme, SigningMode, MAX_CONTEXT_SIZE, ML_DSA_65,
+        ML_DSA_65_DETERMINISTIC, MU_SIZE,
     };
-
-    #[test]
-    fn bench_message_uses_expected_fill_byte() {
-        let message = bench_message(16);
-        assert_eq!(message.len(), 16);
-        assert!(message.iter().all(|&byte| byte == BENCH_MESSAGE_BYTE));
-    }
+    use rand_core::OsRng;
 
     #[test]
     fn signed_message_size_adds_lengths() {
@@ -202,12 +671,258 @@ mod tests {
 
         let keypair = scheme.keypair(&seed);
         let signature = scheme
-            .sign(&keypair, message, context)
+            .sign(scheme.signing_key(&keypair), message, context)
+            .expect("signing should succeed");
+
+        assert!(scheme.verify(
+            scheme.verifying_key(&keypair),
+            message,
+            context,
+            &signature
+        ));
+        assert_eq!(scheme.public_key_size(), 1952);
+        assert_eq!(scheme.secret_key_size(SecretKeyForm::Expanded), 4032);
+        assert_eq!(scheme.secret_key_size(SecretKeyForm::Seed), 32);
+        assert_eq!(scheme.signature_size(), 3309);
+    }
+
+    #[test]
+    fn encoded_artifacts_roundtrip_for_verifier_only_use() {
+        let scheme = ML_DSA_65;
+        let keypair = scheme.keypair(&default_seed());
+        let message = b"dilithium-encoding";
+        let context = b"ctx";
+
+        let public_key =
+            scheme.encode_verifying_key(scheme.verifying_key(&keypair));
+        let secret_key =
+            scheme.encode_signing_key(scheme.signing_key(&keypair));
+        assert_eq!(public_key.len(), scheme.public_key_size());
+        assert_eq!(
+            secret_key.len(),
+            scheme.secret_key_size(SecretKeyForm::Expanded)
+        );
+
+        let signing_key = scheme
+            .decode_signing_key(&secret_key)
+            .expect("signing key should decode");
+        let signature = scheme
+            .sign(&signing_key, message, context)
             .expect("signing should succeed");
+        let encoded_signature = scheme.encode_signature(&signature);
+        assert_eq!(encoded_signature.len(), scheme.signature_size());
+
+        let verifying_key = scheme
+            .decode_verifying_key(&public_key)
+            .expect("verifying key should decode");
+        let decoded_signature = scheme
+            .decode_signature(&encoded_signature)
+            .expect("signature should decode");
+        assert!(scheme.verify(
+            &verifying_key,
+            message,
+            context,
+            &decoded_signature
+        ));
+        assert!(!scheme.verify(
+            &verifying_key,
+            b"other-message",
+            context,
+            &decoded_signature
+        ));
+    }
 
-        assert!(scheme.verify(&keypair, message, context, &signature));
-        assert!(scheme.public_key_size(&keypair) > 0);
-        assert!(scheme.secret_key_size(&keypair) > 0);
-        assert!(scheme.signature_size(&signature) > 0);
+    #[test]
+    fn decode_rejects_wrong_lengths() {
+        let scheme = ML_DSA_65;
+        let short = [0_u8; 16];
+
+        assert!(matches!(
+            scheme.decode_verifying_key(&short),
+            Err(DilithiumError::InvalidLength {
+                expected: 1952,
+                actual: 16,
+                ..
+            })
+        ));
+        assert!(matches!(
+            scheme.decode_signing_key(&short),
+            Err(DilithiumError::InvalidLength { .. })
+        ));
+        assert!(matches!(
+            scheme.decode_signature(&short),
+            Err(DilithiumError::InvalidLength { .. })
+        ));
+    }
+
+    #[test]
+    fn deterministic_and_hedged_modes_both_verify() {
+        let keypair = ML_DSA_65.keypair(&default_seed());
+        let signing_key = ML_DSA_65.signing_key(&keypair);
+        let verifying_key = ML_DSA_65.verifying_key(&keypair);
+        let message = b"signing-modes";
+        let context: &[u8] = &[];
+
+        assert_eq!(ML_DSA_65.signing_mode(), SigningMode::Hedged);
+        let deterministic = [(); 2].map(|_| {
+            ML_DSA_65_DETERMINISTIC
+                .sign(signing_key, message, context)
+                .expect("deterministic signing should succeed")
+        });
+        let hedged = [(); 2].map(|_| {
+            ML_DSA_65
+                .sign(signing_key, message, context)
+                .expect("hedged signing should succeed")
+        });
+        let with_rng = ML_DSA_65_DETERMINISTIC
+            .sign_with_rng(signing_key, message, context, &mut OsRng)
+            .expect("signing with a caller RNG should succeed");
+
+        assert_eq!(deterministic[0], deterministic[1]);
+        assert_ne!(hedged[0], hedged[1]);
+        assert_ne!(with_rng, deterministic[0]);
+        for signature in deterministic.iter().chain(&hedged).chain([&with_rng])
+        {
+            assert!(ML_DSA_65.verify(
+                verifying_key,
+                message,
+                context,
+                signature
+            ));
+        }
+    }
+
+    #[test]
+    fn compute_mu_uses_the_tr_embedded_in_the_signing_key() {
+        let keypair = ML_DSA_65.keypair(&default_seed());
+        let verifying_key = ML_DSA_65.verifying_key(&keypair);
+        let secret_key =
+            ML_DSA_65.encode_signing_key(ML_DSA_65.signing_key(&keypair));
+        // skEncode lays out rho (32) || K (32) || tr (64) || ...
+        let tr = &secret_key[64..64 + MU_SIZE];
+
+        let mu = ML_DSA_65
+            .compute_mu(verifying_key, b"message", b"ctx")
+            .expect("context fits");
+        let expected = super::shake256(&[tr, &[0, 3], b"ctx", b"message"]);
+
+        assert_eq!(mu, expected);
+        assert_ne!(
+            mu,
+            ML_DSA_65
+                .compute_mu(verifying_key, b"message", b"")
+                .expect("context fits")
+        );
+        assert!(matches!(
+            ML_DSA_65.compute_mu(
+                verifying_key,
+                b"message",
+                &[0; MAX_CONTEXT_SIZE + 1]
+            ),
+            Err(DilithiumError::ContextTooLong { actual: 256 })
+        ));
+    }
+
+    #[test]
+    fn sign_mu_matches_sign_and_verifies_under_plain_verify() {
+        let keypair = ML_DSA_65.keypair(&default_seed());
+        let signing_key = ML_DSA_65.signing_key(&keypair);
+        let verifying_key = ML_DSA_65.verifying_key(&keypair);
+        let mu = ML_DSA_65_DETERMINISTIC
+            .compute_mu(verifying_key, b"message", b"ctx")
+            .expect("context fits");
+
+        let signature = ML_DSA_65_DETERMINISTIC
+            .sign_mu(signing_key, &mu)
+            .expect("signing should succeed");
+        assert_eq!(
+            signature,
+            ML_DSA_65_DETERMINISTIC
+                .sign(signing_key, b"message", b"ctx")
+                .expect("signing should succeed")
+        );
+
+        let hedged = ML_DSA_65
+            .sign_mu(signing_key, &mu)
+            .expect("signing should succeed");
+        assert_ne!(hedged, signature);
+        for signature in [&signature, &hedged] {
+            assert!(ML_DSA_65.verify(
+                verifying_key,
+                b"message",
+                b"ctx",
+                signature
+            ));
+            assert!(!ML_DSA_65.verify(
+                verifying_key,
+                b"message",
+                b"",
+                signature
+            ));
+        }
+    }
+
+    #[test]
+    fn verify_mu_accepts_ordinary_signatures() {
+        let keypair = ML_DSA_65.keypair(&default_seed());
+        let signing_key = ML_DSA_65.signing_key(&keypair);
+        let verifying_key = ML_DSA_65.verifying_key(&keypair);
+        let mu = ML_DSA_65
+            .compute_mu(verifying_key, b"message", b"ctx")
+            .expect("context fits");
+        let other_mu = ML_DSA_65
+            .compute_mu(verifying_key, b"other message", b"ctx")
+            .expect("context fits");
+
+        for _ in 0..4 {
+            let signature = ML_DSA_65
+                .sign(signing_key, b"message", b"ctx")
+                .expect("signing should succeed");
+            assert!(ML_DSA_65.verify_mu(verifying_key, &mu, &signature));
+            assert!(!ML_DSA_65.verify_mu(verifying_key, &other_mu, &signature));
+        }
+
+        let other_keypair = ML_DSA_65.keypair(&Default::default());
+        let signature = ML_DSA_65
+            .sign(other_keypair.signing_key(), b"message", b"ctx")
+            .expect("signing should succeed");
+        assert!(!ML_DSA_65.verify_mu(verifying_key, &mu, &signature));
+    }
+
+    #[test]
+    fn seed_key_pair_expands_lazily_and_round_trips_through_its_seed() {
+        let keypair = SeedKeyPair::generate().expect("OS RNG should work");
+        assert!(!keypair.is_expanded());
+        assert!(!format!("{keypair:?}")
+            .contains(&format!("{:?}", keypair.seed_bytes())));
+
+        let public_key =
+            ML_DSA_65.encode_verifying_key(keypair.verifying_key());
+        assert!(keypair.is_expanded());
+
+        let restored = SeedKeyPair::from_seed_bytes(&keypair.seed_bytes())
+            .expect("seed should decode");
+        assert_eq!(
+            ML_DSA_65.encode_verifying_key(restored.verifying_key()),
+            public_key
+        );
+        assert_eq!(
+            ML_DSA_65.encode_signing_key(restored.signing_key()),
+            ML_DSA_65.encode_signing_key(keypair.signing_key())
+        );
+        assert_ne!(
+            SeedKeyPair::generate()
+                .expect("OS RNG should work")
+                .seed_bytes(),
+            keypair.seed_bytes()
+        );
+        assert!(matches!(
+            SeedKeyPair::from_seed_bytes(&[0; 31]),
+            Err(DilithiumError::InvalidLength {
+                expected: 32,
+                actual: 31,
+                ..
+            })
+        ));
     }
 }
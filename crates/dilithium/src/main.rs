@@ -1,9 +1,18 @@
 use dilithium::{
-    default_seed, measure_time, memory, signed_message_size, SignatureScheme,
-    TrackingAllocator, ML_DSA_65,
+    default_seed, random_seed, signed_message_size, SecretKeyForm,
+    SignatureScheme, ML_DSA_65, ML_DSA_65_DETERMINISTIC,
 };
+use pq_bench_core::console::{
+    memory_summary, or_exit, print_memory, print_timing,
+};
+use pq_bench_core::harness::{measure, records};
+use pq_bench_core::report::{self, OutputFormat};
+use pq_bench_core::stats::{sample, SampleConfig};
+use pq_bench_core::TrackingAllocator;
+use pq_formats::cose::{CoseAlgorithm, CoseSign1};
+use pq_formats::jose::{Jws, JwsAlgorithm};
 use std::alloc::System;
-use std::time::Duration;
+use std::env;
 
 static SYSTEM_ALLOC: System = System;
 
@@ -15,41 +24,88 @@ const MESSAGE: &[u8] =
     b"This is a test message for Dilithium signature scheme benchmarking";
 const CONTEXT: &[u8] = &[];
 
-fn print_timing(label: &str, duration: Duration) {
-    println!("Time to {label}: {duration:?}");
-    println!("Time to {label} (ns): {}", duration.as_nanos());
-}
-
 fn main() {
+    let format = or_exit(OutputFormat::from_env());
+    if format != OutputFormat::Text {
+        print_records(format);
+        return;
+    }
+    let config = or_exit(SampleConfig::from_env());
     let scheme = ML_DSA_65;
-    let seed = default_seed();
+    // DETERMINISTIC_BENCH=1 pins the keygen seed for reproducible runs.
+    let deterministic_bench = matches!(
+        env::var("DETERMINISTIC_BENCH").as_deref(),
+        Ok("1" | "true" | "TRUE" | "yes" | "YES")
+    );
+    let seed = if deterministic_bench {
+        default_seed()
+    } else {
+        random_seed().expect("OS RNG should provide a keygen seed")
+    };
     println!(
         "=== Dilithium ({}) Benchmark ===\n",
         scheme.algorithm_name()
     );
 
+    println!(
+        "Sampling: {} warmup runs, {} timed runs per operation",
+        config.warmup, config.samples
+    );
+    println!("Signing mode: {}", scheme.signing_mode().name());
+    println!(
+        "Keygen seed: {}\n",
+        if deterministic_bench {
+            "fixed"
+        } else {
+            "os-rng"
+        }
+    );
     println!("--- Key Generation ---");
-    let (keypair, keygen_duration) = measure_time(|| scheme.keypair(&seed));
-    print_timing("generate keys", keygen_duration);
+    let keygen = || {
+        let keypair = scheme.keypair(&seed);
+        scheme.verifying_key(&keypair);
+        keypair
+    };
+    let (keygen_summary, _) = sample(&config, keygen);
+    print_timing("generate keys", keygen_summary);
+    let keypair = keygen();
 
     println!("\n--- Signing ---");
-    memory::reset_peak();
-    let (signature, sign_duration) = measure_time(|| {
+    let sign = || {
         scheme
-            .sign(&keypair, MESSAGE, CONTEXT)
+            .sign(scheme.signing_key(&keypair), MESSAGE, CONTEXT)
+            .expect("signing should succeed")
+    };
+    let (sign_summary, sign_mem) = sample(&config, sign);
+    print_timing("sign", sign_summary);
+    print_memory("signing", sign_mem);
+    let signature = sign();
+
+    let deterministic = ML_DSA_65_DETERMINISTIC;
+    let (deterministic_sign_summary, _) = sample(&config, || {
+        deterministic
+            .sign(deterministic.signing_key(&keypair), MESSAGE, CONTEXT)
             .expect("signing should succeed")
     });
-    print_timing("sign", sign_duration);
-    let sign_peak_mem = memory::peak_bytes();
-    println!("Peak memory during signing: {sign_peak_mem} bytes");
+    print_timing("sign (deterministic)", deterministic_sign_summary);
+
+    // The verifier only ever sees encoded bytes, so decode them first.
+    let public_key_bytes =
+        scheme.encode_verifying_key(scheme.verifying_key(&keypair));
+    let signature_bytes = scheme.encode_signature(&signature);
+    let verifying_key = scheme
+        .decode_verifying_key(&public_key_bytes)
+        .expect("encoded verifying key should decode");
+    let signature = scheme
+        .decode_signature(&signature_bytes)
+        .expect("encoded signature should decode");
 
     println!("\n--- Verification ---");
-    memory::reset_peak();
-    let (verified, verify_duration) =
-        measure_time(|| scheme.verify(&keypair, MESSAGE, CONTEXT, &signature));
-    print_timing("verify", verify_duration);
-    let verify_peak_mem = memory::peak_bytes();
-    println!("Peak memory during verification: {verify_peak_mem} bytes");
+    let verify = || scheme.verify(&verifying_key, MESSAGE, CONTEXT, &signature);
+    let (verify_summary, verify_mem) = sample(&config, verify);
+    print_timing("verify", verify_summary);
+    print_memory("verification", verify_mem);
+    let verified = verify();
 
     if verified {
         println!("Signature verification: SUCCESS");
@@ -57,42 +113,97 @@ fn main() {
         println!("Signature verification: FAILED");
     }
 
-    let pk_size = scheme.public_key_size(&keypair);
-    let sk_size = scheme.secret_key_size(&keypair);
-    let sig_size = scheme.signature_size(&signature);
+    let pk_size = public_key_bytes.len();
+    let sk_seed_size = scheme.secret_key_size(SecretKeyForm::Seed);
+    let sk_size = scheme
+        .encode_signing_key(scheme.signing_key(&keypair))
+        .len();
+    let sig_size = signature_bytes.len();
+
+    let cose_sign1 =
+        CoseSign1::sign(CoseAlgorithm::MlDsa65, None, MESSAGE, &[], |tbs| {
+            scheme
+                .sign(scheme.signing_key(&keypair), tbs, CONTEXT)
+                .map(|signature| scheme.encode_signature(&signature))
+        })
+        .expect("COSE_Sign1 signing should succeed");
+    let cose_size = cose_sign1.to_vec().len();
+    let cose_overhead = cose_sign1.envelope_overhead();
+
+    let jws = Jws::sign(JwsAlgorithm::MlDsa65, None, MESSAGE, |input| {
+        scheme
+            .sign(scheme.signing_key(&keypair), input, CONTEXT)
+            .map(|signature| scheme.encode_signature(&signature))
+    })
+    .expect("JWS signing should succeed");
+    let jws_size = jws.to_compact().len();
+    let jws_sig_size = jws.encoded_signature_len();
+    let jws_overhead = jws.envelope_overhead();
 
     println!("\n--- Size Measurements ---");
     println!("Public key size: {pk_size} bytes");
-    println!("Secret key size: {sk_size} bytes");
+    println!("Secret key size (seed form): {sk_seed_size} bytes");
+    println!("Secret key size (expanded form): {sk_size} bytes");
     println!("Signature size: {sig_size} bytes");
     println!(
         "Signed message size: {} bytes",
         signed_message_size(MESSAGE.len(), sig_size)
     );
+    println!(
+        "COSE_Sign1 size: {cose_size} bytes (envelope overhead {cose_overhead} bytes)"
+    );
+    println!(
+        "JWS compact size: {jws_size} bytes (base64url signature {jws_sig_size} bytes, overhead {jws_overhead} bytes)"
+    );
 
     println!("\n=== Summary ===");
     println!("Algorithm: {}", scheme.algorithm_name());
-    println!("\nTiming:");
+    println!("\nTiming (median):");
     println!(
         "  Key Generation: {:?} ({} ns)",
-        keygen_duration,
-        keygen_duration.as_nanos()
+        keygen_summary.median,
+        keygen_summary.median.as_nanos()
+    );
+    println!(
+        "  Signing ({}): {:?} ({} ns)",
+        scheme.signing_mode().name(),
+        sign_summary.median,
+        sign_summary.median.as_nanos()
     );
     println!(
-        "  Signing:        {:?} ({} ns)",
-        sign_duration,
-        sign_duration.as_nanos()
+        "  Signing ({}): {:?} ({} ns)",
+        deterministic.signing_mode().name(),
+        deterministic_sign_summary.median,
+        deterministic_sign_summary.median.as_nanos()
     );
     println!(
         "  Verification:   {:?} ({} ns)",
-        verify_duration,
-        verify_duration.as_nanos()
+        verify_summary.median,
+        verify_summary.median.as_nanos()
     );
     println!("\nSizes:");
     println!("  Public Key:  {pk_size} bytes");
-    println!("  Secret Key:  {sk_size} bytes");
+    println!("  Secret Key:  {sk_seed_size} bytes (seed), {sk_size} bytes (expanded)");
     println!("  Signature:   {sig_size} bytes");
+    println!("  COSE_Sign1:  {cose_size} bytes (+{cose_overhead} envelope)");
+    println!("  JWS compact: {jws_size} bytes (+{jws_overhead} header and base64url)");
     println!("\nMemory Usage (heap allocations):");
-    println!("  Signing:      {sign_peak_mem} bytes");
-    println!("  Verification: {verify_peak_mem} bytes");
+    println!("  Signing:      {}", memory_summary(sign_mem));
+    println!("  Verification: {}", memory_summary(verify_mem));
+}
+
+/// `BENCH_FORMAT=json|csv`: records of every operation, sampled as
+/// configured by the `BENCH_*` variables, instead of the human-readable
+/// report.
+fn print_records(format: OutputFormat) {
+    let config = or_exit(SampleConfig::from_env());
+    let mut measurements = Vec::new();
+    for scheme in [&ML_DSA_65, &ML_DSA_65_DETERMINISTIC] {
+        measurements.extend(or_exit(measure(
+            scheme,
+            &[MESSAGE.len()],
+            &config,
+        )));
+    }
+    report::print(format, &records(&measurements));
 }
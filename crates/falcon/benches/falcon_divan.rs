@@ -1,7 +1,7 @@
 use divan::{black_box, AllocProfiler, Bencher};
-use falcon::{
-    bench_message, memory, signature_size, SignatureScheme, TrackingAllocator,
-    BENCH_MESSAGE_SIZES, FALCON512,
+use falcon::{signature_size, SignatureScheme, FALCON512};
+use pq_bench_core::{
+    bench_message, memory, TrackingAllocator, BENCH_MESSAGE_SIZES,
 };
 use pqcrypto_traits::sign::{PublicKey, SecretKey};
 
@@ -73,11 +73,11 @@ fn print_memory_usage() {
     for message_size in BENCH_MESSAGE_SIZES {
         let message = bench_message(message_size);
 
-        memory::reset_peak();
+        memory::reset();
         let signed_message = scheme.sign(&message, &secret_key);
         let sign_peak = memory::peak_bytes();
 
-        memory::reset_peak();
+        memory::reset();
         let _opened = scheme
             .open(&signed_message, &public_key)
             .expect("benchmark setup should verify the signed message");
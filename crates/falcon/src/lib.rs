@@ -1,87 +1,41 @@
-use pqcrypto_falcon::falcon512;
-use pqcrypto_traits::sign::{PublicKey, SecretKey, SignedMessage};
-use std::alloc::{GlobalAlloc, Layout};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::{Duration, Instant};
-
-pub const BENCH_MESSAGE_SIZES: [usize; 4] = [32, 256, 1024, 4096];
-pub const BENCH_MESSAGE_BYTE: u8 = 0x42;
-
-static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
-static PEAK_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
-static BASELINE: AtomicUsize = AtomicUsize::new(0);
-
-pub struct TrackingAllocator<A: GlobalAlloc + Sync + 'static> {
-    inner: &'static A,
-}
-
-impl<A: GlobalAlloc + Sync + 'static> TrackingAllocator<A> {
-    pub const fn new(inner: &'static A) -> Self {
-        Self { inner }
-    }
-}
-
-unsafe impl<A: GlobalAlloc + Sync + 'static> GlobalAlloc
-    for TrackingAllocator<A>
-{
-    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        let ptr = unsafe { self.inner.alloc(layout) };
-        if !ptr.is_null() {
-            track_alloc(layout.size());
-        }
-        ptr
-    }
-
-    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
-        unsafe { self.inner.dealloc(ptr, layout) };
-        track_dealloc(layout.size());
-    }
-}
+#[cfg(getrandom_backend = "custom")]
+mod seeded_rng;
 
-fn track_alloc(size: usize) {
-    let current = ALLOCATED.fetch_add(size, Ordering::SeqCst) + size;
-    let baseline = BASELINE.load(Ordering::SeqCst);
-    let relative_current = current.saturating_sub(baseline);
-    let mut peak = PEAK_ALLOCATED.load(Ordering::SeqCst);
-
-    while relative_current > peak {
-        match PEAK_ALLOCATED.compare_exchange_weak(
-            peak,
-            relative_current,
-            Ordering::SeqCst,
-            Ordering::SeqCst,
-        ) {
-            Ok(_) => break,
-            Err(observed) => peak = observed,
-        }
-    }
-}
-
-fn track_dealloc(size: usize) {
-    ALLOCATED.fetch_sub(size, Ordering::SeqCst);
-}
-
-pub mod memory {
-    use super::{Ordering, ALLOCATED, BASELINE, PEAK_ALLOCATED};
-
-    pub fn reset_peak() {
-        let current = ALLOCATED.load(Ordering::SeqCst);
-        BASELINE.store(current, Ordering::SeqCst);
-        PEAK_ALLOCATED.store(0, Ordering::SeqCst);
-    }
+#[cfg(feature = "op-count")]
+use pq_bench_core::zkvm::{self, OpCounts, SHAKE256_RATE};
+use pq_signature::{
+    AssumptionFamily, Capabilities, Document, Scheme, SchemeSizes,
+    SecurityProfile, Standardization,
+};
+use pqcrypto_falcon::falcon512;
+use pqcrypto_traits::sign::{
+    DetachedSignature, PublicKey, SecretKey, SignedMessage,
+};
+use std::error::Error;
+use std::fmt;
 
-    pub fn peak_bytes() -> usize {
-        PEAK_ALLOCATED.load(Ordering::SeqCst)
-    }
-}
+pub const SEED_SIZE: usize = 32;
 
 pub trait SignatureScheme {
     type PublicKey: PublicKey;
     type SecretKey: SecretKey;
     type SignedMessage: SignedMessage;
+    type Error;
 
     fn algorithm_name(&self) -> &'static str;
     fn keypair(&self) -> (Self::PublicKey, Self::SecretKey);
+    fn keypair_with_seed(
+        &self,
+        seed: &[u8; SEED_SIZE],
+    ) -> Result<(Self::PublicKey, Self::SecretKey), Self::Error>;
+    fn public_key_from_bytes(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Self::PublicKey, Self::Error>;
+    fn secret_key_from_bytes(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Self::SecretKey, Self::Error>;
     fn sign(
         &self,
         message: &[u8],
@@ -94,15 +48,110 @@ pub trait SignatureScheme {
     ) -> Option<Vec<u8>>;
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FalconError {
+    InvalidLength {
+        artifact: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    Malformed {
+        artifact: &'static str,
+    },
+    /// Seeded keygen needs the crate's `getrandom` hook, which is only
+    /// compiled in with `--cfg getrandom_backend="custom"`.
+    SeededKeygenUnavailable,
+}
+
+impl fmt::Display for FalconError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength {
+                artifact,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "invalid {artifact} length: expected {expected}, got {actual}"
+                )
+            }
+            Self::Malformed { artifact } => write!(f, "malformed {artifact}"),
+            Self::SeededKeygenUnavailable => write!(
+                f,
+                "seeded keygen requires building with \
+                 --cfg getrandom_backend=\"custom\""
+            ),
+        }
+    }
+}
+
+impl Error for FalconError {}
+
+impl FalconError {
+    fn from_traits(
+        artifact: &'static str,
+        err: pqcrypto_traits::Error,
+    ) -> Self {
+        match err {
+            pqcrypto_traits::Error::BadLength {
+                actual, expected, ..
+            } => Self::InvalidLength {
+                artifact,
+                expected,
+                actual,
+            },
+            _ => Self::Malformed { artifact },
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Falcon512Scheme;
 
 pub const FALCON512: Falcon512Scheme = Falcon512Scheme;
 
+impl Falcon512Scheme {
+    /// Primitive operations of one verify, as the reference implementation
+    /// runs it: the public key is moved into NTT form on every call.
+    /// Hash-to-point is assumed to squeeze the expected 1092 bytes, 546
+    /// 16-bit samples of which 512 are accepted.
+    #[cfg(feature = "op-count")]
+    pub fn verify_ops(&self, message_len: usize) -> OpCounts {
+        const N: usize = 512;
+        const NONCE_BYTES: usize = 40;
+        OpCounts::shake(NONCE_BYTES + message_len, SHAKE256_RATE, 1092)
+            // Forward NTTs of s2 and h, inverse NTT of their product.
+            + OpCounts::ntt(N, 3)
+            // h into Montgomery form, the product, the inverse scaling.
+            + OpCounts::modmuls(3 * N)
+    }
+
+    /// The round-3 submission; FN-DSA (FIPS 206) changes the encodings.
+    pub const fn security(&self) -> SecurityProfile {
+        SecurityProfile {
+            nist_category: 1,
+            classical_bits: 120,
+            quantum_bits: 108,
+            family: AssumptionFamily::Lattice,
+            assumption: "NTRU lattices (SIS over NTRU rings)",
+            standardization: Standardization::Precursor(Document::Fips(206)),
+            pitfalls: &[
+                "Signing samples discrete Gaussians with floating point; \
+                 FPU timing that depends on the operands leaks the key.",
+                "Signatures vary in length; buffers must take the maximum.",
+                "Reusing the signing salt across messages breaks the \
+                 security proof.",
+            ],
+        }
+    }
+}
+
 impl SignatureScheme for Falcon512Scheme {
     type PublicKey = falcon512::PublicKey;
     type SecretKey = falcon512::SecretKey;
     type SignedMessage = falcon512::SignedMessage;
+    type Error = FalconError;
 
     fn algorithm_name(&self) -> &'static str {
         "Falcon-512"
@@ -112,6 +161,38 @@ impl SignatureScheme for Falcon512Scheme {
         falcon512::keypair()
     }
 
+    #[cfg(getrandom_backend = "custom")]
+    fn keypair_with_seed(
+        &self,
+        seed: &[u8; SEED_SIZE],
+    ) -> Result<(Self::PublicKey, Self::SecretKey), Self::Error> {
+        Ok(seeded_rng::with_seed(seed, falcon512::keypair))
+    }
+
+    #[cfg(not(getrandom_backend = "custom"))]
+    fn keypair_with_seed(
+        &self,
+        _seed: &[u8; SEED_SIZE],
+    ) -> Result<(Self::PublicKey, Self::SecretKey), Self::Error> {
+        Err(FalconError::SeededKeygenUnavailable)
+    }
+
+    fn public_key_from_bytes(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Self::PublicKey, Self::Error> {
+        falcon512::PublicKey::from_bytes(bytes)
+            .map_err(|err| FalconError::from_traits("public key", err))
+    }
+
+    fn secret_key_from_bytes(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Self::SecretKey, Self::Error> {
+        falcon512::SecretKey::from_bytes(bytes)
+            .map_err(|err| FalconError::from_traits("secret key", err))
+    }
+
     fn sign(
         &self,
         message: &[u8],
@@ -129,8 +210,78 @@ impl SignatureScheme for Falcon512Scheme {
     }
 }
 
-pub fn bench_message(size: usize) -> Vec<u8> {
-    vec![BENCH_MESSAGE_BYTE; size]
+impl Scheme for Falcon512Scheme {
+    type PublicKey = falcon512::PublicKey;
+    type SecretKey = falcon512::SecretKey;
+    type Signature = falcon512::DetachedSignature;
+    type Error = FalconError;
+
+    fn algorithm_name(&self) -> &'static str {
+        "Falcon"
+    }
+
+    fn param_set_name(&self) -> &'static str {
+        "Falcon-512"
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "pqcrypto-falcon"
+    }
+
+    fn sizes(&self) -> SchemeSizes {
+        SchemeSizes {
+            public_key_bytes: falcon512::public_key_bytes(),
+            secret_key_bytes: falcon512::secret_key_bytes(),
+            signature_bytes: falcon512::signature_bytes(),
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::STATELESS
+    }
+
+    fn security(&self) -> SecurityProfile {
+        Falcon512Scheme::security(self)
+    }
+
+    fn keypair(
+        &self,
+    ) -> Result<(Self::PublicKey, Self::SecretKey), Self::Error> {
+        Ok(falcon512::keypair())
+    }
+
+    fn sign(
+        &self,
+        message: &[u8],
+        secret_key: &mut Self::SecretKey,
+    ) -> Result<Self::Signature, Self::Error> {
+        Ok(falcon512::detached_sign(message, secret_key))
+    }
+
+    fn verify(
+        &self,
+        message: &[u8],
+        signature: &Self::Signature,
+        public_key: &Self::PublicKey,
+    ) -> Result<bool, Self::Error> {
+        #[cfg(feature = "op-count")]
+        zkvm::estimate(self.verify_ops(message.len()));
+        Ok(
+            falcon512::verify_detached_signature(
+                signature, message, public_key,
+            )
+            .is_ok(),
+        )
+    }
+
+    fn signature_len(&self, signature: &Self::Signature) -> usize {
+        signature.as_bytes().len()
+    }
+}
+
+/// Fixed keygen seed for the deterministic bench mode.
+pub fn default_seed() -> [u8; SEED_SIZE] {
+    [7_u8; SEED_SIZE]
 }
 
 pub fn signature_size<S: SignedMessage>(
@@ -140,25 +291,12 @@ pub fn signature_size<S: SignedMessage>(
     signed_message.as_bytes().len().saturating_sub(message_len)
 }
 
-pub fn measure_time<T, F>(operation: F) -> (T, Duration)
-where
-    F: FnOnce() -> T,
-{
-    let start = Instant::now();
-    let value = operation();
-    (value, start.elapsed())
-}
-
 #[cfg(test)]
 mod tests {
-    use super::{bench_message, signature_size, BENCH_MESSAGE_BYTE};
-
-    #[test]
-    fn bench_message_uses_expected_fill_byte() {
-        let message = bench_message(16);
-        assert_eq!(message.len(), 16);
-        assert!(message.iter().all(|&byte| byte == BENCH_MESSAGE_BYTE));
-    }
+    use super::{
+        default_seed, signature_size, FalconError, SignatureScheme, FALCON512,
+    };
+    use pqcrypto_traits::sign::{PublicKey, SecretKey};
 
     #[test]
     fn signature_size_subtracts_message_length() {
@@ -179,4 +317,74 @@ mod tests {
         assert_eq!(signature_size(&signed, 10), 32);
         assert_eq!(signature_size(&signed, 100), 0);
     }
+
+    #[test]
+    fn keys_round_trip_through_bytes() {
+        let scheme = FALCON512;
+        let (public_key, secret_key) = scheme.keypair();
+
+        let restored_public = scheme
+            .public_key_from_bytes(public_key.as_bytes())
+            .expect("public key should load");
+        let restored_secret = scheme
+            .secret_key_from_bytes(secret_key.as_bytes())
+            .expect("secret key should load");
+        assert_eq!(restored_public.as_bytes(), public_key.as_bytes());
+        assert_eq!(restored_secret.as_bytes(), secret_key.as_bytes());
+
+        let signed = scheme.sign(b"falcon", &restored_secret);
+        assert_eq!(
+            scheme.open(&signed, &restored_public).as_deref(),
+            Some(b"falcon".as_slice())
+        );
+    }
+
+    #[test]
+    fn key_loading_reports_length_errors() {
+        let scheme = FALCON512;
+        assert_eq!(
+            scheme.public_key_from_bytes(&[0; 10]).err(),
+            Some(FalconError::InvalidLength {
+                artifact: "public key",
+                expected: 897,
+                actual: 10,
+            })
+        );
+        assert!(matches!(
+            scheme.secret_key_from_bytes(&[0; 10]),
+            Err(FalconError::InvalidLength {
+                artifact: "secret key",
+                expected: 1281,
+                ..
+            })
+        ));
+    }
+
+    #[cfg(getrandom_backend = "custom")]
+    #[test]
+    fn seeded_keygen_is_reproducible() {
+        let scheme = FALCON512;
+        let (first_public, first_secret) = scheme
+            .keypair_with_seed(&default_seed())
+            .expect("seeded keygen should be available");
+        let (second_public, second_secret) = scheme
+            .keypair_with_seed(&default_seed())
+            .expect("seeded keygen should be available");
+        let (other_public, _) = scheme
+            .keypair_with_seed(&[8; 32])
+            .expect("seeded keygen should be available");
+
+        assert_eq!(first_public.as_bytes(), second_public.as_bytes());
+        assert_eq!(first_secret.as_bytes(), second_secret.as_bytes());
+        assert_ne!(first_public.as_bytes(), other_public.as_bytes());
+    }
+
+    #[cfg(not(getrandom_backend = "custom"))]
+    #[test]
+    fn seeded_keygen_reports_missing_hook() {
+        assert_eq!(
+            FALCON512.keypair_with_seed(&default_seed()).err(),
+            Some(FalconError::SeededKeygenUnavailable)
+        );
+    }
 }
@@ -1,10 +1,14 @@
-use falcon::{
-    measure_time, memory, signature_size, SignatureScheme, TrackingAllocator,
-    FALCON512,
+use falcon::{default_seed, signature_size, SignatureScheme, FALCON512};
+use pq_bench_core::console::{
+    memory_summary, or_exit, print_memory, print_timing,
 };
+use pq_bench_core::harness::{measure, records};
+use pq_bench_core::report::{self, OutputFormat};
+use pq_bench_core::stats::{sample, SampleConfig};
+use pq_bench_core::TrackingAllocator;
 use pqcrypto_traits::sign::{PublicKey, SecretKey, SignedMessage};
 use std::alloc::System;
-use std::time::Duration;
+use std::env;
 
 static SYSTEM_ALLOC: System = System;
 
@@ -15,35 +19,76 @@ static GLOBAL: TrackingAllocator<System> =
 const MESSAGE: &[u8] =
     b"This is a test message for Falcon signature scheme benchmarking";
 
-fn print_timing(label: &str, duration: Duration) {
-    println!("Time to {label}: {duration:?}");
-    println!("Time to {label} (ns): {}", duration.as_nanos());
-}
-
 fn main() {
+    let format = or_exit(OutputFormat::from_env());
+    if format != OutputFormat::Text {
+        print_records(format);
+        return;
+    }
+    let config = or_exit(SampleConfig::from_env());
     let scheme = FALCON512;
     println!("=== {} Benchmark ===\n", scheme.algorithm_name());
+    println!(
+        "Sampling: {} warmup runs, {} timed runs per operation\n",
+        config.warmup, config.samples
+    );
+
+    // DETERMINISTIC_BENCH=1 pins the keygen seed for reproducible runs. A
+    // build without the seeded keygen backend falls back to the OS RNG.
+    let mut deterministic_bench = matches!(
+        env::var("DETERMINISTIC_BENCH").as_deref(),
+        Ok("1" | "true" | "TRUE" | "yes" | "YES")
+    );
+    if deterministic_bench {
+        if let Err(err) = scheme.keypair_with_seed(&default_seed()) {
+            eprintln!("warning: DETERMINISTIC_BENCH ignored: {err}");
+            deterministic_bench = false;
+        }
+    }
+    println!(
+        "Keygen seed: {}\n",
+        if deterministic_bench {
+            "fixed"
+        } else {
+            "os-rng"
+        }
+    );
 
     println!("--- Key Generation ---");
-    let ((public_key, secret_key), keygen_duration) =
-        measure_time(|| scheme.keypair());
-    print_timing("generate keys", keygen_duration);
+    let keygen = || {
+        if deterministic_bench {
+            scheme
+                .keypair_with_seed(&default_seed())
+                .expect("seeded keygen was available above")
+        } else {
+            scheme.keypair()
+        }
+    };
+    let (keygen_summary, _) = sample(&config, keygen);
+    print_timing("generate keys", keygen_summary);
+    let (public_key, secret_key) = keygen();
+
+    // Persist and reload both keys, as a deployment would.
+    let public_key = scheme
+        .public_key_from_bytes(public_key.as_bytes())
+        .expect("public key should reload");
+    let secret_key = scheme
+        .secret_key_from_bytes(secret_key.as_bytes())
+        .expect("secret key should reload");
 
     println!("\n--- Signing ---");
-    memory::reset_peak();
-    let (signed_message, sign_duration) =
-        measure_time(|| scheme.sign(MESSAGE, &secret_key));
-    print_timing("sign", sign_duration);
-    let sign_peak_mem = memory::peak_bytes();
-    println!("Peak memory during signing: {sign_peak_mem} bytes");
+    let (sign_summary, sign_mem) =
+        sample(&config, || scheme.sign(MESSAGE, &secret_key));
+    print_timing("sign", sign_summary);
+    print_memory("signing", sign_mem);
+    let signed_message = scheme.sign(MESSAGE, &secret_key);
 
     println!("\n--- Verification ---");
-    memory::reset_peak();
-    let (opened_message, verify_duration) =
-        measure_time(|| scheme.open(&signed_message, &public_key));
-    print_timing("verify", verify_duration);
-    let verify_peak_mem = memory::peak_bytes();
-    println!("Peak memory during verification: {verify_peak_mem} bytes");
+    let (verify_summary, verify_mem) =
+        sample(&config, || scheme.open(&signed_message, &public_key));
+    print_timing("verify", verify_summary);
+    print_memory("verification", verify_mem);
+    let opened_message = scheme.open(&signed_message, &public_key);
 
     match opened_message {
         Some(message) if message == MESSAGE => {
@@ -69,21 +114,21 @@ fn main() {
 
     println!("\n=== Summary ===");
     println!("Algorithm: {}", scheme.algorithm_name());
-    println!("\nTiming:");
+    println!("\nTiming (median):");
     println!(
         "  Key Generation: {:?} ({} ns)",
-        keygen_duration,
-        keygen_duration.as_nanos()
+        keygen_summary.median,
+        keygen_summary.median.as_nanos()
     );
     println!(
         "  Signing:        {:?} ({} ns)",
-        sign_duration,
-        sign_duration.as_nanos()
+        sign_summary.median,
+        sign_summary.median.as_nanos()
     );
     println!(
         "  Verification:   {:?} ({} ns)",
-        verify_duration,
-        verify_duration.as_nanos()
+        verify_summary.median,
+        verify_summary.median.as_nanos()
     );
     println!("\nSizes:");
     println!("  Public Key:  {} bytes", public_key.as_bytes().len());
@@ -93,6 +138,15 @@ fn main() {
         signature_size(&signed_message, MESSAGE.len())
     );
     println!("\nMemory Usage (heap allocations):");
-    println!("  Signing:      {sign_peak_mem} bytes");
-    println!("  Verification: {verify_peak_mem} bytes");
+    println!("  Signing:      {}", memory_summary(sign_mem));
+    println!("  Verification: {}", memory_summary(verify_mem));
+}
+
+/// `BENCH_FORMAT=json|csv`: records of every operation, sampled as
+/// configured by the `BENCH_*` variables, instead of the human-readable
+/// report.
+fn print_records(format: OutputFormat) {
+    let config = or_exit(SampleConfig::from_env());
+    let measurements = or_exit(measure(&FALCON512, &[MESSAGE.len()], &config));
+    report::print(format, &records(&measurements));
 }
@@ -0,0 +1,62 @@
+//! Reproducible randomness hook for Falcon key generation.
+//!
+//! PQClean's Falcon draws all of its randomness through `getrandom` 0.3 and
+//! takes no seed. When the crate is built with
+//! `--cfg getrandom_backend="custom"`, this module supplies that backend:
+//! inside `with_seed`, requests on the current thread are served from a
+//! SHAKE256 stream keyed by the seed, and everywhere else they fall back to
+//! the OS RNG.
+
+use rand_core::{OsRng, RngCore};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Shake256, Shake256Reader};
+use std::cell::RefCell;
+
+const DOMAIN: &[u8] = b"falcon-bench-seeded-keygen";
+
+thread_local! {
+    static STREAM: RefCell<Option<Shake256Reader>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn with_seed<T>(
+    seed: &[u8; 32],
+    operation: impl FnOnce() -> T,
+) -> T {
+    let mut hasher = Shake256::default();
+    hasher.update(DOMAIN);
+    hasher.update(seed);
+    STREAM.with(|stream| *stream.borrow_mut() = Some(hasher.finalize_xof()));
+
+    struct Reset;
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            STREAM.with(|stream| *stream.borrow_mut() = None);
+        }
+    }
+    let _reset = Reset;
+    operation()
+}
+
+/// # Safety
+///
+/// `dest` must be valid for writes of `len` bytes.
+#[no_mangle]
+unsafe extern "Rust" fn __getrandom_v03_custom(
+    dest: *mut u8,
+    len: usize,
+) -> Result<(), getrandom::Error> {
+    let buf = unsafe { std::slice::from_raw_parts_mut(dest, len) };
+    let seeded = STREAM.with(|stream| match stream.borrow_mut().as_mut() {
+        Some(reader) => {
+            reader.read(buf);
+            true
+        }
+        None => false,
+    });
+    if seeded {
+        return Ok(());
+    }
+    OsRng
+        .try_fill_bytes(buf)
+        .map_err(|_| getrandom::Error::UNEXPECTED)
+}
@@ -1,5 +1,6 @@
 use divan::Bencher;
-use lamport_ots::{seed_from_str, LamportOtsScheme};
+use lamport_ots::mss::{MssHeight, MssScheme};
+use lamport_ots::{seed_from_str, LamportVariant, LAMPORT_OTS_SCHEME};
 use std::sync::atomic::{AtomicU64, Ordering};
 
 fn main() {
@@ -8,7 +9,7 @@ fn main() {
 
 #[divan::bench]
 fn keygen(bencher: Bencher) {
-    let scheme = LamportOtsScheme;
+    let scheme = LAMPORT_OTS_SCHEME;
     static COUNTER: AtomicU64 = AtomicU64::new(0);
     let seed_base = seed_from_str("lamport-divan-keygen");
 
@@ -21,7 +22,7 @@ fn keygen(bencher: Bencher) {
 
 #[divan::bench(args = [32_usize, 1024_usize])]
 fn sign(bencher: Bencher, message_size: usize) {
-    let scheme = LamportOtsScheme;
+    let scheme = LAMPORT_OTS_SCHEME;
     let mut message = vec![0_u8; message_size];
     for (i, byte) in message.iter_mut().enumerate() {
         *byte = (i % 251) as u8;
@@ -42,7 +43,7 @@ fn sign(bencher: Bencher, message_size: usize) {
 
 #[divan::bench(args = [32_usize, 1024_usize])]
 fn verify(bencher: Bencher, message_size: usize) {
-    let scheme = LamportOtsScheme;
+    let scheme = LAMPORT_OTS_SCHEME;
     let mut message = vec![0_u8; message_size];
     for (i, byte) in message.iter_mut().enumerate() {
         *byte = (i % 251) as u8;
@@ -62,3 +63,59 @@ fn verify(bencher: Bencher, message_size: usize) {
         std::hint::black_box(is_valid);
     });
 }
+
+const MSS_SCHEME: MssScheme =
+    MssScheme::new(MssHeight::H5, LamportVariant::Plain);
+
+#[divan::bench]
+fn mss_keygen(bencher: Bencher) {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let seed_base = seed_from_str("lamport-divan-mss-keygen");
+
+    bencher.bench(|| {
+        let seed = seed_base ^ COUNTER.fetch_add(1, Ordering::Relaxed);
+        let keypair = MSS_SCHEME.keypair_with_seed(seed);
+        std::hint::black_box(keypair);
+    });
+}
+
+#[divan::bench(args = [32_usize, 1024_usize])]
+fn mss_sign(bencher: Bencher, message_size: usize) {
+    let mut message = vec![0_u8; message_size];
+    for (i, byte) in message.iter_mut().enumerate() {
+        *byte = (i % 251) as u8;
+    }
+
+    let seed = seed_from_str("lamport-divan-mss-sign");
+
+    bencher
+        .with_inputs(|| MSS_SCHEME.keypair_with_seed(seed).1)
+        .bench_local_refs(|secret_key| {
+            let signature = MSS_SCHEME
+                .sign(&message, secret_key)
+                .expect("mss sign should succeed");
+            std::hint::black_box(signature);
+        });
+}
+
+#[divan::bench(args = [32_usize, 1024_usize])]
+fn mss_verify(bencher: Bencher, message_size: usize) {
+    let mut message = vec![0_u8; message_size];
+    for (i, byte) in message.iter_mut().enumerate() {
+        *byte = (i % 251) as u8;
+    }
+
+    let (public_key, mut secret_key) =
+        MSS_SCHEME.keypair_with_seed(seed_from_str("lamport-divan-mss-verify"));
+    let signature = MSS_SCHEME
+        .sign(&message, &mut secret_key)
+        .expect("mss sign should succeed");
+
+    bencher.bench(|| {
+        let is_valid = MSS_SCHEME
+            .verify(&message, &signature, &public_key)
+            .expect("mss verify call should succeed");
+        assert!(is_valid, "mss verify must return true");
+        std::hint::black_box(is_valid);
+    });
+}
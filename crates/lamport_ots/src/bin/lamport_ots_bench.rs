@@ -1,91 +1,169 @@
-use lamport_ots::{seed_from_str, LamportOtsScheme, XorShift64};
+use lamport_ots::{
+    default_threads, seed_from_str, LamportVariant, XorShift64,
+    LAMPORT_OTS_RANDOMIZED_SCHEME, LAMPORT_OTS_SCHEME,
+};
+use pq_bench_core::memory::MemoryStats;
+use pq_bench_core::report::{self, BenchRecord, OutputFormat};
+use pq_bench_core::stats::{sample, SampleConfig, Summary};
+use pq_bench_core::TrackingAllocator;
+use pq_signature::DynScheme;
+use std::alloc::System;
 use std::env;
-use std::time::Instant;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+static SYSTEM_ALLOC: System = System;
+
+#[global_allocator]
+static GLOBAL: TrackingAllocator<System> =
+    TrackingAllocator::new(&SYSTEM_ALLOC);
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let operation =
         env::var("OPERATION").unwrap_or_else(|_| "keygen".to_owned());
     let iterations = parse_usize_env("ITERATIONS", 100)?;
     let message_size = parse_usize_env("MSG_SIZE", 32)?;
     let deterministic = parse_bool_env("DETERMINISTIC_RNG", true);
+    let randomized = parse_bool_env("RANDOMIZED", false);
+    let thread_counts = parse_threads_env("THREADS")?;
+    // BENCH_WARMUP and BENCH_OUTLIER_MAD apply; ITERATIONS sets the sample
+    // count.
+    let config = SampleConfig {
+        samples: iterations.max(1),
+        ..SampleConfig::from_env()?
+    };
+    // BENCH_FORMAT=json|csv prints one record per thread count instead.
+    let format = OutputFormat::from_env()?;
 
-    let scheme = LamportOtsScheme;
+    let (variant, scheme): (_, &dyn DynScheme) = if randomized {
+        (LamportVariant::Randomized, &LAMPORT_OTS_RANDOMIZED_SCHEME)
+    } else {
+        (LamportVariant::Plain, &LAMPORT_OTS_SCHEME)
+    };
     let mut message = vec![0_u8; message_size];
     for (i, byte) in message.iter_mut().enumerate() {
         *byte = (i % 251) as u8;
     }
 
-    let total = match operation.as_str() {
-        "keygen" => bench_keygen(scheme, iterations, deterministic)?,
-        "sign" => bench_sign(scheme, &message, iterations, deterministic)?,
-        "verify" => bench_verify(scheme, &message, iterations, deterministic)?,
-        other => {
-            return Err(format!(
-                "unsupported OPERATION={other}; expected one of: keygen, sign, verify"
-            )
-            .into())
-        }
-    };
+    let mut records = Vec::with_capacity(thread_counts.len());
+    for &threads in &thread_counts {
+        let (summary, memory) = match operation.as_str() {
+            "keygen" => bench_keygen(variant, &config, deterministic, threads),
+            "sign" => bench_sign(variant, &message, &config, deterministic)?,
+            "verify" => bench_verify(
+                variant,
+                &message,
+                &config,
+                deterministic,
+                threads,
+            )?,
+            other => {
+                return Err(format!(
+                    "unsupported OPERATION={other}; expected one of: keygen, sign, verify"
+                )
+                .into())
+            }
+        };
 
-    println!("{}", total.as_nanos());
+        if format != OutputFormat::Text {
+            records.push(
+                BenchRecord::new(
+                    scheme.algorithm_name(),
+                    scheme.param_set_name(),
+                    scheme.backend_name(),
+                    &operation,
+                    // Matches `harness::records`: keygen has no message.
+                    if operation == "keygen" {
+                        0
+                    } else {
+                        message_size
+                    },
+                    summary,
+                    memory,
+                )
+                .with_threads(threads),
+            );
+        } else if thread_counts.len() == 1 {
+            println!("{}", summary.median.as_nanos());
+        } else {
+            let throughput = if summary.mean.is_zero() {
+                0.0
+            } else {
+                1.0 / summary.mean.as_secs_f64()
+            };
+            println!(
+                "threads={threads} median_ns={} p90_ns={} p99_ns={} stddev_ns={} outliers={} throughput_ops_per_s={throughput:.3} peak_bytes={} allocations={}",
+                summary.median.as_nanos(),
+                summary.p90.as_nanos(),
+                summary.p99.as_nanos(),
+                summary.stddev.as_nanos(),
+                summary.outliers,
+                memory.peak_bytes,
+                memory.allocations
+            );
+        }
+    }
+    report::print(format, &records);
     Ok(())
 }
 
 fn bench_keygen(
-    scheme: LamportOtsScheme,
-    iterations: usize,
+    scheme: LamportVariant,
+    config: &SampleConfig,
     deterministic: bool,
-) -> Result<std::time::Duration, Box<dyn std::error::Error>> {
+    threads: usize,
+) -> (Summary, MemoryStats) {
     let mut rng = bench_rng("keygen", deterministic);
-    let start = Instant::now();
-    for _ in 0..iterations {
-        let keypair = scheme.keypair_with_rng(&mut rng);
-        std::hint::black_box(keypair);
-    }
-    Ok(start.elapsed())
+    sample(config, || {
+        scheme.keypair_with_rng_threads(&mut rng, threads)
+    })
 }
 
 fn bench_sign(
-    scheme: LamportOtsScheme,
+    scheme: LamportVariant,
     message: &[u8],
-    iterations: usize,
+    config: &SampleConfig,
     deterministic: bool,
-) -> Result<std::time::Duration, Box<dyn std::error::Error>> {
+) -> Result<(Summary, MemoryStats), Box<dyn std::error::Error>> {
+    // One-time keys: every warmup and timed run needs its own.
+    let runs = config.warmup + config.samples.max(1);
     let mut rng = bench_rng("sign-keygen", deterministic);
-    let mut secret_keys = Vec::with_capacity(iterations.max(1));
-    for _ in 0..iterations.max(1) {
+    let mut secret_keys = Vec::with_capacity(runs);
+    for _ in 0..runs {
         let (_, secret_key) = scheme.keypair_with_rng(&mut rng);
         secret_keys.push(secret_key);
     }
 
-    let start = Instant::now();
-    for secret_key in secret_keys.iter_mut().take(iterations) {
-        let signature = scheme.sign(message, secret_key)?;
-        std::hint::black_box(signature);
+    let mut secret_keys = secret_keys.iter_mut();
+    let mut failure = None;
+    let stats = sample(config, || {
+        let secret_key = secret_keys.next().expect("one key per run");
+        if let Err(err) = scheme.sign(message, secret_key) {
+            failure.get_or_insert(err);
+        }
+    });
+    match failure {
+        Some(err) => Err(err.into()),
+        None => Ok(stats),
     }
-    Ok(start.elapsed())
 }
 
 fn bench_verify(
-    scheme: LamportOtsScheme,
+    scheme: LamportVariant,
     message: &[u8],
-    iterations: usize,
+    config: &SampleConfig,
     deterministic: bool,
-) -> Result<std::time::Duration, Box<dyn std::error::Error>> {
+    threads: usize,
+) -> Result<(Summary, MemoryStats), Box<dyn std::error::Error>> {
     let mut rng = bench_rng("verify-keygen", deterministic);
     let (public_key, mut secret_key) = scheme.keypair_with_rng(&mut rng);
     let signature = scheme.sign(message, &mut secret_key)?;
-
-    let start = Instant::now();
-    for _ in 0..iterations {
-        let is_valid = scheme.verify(message, &signature, &public_key)?;
-        if !is_valid {
-            return Err("lamport verify failed during benchmark loop".into());
-        }
-        std::hint::black_box(is_valid);
+    if !scheme.verify_with_threads(message, &signature, &public_key, threads)? {
+        return Err("lamport verify failed before the benchmark loop".into());
     }
-    Ok(start.elapsed())
+
+    Ok(sample(config, || {
+        scheme.verify_with_threads(message, &signature, &public_key, threads)
+    }))
 }
 
 fn bench_rng(label: &str, deterministic: bool) -> XorShift64 {
@@ -115,6 +193,26 @@ fn parse_usize_env(
     }
 }
 
+// `THREADS=1,2,4` runs the operation once per thread count.
+fn parse_threads_env(
+    name: &str,
+) -> Result<Vec<usize>, Box<dyn std::error::Error>> {
+    let threads = match env::var(name) {
+        Ok(value) => value
+            .split(',')
+            .map(|count| count.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()?,
+        Err(_) => vec![default_threads()],
+    };
+    if !cfg!(feature = "parallel") && threads.iter().any(|&count| count > 1) {
+        return Err(format!(
+            "{name} > 1 requires building with `--features parallel`"
+        )
+        .into());
+    }
+    Ok(threads)
+}
+
 fn parse_bool_env(name: &str, default: bool) -> bool {
     match env::var(name) {
         Ok(value) => {
@@ -0,0 +1,112 @@
+use lamport_ots::forgery::RevealedSecrets;
+use lamport_ots::{seed_from_str, LamportVariant, BITS};
+use std::env;
+
+const TARGET_MESSAGE: &[u8] = b"forged target message";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let signatures = parse_usize_env("SIGNATURES", 2)?;
+    let attempts = parse_usize_env("GRIND_ATTEMPTS", 100_000)?;
+    let randomized = parse_bool_env("RANDOMIZED", false);
+
+    let scheme = if randomized {
+        LamportVariant::Randomized
+    } else {
+        LamportVariant::Plain
+    };
+
+    // Replaying the keygen seed stands in for a cloned or restored key: the
+    // API itself refuses to sign twice with one `LamportSecretKey`.
+    let seed = seed_from_str("lamport-forgery-demo");
+    let (public_key, mut secret_key) = scheme.keypair_with_seed(seed);
+    scheme.sign(b"first", &mut secret_key)?;
+    let api_reuse_rejected = scheme.sign(b"second", &mut secret_key).is_err();
+
+    let messages: Vec<Vec<u8>> = (0..signatures)
+        .map(|i| format!("reused-message-{i}").into_bytes())
+        .collect();
+    let mut reused = Vec::with_capacity(signatures);
+    for message in &messages {
+        let (_, mut secret_key) = scheme.keypair_with_seed(seed);
+        reused.push(scheme.sign(message, &mut secret_key)?);
+    }
+    let signed: Vec<_> = messages
+        .iter()
+        .map(Vec::as_slice)
+        .zip(reused.iter())
+        .collect();
+    let revealed = RevealedSecrets::collect(scheme, &public_key, &signed)?;
+
+    println!("param_set: {}", scheme.param_set_name());
+    println!("api_reuse_rejected: {api_reuse_rejected}");
+    println!("reused_signatures: {}", revealed.signatures());
+    println!("revealed_elements: {}", revealed.revealed_elements());
+    println!("free_bits: {}", revealed.free_bits());
+    match revealed.forgeable_digests_log2() {
+        Some(log2) => {
+            println!("forgeable_digests: 2^{log2}");
+            println!(
+                "forgeable_message_fraction: 2^-{}",
+                BITS.saturating_sub(log2)
+            );
+        }
+        None => println!("forgeable_digests: 0"),
+    }
+
+    let (forged, tried) = match scheme {
+        LamportVariant::Plain => grind_messages(attempts, |message| {
+            revealed.forge(scheme, &public_key, message, 0)
+        }),
+        LamportVariant::Randomized => (
+            revealed
+                .forge(scheme, &public_key, TARGET_MESSAGE, attempts as u64)
+                .map(|signature| (TARGET_MESSAGE.to_vec(), signature)),
+            attempts,
+        ),
+    };
+
+    println!("grind_attempts: {tried}");
+    match forged {
+        Some((message, signature)) => {
+            let verified = scheme.verify(&message, &signature, &public_key)?;
+            println!("forged_message: {}", String::from_utf8_lossy(&message));
+            println!("forged_signature_verifies: {verified}");
+        }
+        None => println!("forged_message: none"),
+    }
+
+    Ok(())
+}
+
+fn grind_messages<T>(
+    attempts: usize,
+    mut forge: impl FnMut(&[u8]) -> Option<T>,
+) -> (Option<(Vec<u8>, T)>, usize) {
+    for counter in 0..attempts {
+        let mut message = TARGET_MESSAGE.to_vec();
+        message.extend_from_slice(format!(" #{counter}").as_bytes());
+        if let Some(signature) = forge(&message) {
+            return (Some((message, signature)), counter + 1);
+        }
+    }
+    (None, attempts)
+}
+
+fn parse_usize_env(
+    name: &str,
+    default: usize,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    match env::var(name) {
+        Ok(value) => Ok(value.parse::<usize>()?),
+        Err(_) => Ok(default),
+    }
+}
+
+fn parse_bool_env(name: &str, default: bool) -> bool {
+    match env::var(name) {
+        Ok(value) => {
+            matches!(value.as_str(), "1" | "true" | "TRUE" | "yes" | "YES")
+        }
+        Err(_) => default,
+    }
+}
@@ -0,0 +1,267 @@
+//! Key-reuse forgery demonstrator.
+//!
+//! A Lamport key that signs more than one digest reveals both secret halves
+//! at every bit position where those digests differ. Any digest built only
+//! from revealed halves can then be signed by anyone holding the
+//! signatures. This module collects the revealed halves from reused-key
+//! signatures and forges signatures wherever they allow it.
+//!
+//! The API itself never signs twice with one key: `sign` burns the key and
+//! `LamportSecretKey` is not `Clone`, so reuse only happens outside of it,
+//! e.g. by replaying a keygen seed or restoring a key from a backup.
+//!
+//! ```compile_fail
+//! let (_, secret_key) = lamport_ots::LAMPORT_OTS_SCHEME.keypair().unwrap();
+//! let _copy = secret_key.clone();
+//! ```
+
+use crate::{
+    selected_secret_index, LamportError, LamportPublicKey, LamportSignature,
+    LamportVariant, HASH_SIZE, NONCE_SIZE, SECRET_ELEMENTS, SIGNATURE_ELEMENTS,
+};
+
+#[derive(Clone, Debug)]
+pub struct RevealedSecrets {
+    elements: Vec<Option<[u8; HASH_SIZE]>>,
+    signatures: usize,
+}
+
+impl RevealedSecrets {
+    pub fn collect(
+        variant: LamportVariant,
+        public_key: &LamportPublicKey,
+        signed: &[(&[u8], &LamportSignature)],
+    ) -> Result<Self, LamportError> {
+        let mut elements = vec![None; SECRET_ELEMENTS];
+        let mut signatures = 0;
+
+        for (message, signature) in signed {
+            if !variant.verify(message, signature, public_key)? {
+                continue;
+            }
+            let digest = variant.message_digest(
+                message,
+                public_key.key_id.as_ref(),
+                &signature.nonce,
+            )?;
+            for i in 0..SIGNATURE_ELEMENTS {
+                let idx = selected_secret_index(&digest, i);
+                elements[idx] = Some(signature.elements[i]);
+            }
+            signatures += 1;
+        }
+
+        Ok(Self {
+            elements,
+            signatures,
+        })
+    }
+
+    pub fn signatures(&self) -> usize {
+        self.signatures
+    }
+
+    pub fn revealed_elements(&self) -> usize {
+        self.elements
+            .iter()
+            .filter(|element| element.is_some())
+            .count()
+    }
+
+    /// Bit positions where both halves are known, i.e. free for the forger.
+    pub fn free_bits(&self) -> usize {
+        self.elements
+            .chunks_exact(2)
+            .filter(|pair| pair[0].is_some() && pair[1].is_some())
+            .count()
+    }
+
+    /// log2 of the number of digests the revealed halves can sign, or `None`
+    /// when some bit position has no revealed half at all.
+    pub fn forgeable_digests_log2(&self) -> Option<usize> {
+        let covered = self
+            .elements
+            .chunks_exact(2)
+            .all(|pair| pair[0].is_some() || pair[1].is_some());
+        covered.then(|| self.free_bits())
+    }
+
+    pub fn covers(&self, digest: &[u8; HASH_SIZE]) -> bool {
+        (0..SIGNATURE_ELEMENTS)
+            .all(|i| self.elements[selected_secret_index(digest, i)].is_some())
+    }
+
+    /// Forges a signature on `message` from revealed halves only.
+    ///
+    /// The plain variant has a single digest per message. The randomized
+    /// variant lets the forger pick the nonce, so up to `nonce_attempts`
+    /// nonces are tried.
+    pub fn forge(
+        &self,
+        variant: LamportVariant,
+        public_key: &LamportPublicKey,
+        message: &[u8],
+        nonce_attempts: u64,
+    ) -> Option<LamportSignature> {
+        match variant {
+            LamportVariant::Plain => {
+                self.forge_with_nonce(variant, public_key, message, None)
+            }
+            LamportVariant::Randomized => {
+                (0..nonce_attempts).find_map(|counter| {
+                    let mut nonce = [0_u8; NONCE_SIZE];
+                    nonce[..8].copy_from_slice(&counter.to_le_bytes());
+                    let nonce = Some(nonce);
+                    self.forge_with_nonce(variant, public_key, message, nonce)
+                })
+            }
+        }
+    }
+
+    fn forge_with_nonce(
+        &self,
+        variant: LamportVariant,
+        public_key: &LamportPublicKey,
+        message: &[u8],
+        nonce: Option<[u8; NONCE_SIZE]>,
+    ) -> Option<LamportSignature> {
+        let digest = variant
+            .message_digest(message, public_key.key_id.as_ref(), &nonce)
+            .ok()?;
+        let elements = (0..SIGNATURE_ELEMENTS)
+            .map(|i| self.elements[selected_secret_index(&digest, i)])
+            .collect::<Option<Vec<_>>>()?;
+        Some(LamportSignature { elements, nonce })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RevealedSecrets;
+    use crate::{hash_bytes, LamportSignature, LamportVariant, BITS};
+
+    const SEED: u64 = 42;
+
+    fn reused_signatures(
+        scheme: LamportVariant,
+        messages: &[Vec<u8>],
+    ) -> Vec<LamportSignature> {
+        messages
+            .iter()
+            .map(|message| {
+                let (_, mut secret_key) = scheme.keypair_with_seed(SEED);
+                scheme
+                    .sign(message, &mut secret_key)
+                    .expect("sign should succeed")
+            })
+            .collect()
+    }
+
+    fn indexed_messages(count: usize) -> Vec<Vec<u8>> {
+        (0..count)
+            .map(|i| format!("reused-message-{i}").into_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn two_signatures_free_the_differing_bits() {
+        let scheme = LamportVariant::Plain;
+        let (public_key, _) = scheme.keypair_with_seed(SEED);
+        let messages = indexed_messages(2);
+        let signatures = reused_signatures(scheme, &messages);
+        let signed: Vec<_> = messages
+            .iter()
+            .map(Vec::as_slice)
+            .zip(signatures.iter())
+            .collect();
+
+        let revealed = RevealedSecrets::collect(scheme, &public_key, &signed)
+            .expect("collect should succeed");
+
+        let first = hash_bytes(&messages[0]);
+        let second = hash_bytes(&messages[1]);
+        let distance: u32 = first
+            .iter()
+            .zip(second.iter())
+            .map(|(a, b)| (a ^ b).count_ones())
+            .sum();
+        assert_eq!(revealed.free_bits(), distance as usize);
+        assert_eq!(revealed.forgeable_digests_log2(), Some(distance as usize));
+        assert_eq!(revealed.revealed_elements(), BITS + distance as usize);
+        assert!(revealed.covers(&first) && revealed.covers(&second));
+    }
+
+    #[test]
+    fn many_reused_signatures_forge_a_new_message() {
+        let scheme = LamportVariant::Plain;
+        let (public_key, _) = scheme.keypair_with_seed(SEED);
+        let messages = indexed_messages(24);
+        let signatures = reused_signatures(scheme, &messages);
+        let signed: Vec<_> = messages
+            .iter()
+            .map(Vec::as_slice)
+            .zip(signatures.iter())
+            .collect();
+
+        let revealed = RevealedSecrets::collect(scheme, &public_key, &signed)
+            .expect("collect should succeed");
+        let target = b"never signed by the key owner";
+        let forged = revealed
+            .forge(scheme, &public_key, target, 0)
+            .expect("24 reused signatures should cover every bit");
+
+        assert!(scheme
+            .verify(target, &forged, &public_key)
+            .expect("verify should succeed"));
+    }
+
+    #[test]
+    fn randomized_variant_is_forged_by_grinding_nonces() {
+        let scheme = LamportVariant::Randomized;
+        let (public_key, _) = scheme.keypair_with_seed(SEED);
+        let messages = indexed_messages(12);
+        let signatures = reused_signatures(scheme, &messages);
+        let signed: Vec<_> = messages
+            .iter()
+            .map(Vec::as_slice)
+            .zip(signatures.iter())
+            .collect();
+
+        let revealed = RevealedSecrets::collect(scheme, &public_key, &signed)
+            .expect("collect should succeed");
+        let target = b"never signed by the key owner";
+        let forged = revealed
+            .forge(scheme, &public_key, target, 64)
+            .expect("a few nonces should hit a covered digest");
+
+        assert!(scheme
+            .verify(target, &forged, &public_key)
+            .expect("verify should succeed"));
+    }
+
+    #[test]
+    fn single_signature_forges_nothing_new() {
+        let scheme = LamportVariant::Plain;
+        let (public_key, mut secret_key) = scheme.keypair_with_seed(SEED);
+        let signature = scheme
+            .sign(b"only-message", &mut secret_key)
+            .expect("sign should succeed");
+        assert!(
+            scheme.sign(b"second-message", &mut secret_key).is_err(),
+            "the API must refuse to reuse the key"
+        );
+
+        let revealed = RevealedSecrets::collect(
+            scheme,
+            &public_key,
+            &[(b"only-message".as_slice(), &signature)],
+        )
+        .expect("collect should succeed");
+
+        assert_eq!(revealed.free_bits(), 0);
+        assert_eq!(revealed.forgeable_digests_log2(), Some(0));
+        assert!(revealed
+            .forge(scheme, &public_key, b"second-message", 0)
+            .is_none());
+    }
+}
@@ -0,0 +1,187 @@
+//! Entry point for zkVM guests: verifies one framed input without
+//! allocating.
+//!
+//! An input is four fields, each a little-endian `u32` length followed by
+//! that many bytes: the param-set name, the message, the encoded signature
+//! and the encoded public key. Signatures and public keys are laid out as
+//! `to_bytes` writes them and `verify_encoded` reads them, on
+//! [`LamportVariant`] and [`MssScheme`] alike.
+
+use crate::mss::{MssHeight, MssScheme};
+use crate::{LamportError, LamportVariant};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+const LENGTH_SIZE: usize = 4;
+
+/// A parameter set a guest can verify.
+#[derive(Clone, Copy, Debug)]
+pub enum Verifier {
+    Ots(LamportVariant),
+    Mss(MssScheme),
+}
+
+impl Verifier {
+    pub const ALL: [Self; 8] = [
+        Self::Ots(LamportVariant::Plain),
+        Self::Ots(LamportVariant::Randomized),
+        Self::Mss(MssScheme::new(MssHeight::H5, LamportVariant::Plain)),
+        Self::Mss(MssScheme::new(MssHeight::H10, LamportVariant::Plain)),
+        Self::Mss(MssScheme::new(MssHeight::H15, LamportVariant::Plain)),
+        Self::Mss(MssScheme::new(MssHeight::H5, LamportVariant::Randomized)),
+        Self::Mss(MssScheme::new(MssHeight::H10, LamportVariant::Randomized)),
+        Self::Mss(MssScheme::new(MssHeight::H15, LamportVariant::Randomized)),
+    ];
+
+    pub fn from_param_set_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|verifier| verifier.param_set_name() == name)
+    }
+
+    pub const fn param_set_name(&self) -> &'static str {
+        match self {
+            Self::Ots(scheme) => scheme.param_set_name(),
+            Self::Mss(scheme) => scheme.param_set_name(),
+        }
+    }
+
+    pub fn verify_encoded(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, LamportError> {
+        match self {
+            Self::Ots(scheme) => {
+                scheme.verify_encoded(message, signature, public_key)
+            }
+            Self::Mss(scheme) => {
+                scheme.verify_encoded(message, signature, public_key)
+            }
+        }
+    }
+}
+
+/// Verifies a framed input.
+pub fn verify_input(input: &[u8]) -> Result<bool, LamportError> {
+    let mut rest = input;
+    let name = next_field(&mut rest)?;
+    let message = next_field(&mut rest)?;
+    let signature = next_field(&mut rest)?;
+    let public_key = next_field(&mut rest)?;
+    if !rest.is_empty() {
+        return Err(LamportError::MalformedInput);
+    }
+
+    let verifier = core::str::from_utf8(name)
+        .ok()
+        .and_then(Verifier::from_param_set_name)
+        .ok_or(LamportError::MalformedInput)?;
+    verifier.verify_encoded(message, signature, public_key)
+}
+
+/// Frames an input for [`verify_input`].
+#[cfg(feature = "alloc")]
+pub fn encode_input(
+    param_set: &str,
+    message: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+) -> Vec<u8> {
+    let fields = [param_set.as_bytes(), message, signature, public_key];
+    let len = fields.iter().map(|field| LENGTH_SIZE + field.len()).sum();
+    let mut input = Vec::with_capacity(len);
+    for field in fields {
+        input.extend((field.len() as u32).to_le_bytes());
+        input.extend_from_slice(field);
+    }
+    input
+}
+
+fn next_field<'a>(rest: &mut &'a [u8]) -> Result<&'a [u8], LamportError> {
+    let (len, tail) = rest
+        .split_first_chunk::<LENGTH_SIZE>()
+        .ok_or(LamportError::MalformedInput)?;
+    let len = u32::from_le_bytes(*len) as usize;
+    if tail.len() < len {
+        return Err(LamportError::MalformedInput);
+    }
+    let (field, tail) = tail.split_at(len);
+    *rest = tail;
+    Ok(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_input, verify_input, Verifier};
+    use crate::mss::{MssHeight, MssScheme};
+    use crate::{LamportError, LamportVariant, LAMPORT_OTS_RANDOMIZED_SCHEME};
+
+    #[test]
+    fn framed_inputs_verify_like_the_typed_api() {
+        let ots = LAMPORT_OTS_RANDOMIZED_SCHEME;
+        let (public_key, mut secret_key) = ots.keypair_with_seed(7);
+        let signature = ots.sign(b"guest", &mut secret_key).unwrap();
+        let input = encode_input(
+            ots.param_set_name(),
+            b"guest",
+            &signature.to_bytes(),
+            &public_key.to_bytes(),
+        );
+        assert!(verify_input(&input).unwrap());
+
+        let mut tampered = signature.to_bytes();
+        tampered[100] ^= 1;
+        let input = encode_input(
+            ots.param_set_name(),
+            b"guest",
+            &tampered,
+            &public_key.to_bytes(),
+        );
+        assert!(!verify_input(&input).unwrap());
+
+        let mss = MssScheme::new(MssHeight::H5, LamportVariant::Randomized);
+        let (public_key, mut secret_key) = mss.keypair_with_seed(7);
+        mss.sign(b"first", &mut secret_key).unwrap();
+        let signature = mss.sign(b"guest", &mut secret_key).unwrap();
+        let input = encode_input(
+            mss.param_set_name(),
+            b"guest",
+            &signature.to_bytes(),
+            public_key.root(),
+        );
+        assert!(verify_input(&input).unwrap());
+        let input = encode_input(
+            mss.param_set_name(),
+            b"other",
+            &signature.to_bytes(),
+            public_key.root(),
+        );
+        assert!(!verify_input(&input).unwrap());
+    }
+
+    #[test]
+    fn malformed_inputs_are_rejected() {
+        let input = encode_input("Lamport-OTS-256", b"m", &[0; 8192], &[0; 4]);
+        assert!(matches!(
+            verify_input(&input),
+            Err(LamportError::InvalidPublicKeyLength { .. })
+        ));
+        assert!(matches!(
+            verify_input(&input[..input.len() - 1]),
+            Err(LamportError::MalformedInput)
+        ));
+        let input = encode_input("RSA-2048", b"m", &[], &[]);
+        assert!(matches!(
+            verify_input(&input),
+            Err(LamportError::MalformedInput)
+        ));
+        assert_eq!(Verifier::ALL.len(), 8);
+        for verifier in Verifier::ALL {
+            let name = verifier.param_set_name();
+            let found = Verifier::from_param_set_name(name).unwrap();
+            assert_eq!(found.param_set_name(), name);
+        }
+    }
+}
@@ -1,18 +1,83 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "alloc", feature = "verify-only")))]
+compile_error!("lamport_ots needs the `std`, `alloc` or `verify-only` feature");
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use core::error::Error;
+use core::fmt;
+#[cfg(feature = "op-count")]
+use pq_bench_core::zkvm::{self, OpCounts};
+#[cfg(feature = "std")]
+use pq_signature::{
+    AssumptionFamily, Capabilities, Scheme, SchemeSizes, SecurityProfile,
+    Standardization,
+};
+#[cfg(feature = "std")]
+use rand_core::{OsRng, RngCore};
 use sha2::{Digest, Sha256};
-use std::error::Error;
-use std::fmt;
-use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "std")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+#[cfg(feature = "std")]
+pub mod forgery;
+#[cfg(feature = "verify-only")]
+pub mod guest;
+pub mod mss;
+#[cfg(feature = "parallel")]
+mod parallel;
 
 pub const HASH_SIZE: usize = 32;
 pub const BITS: usize = HASH_SIZE * 8;
 pub const SECRET_ELEMENTS: usize = BITS * 2;
 pub const SIGNATURE_ELEMENTS: usize = BITS;
+pub const KEY_ID_SIZE: usize = 16;
+pub const NONCE_SIZE: usize = HASH_SIZE;
+
+// Message-hash domain tag, borrowed from LM-OTS (RFC 8554, D_MESG).
+const D_MESG: [u8; 2] = [0x81, 0x81];
+
+#[cfg(feature = "std")]
+const ONE_TIME_PITFALL: &str =
+    "Signing two messages with one key reveals enough secret halves to forge.";
+#[cfg(feature = "std")]
+pub(crate) const COLLISION_PITFALL: &str =
+    "Signatures over colliding digests are interchangeable.";
+#[cfg(feature = "std")]
+pub(crate) const NONCE_PITFALL: &str =
+    "A predictable nonce brings back collision attacks.";
+#[cfg(feature = "std")]
+pub(crate) const SEEDED_PITFALL: &str = "Keys from `keypair_with_seed` \
+    have only 64 bits of entropy; production keys must come from `keypair`.";
+
+/// Entropy of each secret element `keypair` draws from the operating
+/// system, in bits.
+#[cfg(feature = "std")]
+pub(crate) const KEYGEN_BITS: u16 = BITS as u16;
+
+/// An attack cost of `bits`, unless guessing the `keygen_bits` of secret
+/// key entropy is cheaper.
+#[cfg(feature = "std")]
+pub(crate) const fn keygen_capped(bits: u16, keygen_bits: u16) -> u16 {
+    if bits < keygen_bits {
+        bits
+    } else {
+        keygen_bits
+    }
+}
 
+#[cfg(feature = "alloc")]
 #[derive(Clone, Debug)]
 pub struct LamportPublicKey {
     elements: Vec<[u8; HASH_SIZE]>,
+    key_id: Option<[u8; KEY_ID_SIZE]>,
 }
 
+#[cfg(feature = "alloc")]
 impl LamportPublicKey {
     pub fn len(&self) -> usize {
         self.elements.len()
@@ -23,16 +88,31 @@ impl LamportPublicKey {
     }
 
     pub fn byte_len(&self) -> usize {
-        self.elements.len() * HASH_SIZE
+        self.elements.len() * HASH_SIZE + key_id_len(&self.key_id)
+    }
+
+    pub fn key_id(&self) -> Option<&[u8; KEY_ID_SIZE]> {
+        self.key_id.as_ref()
+    }
+
+    /// The key identifier, if any, then the public elements: the layout
+    /// `LamportOtsScheme::verify_encoded` reads.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.byte_len());
+        bytes.extend(self.key_id.iter().flatten());
+        bytes.extend(self.elements.iter().flatten());
+        bytes
     }
 }
 
-#[derive(Clone, Debug)]
+#[cfg(feature = "std")]
 pub struct LamportSecretKey {
     elements: Vec<[u8; HASH_SIZE]>,
+    key_id: Option<[u8; KEY_ID_SIZE]>,
     used: bool,
 }
 
+#[cfg(feature = "std")]
 impl LamportSecretKey {
     pub fn len(&self) -> usize {
         self.elements.len()
@@ -43,19 +123,55 @@ impl LamportSecretKey {
     }
 
     pub fn byte_len(&self) -> usize {
-        self.elements.len() * HASH_SIZE
+        self.elements.len() * HASH_SIZE + key_id_len(&self.key_id)
+    }
+
+    pub fn key_id(&self) -> Option<&[u8; KEY_ID_SIZE]> {
+        self.key_id.as_ref()
     }
 
     pub fn used(&self) -> bool {
         self.used
     }
+
+    // A used one-time key is worthless, so drop the halves never revealed.
+    fn wipe_unrevealed(&mut self, digest: &[u8; HASH_SIZE]) {
+        for i in 0..SIGNATURE_ELEMENTS {
+            let idx = selected_secret_index(digest, i) ^ 1;
+            self.elements[idx].zeroize();
+        }
+    }
 }
 
+#[cfg(feature = "std")]
+impl fmt::Debug for LamportSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LamportSecretKey")
+            .field("elements", &format_args!("[REDACTED; {}]", self.len()))
+            .field("key_id", &self.key_id)
+            .field("used", &self.used)
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for LamportSecretKey {
+    fn drop(&mut self) {
+        self.elements.zeroize();
+    }
+}
+
+#[cfg(feature = "std")]
+impl ZeroizeOnDrop for LamportSecretKey {}
+
+#[cfg(feature = "alloc")]
 #[derive(Clone, Debug)]
 pub struct LamportSignature {
     elements: Vec<[u8; HASH_SIZE]>,
+    nonce: Option<[u8; NONCE_SIZE]>,
 }
 
+#[cfg(feature = "alloc")]
 impl LamportSignature {
     pub fn len(&self) -> usize {
         self.elements.len()
@@ -66,7 +182,20 @@ impl LamportSignature {
     }
 
     pub fn byte_len(&self) -> usize {
-        self.elements.len() * HASH_SIZE
+        let nonce_len = self.nonce.map_or(0, |nonce| nonce.len());
+        self.elements.len() * HASH_SIZE + nonce_len
+    }
+
+    pub fn nonce(&self) -> Option<&[u8; NONCE_SIZE]> {
+        self.nonce.as_ref()
+    }
+
+    /// The nonce, if any, then the revealed elements.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.byte_len());
+        bytes.extend(self.nonce.iter().flatten());
+        bytes.extend(self.elements.iter().flatten());
+        bytes
     }
 }
 
@@ -77,41 +206,91 @@ pub struct LamportSizes {
     pub signature_bytes: usize,
 }
 
-#[derive(Clone, Copy, Debug, Default)]
-pub struct LamportOtsScheme;
-
-pub const LAMPORT_OTS_SCHEME: LamportOtsScheme = LamportOtsScheme;
-
-impl LamportOtsScheme {
-    pub const fn algorithm_name(&self) -> &'static str {
-        "Lamport OTS"
-    }
-
-    pub const fn backend_name(&self) -> &'static str {
-        "custom-rust-sha2"
-    }
+/// How a Lamport key hashes the message. Its methods back both
+/// [`LamportOtsScheme`] and [`LamportOtsRandomizedScheme`], and let callers
+/// such as [`mss::MssScheme`] pick the variant at runtime.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LamportVariant {
+    /// Signs `SHA-256(message)` directly; relies on collision resistance.
+    #[default]
+    Plain,
+    /// Signs `SHA-256(I || D_MESG || C || message)` with a per-key
+    /// identifier `I` and a per-signature nonce `C`, as LM-OTS does.
+    Randomized,
+}
 
+impl LamportVariant {
     pub const fn param_set_name(&self) -> &'static str {
-        "Lamport-OTS-256"
+        match self {
+            Self::Plain => "Lamport-OTS-256",
+            Self::Randomized => "Lamport-OTS-256-R",
+        }
     }
 
-    pub const fn max_signatures_per_key(&self) -> usize {
-        1
+    /// The plain variant falls to a SHA-256 collision: a pair of messages
+    /// with the same digest share a signature. Randomizing the digest per
+    /// signature leaves only (second) preimages. Either bound is capped by
+    /// the entropy `keypair` draws, [`KEYGEN_BITS`] per secret element;
+    /// keys from `keypair_with_seed` have 64 bits and are not covered.
+    #[cfg(feature = "std")]
+    pub const fn security(&self) -> SecurityProfile {
+        match self {
+            Self::Plain => SecurityProfile {
+                nist_category: 2,
+                classical_bits: keygen_capped(BITS as u16 / 2, KEYGEN_BITS),
+                quantum_bits: keygen_capped(BITS as u16 / 3, KEYGEN_BITS / 2),
+                family: AssumptionFamily::HashBased,
+                assumption: "SHA-256 collision resistance",
+                standardization: Standardization::Unstandardized,
+                pitfalls: &[
+                    ONE_TIME_PITFALL,
+                    COLLISION_PITFALL,
+                    SEEDED_PITFALL,
+                ],
+            },
+            Self::Randomized => SecurityProfile {
+                nist_category: 5,
+                classical_bits: keygen_capped(BITS as u16, KEYGEN_BITS),
+                quantum_bits: keygen_capped(BITS as u16 / 2, KEYGEN_BITS / 2),
+                family: AssumptionFamily::HashBased,
+                assumption: "SHA-256 second-preimage resistance",
+                standardization: Standardization::Unstandardized,
+                pitfalls: &[ONE_TIME_PITFALL, NONCE_PITFALL, SEEDED_PITFALL],
+            },
+        }
     }
 
     pub const fn sizes(&self) -> LamportSizes {
+        let (key_id_bytes, nonce_bytes) = match self {
+            Self::Plain => (0, 0),
+            Self::Randomized => (KEY_ID_SIZE, NONCE_SIZE),
+        };
         LamportSizes {
-            public_key_bytes: SECRET_ELEMENTS * HASH_SIZE,
-            secret_key_bytes: SECRET_ELEMENTS * HASH_SIZE,
-            signature_bytes: SIGNATURE_ELEMENTS * HASH_SIZE,
+            public_key_bytes: SECRET_ELEMENTS * HASH_SIZE + key_id_bytes,
+            secret_key_bytes: SECRET_ELEMENTS * HASH_SIZE + key_id_bytes,
+            signature_bytes: SIGNATURE_ELEMENTS * HASH_SIZE + nonce_bytes,
         }
     }
 
-    pub fn keypair(&self) -> (LamportPublicKey, LamportSecretKey) {
-        let mut rng = XorShift64::new(default_seed());
-        self.keypair_with_rng(&mut rng)
+    /// Draws the secret elements and, for the randomized variant, the key
+    /// identifier from the operating system's CSPRNG.
+    #[cfg(feature = "std")]
+    pub fn keypair(
+        &self,
+    ) -> Result<(LamportPublicKey, LamportSecretKey), LamportError> {
+        self.generate(
+            |out| {
+                OsRng
+                    .try_fill_bytes(out)
+                    .map_err(|_| LamportError::Randomness)
+            },
+            default_threads(),
+        )
     }
 
+    /// A key expanded from a 64-bit seed. Only meant for reproducible tests
+    /// and benchmarks: the seed is all the entropy the key has.
+    #[cfg(feature = "std")]
     pub fn keypair_with_seed(
         &self,
         seed: u64,
@@ -120,35 +299,119 @@ impl LamportOtsScheme {
         self.keypair_with_rng(&mut rng)
     }
 
+    /// Like `keypair_with_seed`, continuing from `rng`'s state.
+    #[cfg(feature = "std")]
     pub fn keypair_with_rng(
         &self,
         rng: &mut XorShift64,
     ) -> (LamportPublicKey, LamportSecretKey) {
-        let mut secret_elements = Vec::with_capacity(SECRET_ELEMENTS);
-        let mut public_elements = Vec::with_capacity(SECRET_ELEMENTS);
-
-        for _ in 0..SECRET_ELEMENTS {
-            let mut secret = [0_u8; HASH_SIZE];
-            rng.fill_bytes(&mut secret);
-            public_elements.push(hash_bytes(&secret));
-            secret_elements.push(secret);
+        self.keypair_with_rng_threads(rng, default_threads())
+    }
+
+    /// Like `keypair_with_rng`, hashing the secret elements on `threads`
+    /// threads. Without the `parallel` feature `threads` is ignored.
+    #[cfg(feature = "std")]
+    pub fn keypair_with_rng_threads(
+        &self,
+        rng: &mut XorShift64,
+        threads: usize,
+    ) -> (LamportPublicKey, LamportSecretKey) {
+        self.generate(
+            |out| {
+                rng.fill_bytes(out);
+                Ok(())
+            },
+            threads,
+        )
+        .expect("XorShift64 cannot fail")
+    }
+
+    #[cfg(feature = "std")]
+    fn generate(
+        &self,
+        mut fill: impl FnMut(&mut [u8]) -> Result<(), LamportError>,
+        threads: usize,
+    ) -> Result<(LamportPublicKey, LamportSecretKey), LamportError> {
+        let mut secret_elements = vec![[0_u8; HASH_SIZE]; SECRET_ELEMENTS];
+        let mut key_id = match self {
+            Self::Plain => None,
+            Self::Randomized => Some([0_u8; KEY_ID_SIZE]),
+        };
+        let filled = secret_elements
+            .iter_mut()
+            .map(|secret| secret.as_mut_slice())
+            .chain(key_id.iter_mut().map(|key_id| key_id.as_mut_slice()))
+            .try_for_each(&mut fill);
+        if let Err(err) = filled {
+            secret_elements.zeroize();
+            return Err(err);
         }
+        Ok(self.keypair_from_elements(secret_elements, key_id, threads))
+    }
 
+    /// The keypair holding `secret_elements`, with `key_id` for the
+    /// randomized variant. MSS derives both per leaf.
+    #[cfg(feature = "std")]
+    pub(crate) fn keypair_from_elements(
+        &self,
+        secret_elements: Vec<[u8; HASH_SIZE]>,
+        key_id: Option<[u8; KEY_ID_SIZE]>,
+        threads: usize,
+    ) -> (LamportPublicKey, LamportSecretKey) {
+        let public_elements = hash_elements(&secret_elements, threads);
         (
             LamportPublicKey {
                 elements: public_elements,
+                key_id,
             },
             LamportSecretKey {
                 elements: secret_elements,
+                key_id,
                 used: false,
             },
         )
     }
 
+    /// The randomized variant draws its nonce from the operating system;
+    /// the plain variant uses no randomness at all.
+    #[cfg(feature = "std")]
     pub fn sign(
         &self,
         message: &[u8],
         secret_key: &mut LamportSecretKey,
+    ) -> Result<LamportSignature, LamportError> {
+        self.sign_with_nonce(message, secret_key, || {
+            let mut nonce = [0_u8; NONCE_SIZE];
+            OsRng
+                .try_fill_bytes(&mut nonce)
+                .map_err(|_| LamportError::Randomness)?;
+            Ok(nonce)
+        })
+    }
+
+    /// Like `sign`, drawing the randomized variant's nonce from `rng`. Only
+    /// meant for reproducible tests and benchmarks: a predictable nonce
+    /// brings back collision attacks.
+    #[cfg(feature = "std")]
+    pub fn sign_with_rng(
+        &self,
+        message: &[u8],
+        secret_key: &mut LamportSecretKey,
+        rng: &mut XorShift64,
+    ) -> Result<LamportSignature, LamportError> {
+        self.sign_with_nonce(message, secret_key, || {
+            let mut nonce = [0_u8; NONCE_SIZE];
+            rng.fill_bytes(&mut nonce);
+            Ok(nonce)
+        })
+    }
+
+    #[cfg(feature = "std")]
+    fn sign_with_nonce(
+        &self,
+        message: &[u8],
+        secret_key: &mut LamportSecretKey,
+        nonce: impl FnOnce() -> Result<[u8; NONCE_SIZE], LamportError>,
     ) -> Result<LamportSignature, LamportError> {
         if secret_key.used {
             return Err(LamportError::KeyAlreadyUsed);
@@ -160,20 +423,46 @@ impl LamportOtsScheme {
             });
         }
 
-        let digest = hash_bytes(message);
+        let nonce = match self {
+            Self::Plain => None,
+            Self::Randomized => Some(nonce()?),
+        };
+        let digest =
+            self.message_digest(message, secret_key.key_id.as_ref(), &nonce)?;
         let signature = sign_digest(&digest, &secret_key.elements);
         secret_key.used = true;
+        secret_key.wipe_unrevealed(&digest);
 
         Ok(LamportSignature {
             elements: signature,
+            nonce,
         })
     }
 
+    #[cfg(feature = "alloc")]
     pub fn verify(
         &self,
         message: &[u8],
         signature: &LamportSignature,
         public_key: &LamportPublicKey,
+    ) -> Result<bool, LamportError> {
+        self.verify_with_threads(
+            message,
+            signature,
+            public_key,
+            default_threads(),
+        )
+    }
+
+    /// Like `verify`, hashing the signature elements on `threads` threads.
+    /// Without the `parallel` feature `threads` is ignored.
+    #[cfg(feature = "alloc")]
+    pub fn verify_with_threads(
+        &self,
+        message: &[u8],
+        signature: &LamportSignature,
+        public_key: &LamportPublicKey,
+        threads: usize,
     ) -> Result<bool, LamportError> {
         if signature.elements.len() != SIGNATURE_ELEMENTS {
             return Err(LamportError::InvalidSignatureLength {
@@ -188,16 +477,316 @@ impl LamportOtsScheme {
             });
         }
 
-        let digest = hash_bytes(message);
-        for i in 0..SIGNATURE_ELEMENTS {
-            let idx = selected_secret_index(&digest, i);
-            if hash_bytes(&signature.elements[i]) != public_key.elements[idx] {
-                return Ok(false);
+        let digest = self.message_digest(
+            message,
+            public_key.key_id.as_ref(),
+            &signature.nonce,
+        )?;
+        let hashes = hash_elements(&signature.elements, threads);
+        let is_valid = hashes.iter().enumerate().all(|(i, hash)| {
+            *hash == public_key.elements[selected_secret_index(&digest, i)]
+        });
+
+        Ok(is_valid)
+    }
+
+    pub(crate) fn message_digest(
+        &self,
+        message: &[u8],
+        key_id: Option<&[u8; KEY_ID_SIZE]>,
+        nonce: &Option<[u8; NONCE_SIZE]>,
+    ) -> Result<[u8; HASH_SIZE], LamportError> {
+        match (self, key_id, nonce) {
+            (Self::Plain, None, None) => Ok(hash_bytes(message)),
+            (Self::Randomized, Some(key_id), Some(nonce)) => {
+                record_sha256(
+                    KEY_ID_SIZE + D_MESG.len() + NONCE_SIZE + message.len(),
+                    1,
+                );
+                let mut hasher = Sha256::new();
+                hasher.update(key_id);
+                hasher.update(D_MESG);
+                hasher.update(nonce);
+                hasher.update(message);
+                Ok(hasher.finalize().into())
             }
+            _ => Err(LamportError::ParamSetMismatch {
+                expected: self.param_set_name(),
+            }),
         }
+    }
 
+    /// Verifies an encoded signature (the nonce, if any, then the revealed
+    /// elements) against an encoded public key (the key identifier, if any,
+    /// then the public elements) without allocating.
+    #[cfg(feature = "verify-only")]
+    pub fn verify_encoded(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, LamportError> {
+        let sizes = self.sizes();
+        if signature.len() != sizes.signature_bytes {
+            return Err(LamportError::InvalidSignatureLength {
+                expected: sizes.signature_bytes,
+                actual: signature.len(),
+            });
+        }
+        if public_key.len() != sizes.public_key_bytes {
+            return Err(LamportError::InvalidPublicKeyLength {
+                expected: sizes.public_key_bytes,
+                actual: public_key.len(),
+            });
+        }
+
+        let (nonce, signature) = self.split_tag::<NONCE_SIZE>(signature);
+        let (key_id, public_key) = self.split_tag::<KEY_ID_SIZE>(public_key);
+        let digest = self.message_digest(message, key_id.as_ref(), &nonce)?;
+        let (signature, _) = signature.as_chunks::<HASH_SIZE>();
+        let (public_key, _) = public_key.as_chunks::<HASH_SIZE>();
+
+        record_sha256(HASH_SIZE, signature.len());
+        let mut hashes = [[0_u8; HASH_SIZE]; sha256_multilane::MAX_LANES];
+        for (chunk, elements) in
+            signature.chunks(sha256_multilane::MAX_LANES).enumerate()
+        {
+            let hashes = &mut hashes[..elements.len()];
+            sha256_multilane::hash_batch(elements, hashes);
+            for (offset, hash) in hashes.iter().enumerate() {
+                let i = chunk * sha256_multilane::MAX_LANES + offset;
+                if *hash != public_key[selected_secret_index(&digest, i)] {
+                    return Ok(false);
+                }
+            }
+        }
         Ok(true)
     }
+
+    // Splits off the key identifier or nonce the randomized variant
+    // prefixes its encodings with. `bytes` has been length-checked.
+    #[cfg(feature = "verify-only")]
+    pub(crate) fn split_tag<'a, const N: usize>(
+        &self,
+        bytes: &'a [u8],
+    ) -> (Option<[u8; N]>, &'a [u8]) {
+        match self {
+            Self::Plain => (None, bytes),
+            Self::Randomized => {
+                let (tag, rest) = bytes
+                    .split_first_chunk::<N>()
+                    .expect("length checked by the caller");
+                (Some(*tag), rest)
+            }
+        }
+    }
+}
+
+// The per-variant scheme types are unit structs whose inherent methods and
+// `Scheme` impl forward to `LamportVariant`; only the variant differs.
+macro_rules! lamport_ots_scheme {
+    ($(#[$doc:meta])* $name:ident, $constant:ident, $variant:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, Default)]
+        pub struct $name;
+
+        pub const $constant: $name = $name;
+
+        impl $name {
+            pub const fn variant(&self) -> LamportVariant {
+                $variant
+            }
+
+            pub const fn algorithm_name(&self) -> &'static str {
+                "Lamport OTS"
+            }
+
+            pub const fn backend_name(&self) -> &'static str {
+                "custom-rust-sha2"
+            }
+
+            pub const fn param_set_name(&self) -> &'static str {
+                self.variant().param_set_name()
+            }
+
+            pub const fn max_signatures_per_key(&self) -> usize {
+                1
+            }
+
+            #[cfg(feature = "std")]
+            pub const fn security(&self) -> SecurityProfile {
+                self.variant().security()
+            }
+
+            pub const fn sizes(&self) -> LamportSizes {
+                self.variant().sizes()
+            }
+
+            #[cfg(feature = "std")]
+            pub fn keypair(
+                &self,
+            ) -> Result<(LamportPublicKey, LamportSecretKey), LamportError>
+            {
+                self.variant().keypair()
+            }
+
+            #[cfg(feature = "std")]
+            pub fn keypair_with_seed(
+                &self,
+                seed: u64,
+            ) -> (LamportPublicKey, LamportSecretKey) {
+                self.variant().keypair_with_seed(seed)
+            }
+
+            #[cfg(feature = "std")]
+            pub fn keypair_with_rng(
+                &self,
+                rng: &mut XorShift64,
+            ) -> (LamportPublicKey, LamportSecretKey) {
+                self.variant().keypair_with_rng(rng)
+            }
+
+            #[cfg(feature = "std")]
+            pub fn keypair_with_rng_threads(
+                &self,
+                rng: &mut XorShift64,
+                threads: usize,
+            ) -> (LamportPublicKey, LamportSecretKey) {
+                self.variant().keypair_with_rng_threads(rng, threads)
+            }
+
+            #[cfg(feature = "std")]
+            pub fn sign(
+                &self,
+                message: &[u8],
+                secret_key: &mut LamportSecretKey,
+            ) -> Result<LamportSignature, LamportError> {
+                self.variant().sign(message, secret_key)
+            }
+
+            #[cfg(feature = "alloc")]
+            pub fn verify(
+                &self,
+                message: &[u8],
+                signature: &LamportSignature,
+                public_key: &LamportPublicKey,
+            ) -> Result<bool, LamportError> {
+                self.variant().verify(message, signature, public_key)
+            }
+
+            #[cfg(feature = "alloc")]
+            pub fn verify_with_threads(
+                &self,
+                message: &[u8],
+                signature: &LamportSignature,
+                public_key: &LamportPublicKey,
+                threads: usize,
+            ) -> Result<bool, LamportError> {
+                self.variant().verify_with_threads(
+                    message, signature, public_key, threads,
+                )
+            }
+
+            #[cfg(feature = "verify-only")]
+            pub fn verify_encoded(
+                &self,
+                message: &[u8],
+                signature: &[u8],
+                public_key: &[u8],
+            ) -> Result<bool, LamportError> {
+                self.variant()
+                    .verify_encoded(message, signature, public_key)
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl Scheme for $name {
+            type PublicKey = LamportPublicKey;
+            type SecretKey = LamportSecretKey;
+            type Signature = LamportSignature;
+            type Error = LamportError;
+
+            fn algorithm_name(&self) -> &'static str {
+                $name::algorithm_name(self)
+            }
+
+            fn param_set_name(&self) -> &'static str {
+                $name::param_set_name(self)
+            }
+
+            fn backend_name(&self) -> &'static str {
+                $name::backend_name(self)
+            }
+
+            fn sizes(&self) -> SchemeSizes {
+                $name::sizes(self).into()
+            }
+
+            fn capabilities(&self) -> Capabilities {
+                Capabilities::stateful(self.max_signatures_per_key() as u64)
+            }
+
+            fn security(&self) -> SecurityProfile {
+                $name::security(self)
+            }
+
+            fn keypair(
+                &self,
+            ) -> Result<(Self::PublicKey, Self::SecretKey), Self::Error> {
+                $name::keypair(self)
+            }
+
+            fn sign(
+                &self,
+                message: &[u8],
+                secret_key: &mut Self::SecretKey,
+            ) -> Result<Self::Signature, Self::Error> {
+                $name::sign(self, message, secret_key)
+            }
+
+            fn verify(
+                &self,
+                message: &[u8],
+                signature: &Self::Signature,
+                public_key: &Self::PublicKey,
+            ) -> Result<bool, Self::Error> {
+                $name::verify(self, message, signature, public_key)
+            }
+
+            fn signature_len(&self, signature: &Self::Signature) -> usize {
+                signature.byte_len()
+            }
+        }
+    };
+}
+
+lamport_ots_scheme!(
+    /// Lamport OTS over `SHA-256(message)`: [`LamportVariant::Plain`].
+    LamportOtsScheme,
+    LAMPORT_OTS_SCHEME,
+    LamportVariant::Plain
+);
+
+lamport_ots_scheme!(
+    /// Lamport OTS over a per-key, per-signature randomized digest:
+    /// [`LamportVariant::Randomized`].
+    LamportOtsRandomizedScheme,
+    LAMPORT_OTS_RANDOMIZED_SCHEME,
+    LamportVariant::Randomized
+);
+
+impl LamportOtsRandomizedScheme {
+    /// Draws the nonce from `rng` instead of the operating system, for
+    /// reproducible tests and benchmarks.
+    #[cfg(feature = "std")]
+    pub fn sign_with_rng(
+        &self,
+        message: &[u8],
+        secret_key: &mut LamportSecretKey,
+        rng: &mut XorShift64,
+    ) -> Result<LamportSignature, LamportError> {
+        self.variant().sign_with_rng(message, secret_key, rng)
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -235,12 +824,47 @@ impl XorShift64 {
     }
 }
 
+#[cfg(feature = "std")]
+impl From<LamportSizes> for SchemeSizes {
+    fn from(sizes: LamportSizes) -> Self {
+        Self {
+            public_key_bytes: sizes.public_key_bytes,
+            secret_key_bytes: sizes.secret_key_bytes,
+            signature_bytes: sizes.signature_bytes,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum LamportError {
     KeyAlreadyUsed,
-    InvalidSecretKeyLength { expected: usize, actual: usize },
-    InvalidPublicKeyLength { expected: usize, actual: usize },
-    InvalidSignatureLength { expected: usize, actual: usize },
+    InvalidSecretKeyLength {
+        expected: usize,
+        actual: usize,
+    },
+    InvalidPublicKeyLength {
+        expected: usize,
+        actual: usize,
+    },
+    InvalidSignatureLength {
+        expected: usize,
+        actual: usize,
+    },
+    ParamSetMismatch {
+        expected: &'static str,
+    },
+    KeyExhausted {
+        max_signatures: usize,
+    },
+    InvalidAuthPathLength {
+        expected: usize,
+        actual: usize,
+    },
+    /// Guest input that is truncated, has trailing bytes or names no known
+    /// param set.
+    MalformedInput,
+    /// The operating system could not supply a nonce.
+    Randomness,
 }
 
 impl fmt::Display for LamportError {
@@ -267,6 +891,26 @@ impl fmt::Display for LamportError {
                     "invalid signature length: expected {expected}, got {actual}"
                 )
             }
+            Self::ParamSetMismatch { expected } => {
+                write!(
+                    f,
+                    "key or signature does not match param set {expected}"
+                )
+            }
+            Self::KeyExhausted { max_signatures } => {
+                write!(
+                    f,
+                    "secret key exhausted after {max_signatures} signatures"
+                )
+            }
+            Self::InvalidAuthPathLength { expected, actual } => {
+                write!(
+                    f,
+                    "invalid auth path length: expected {expected}, got {actual}"
+                )
+            }
+            Self::MalformedInput => write!(f, "malformed guest input"),
+            Self::Randomness => write!(f, "OS randomness unavailable"),
         }
     }
 }
@@ -280,15 +924,53 @@ pub fn seed_from_str(seed: &str) -> u64 {
     u64::from_le_bytes(seed_bytes)
 }
 
-fn default_seed() -> u64 {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default();
-    let pid = std::process::id() as u64;
-    (now.as_nanos() as u64) ^ (pid << 32)
+#[cfg(feature = "alloc")]
+fn key_id_len(key_id: &Option<[u8; KEY_ID_SIZE]>) -> usize {
+    key_id.map_or(0, |key_id| key_id.len())
+}
+
+/// The thread count `keypair_with_rng` and `verify` hash with, with or
+/// without the `parallel` feature. A single key hashes too few elements to
+/// pay for spawning threads, and MSS keygen would spawn them for every leaf;
+/// callers that measured a gain pass a count to the `*_with_threads`
+/// methods instead.
+pub fn default_threads() -> usize {
+    1
+}
+
+#[cfg(feature = "parallel")]
+fn hash_elements(
+    elements: &[[u8; HASH_SIZE]],
+    threads: usize,
+) -> Vec<[u8; HASH_SIZE]> {
+    // Recorded here: the worker threads have no counting scope.
+    record_sha256(HASH_SIZE, elements.len());
+    parallel::hash_elements(elements, threads)
+}
+
+#[cfg(all(feature = "alloc", not(feature = "parallel")))]
+fn hash_elements(
+    elements: &[[u8; HASH_SIZE]],
+    _threads: usize,
+) -> Vec<[u8; HASH_SIZE]> {
+    record_sha256(HASH_SIZE, elements.len());
+    let mut hashes = vec![[0_u8; HASH_SIZE]; elements.len()];
+    sha256_multilane::hash_batch(elements, &mut hashes);
+    hashes
+}
+
+// Counting is opt-in, so ordinary signing and verifying skip the
+// thread-local lookup on every hash.
+#[cfg(feature = "op-count")]
+fn record_sha256(len: usize, count: usize) {
+    zkvm::record(OpCounts::sha256(len).times(count));
 }
 
+#[cfg(not(feature = "op-count"))]
+fn record_sha256(_len: usize, _count: usize) {}
+
 fn hash_bytes(data: &[u8]) -> [u8; HASH_SIZE] {
+    record_sha256(data.len(), 1);
     let mut hasher = Sha256::new();
     hasher.update(data);
     let digest = hasher.finalize();
@@ -297,6 +979,7 @@ fn hash_bytes(data: &[u8]) -> [u8; HASH_SIZE] {
     out
 }
 
+#[cfg(feature = "std")]
 fn sign_digest(
     digest: &[u8; HASH_SIZE],
     secret_elements: &[[u8; HASH_SIZE]],
@@ -317,11 +1000,17 @@ fn selected_secret_index(digest: &[u8; HASH_SIZE], bit_index: usize) -> usize {
 
 #[cfg(test)]
 mod tests {
-    use super::{LamportOtsScheme, XorShift64};
+    use super::{
+        hash_bytes, keygen_capped, selected_secret_index, LamportError,
+        LamportVariant, XorShift64, BITS, HASH_SIZE,
+        LAMPORT_OTS_RANDOMIZED_SCHEME, LAMPORT_OTS_SCHEME, SIGNATURE_ELEMENTS,
+    };
+    #[cfg(feature = "op-count")]
+    use pq_bench_core::zkvm::{self, OpCounts};
 
     #[test]
     fn sign_and_verify_roundtrip() {
-        let scheme = LamportOtsScheme;
+        let scheme = LAMPORT_OTS_SCHEME;
         let mut rng = XorShift64::new(42);
         let message = b"lamport-roundtrip-test";
 
@@ -336,9 +1025,34 @@ mod tests {
         assert!(is_valid, "signature must verify");
     }
 
+    #[cfg(feature = "op-count")]
+    #[test]
+    fn verify_records_each_hash() {
+        let mut rng = XorShift64::new(42);
+        let message = [0x42_u8; 100];
+        for (scheme, digest_input) in [
+            (LamportVariant::Plain, message.len()),
+            (LamportVariant::Randomized, 16 + 2 + 32 + message.len()),
+        ] {
+            let (public_key, mut secret_key) =
+                scheme.keypair_with_rng(&mut rng);
+            let signature = scheme.sign(&message, &mut secret_key).unwrap();
+            let (valid, counts) = zkvm::count(|| {
+                scheme.verify(&message, &signature, &public_key)
+            });
+
+            assert!(valid.unwrap());
+            assert_eq!(
+                counts,
+                OpCounts::sha256(digest_input)
+                    + OpCounts::sha256(HASH_SIZE).times(SIGNATURE_ELEMENTS)
+            );
+        }
+    }
+
     #[test]
     fn key_reuse_is_rejected() {
-        let scheme = LamportOtsScheme;
+        let scheme = LAMPORT_OTS_SCHEME;
         let mut rng = XorShift64::new(42);
 
         let (_public_key, mut secret_key) = scheme.keypair_with_rng(&mut rng);
@@ -355,7 +1069,7 @@ mod tests {
 
     #[test]
     fn verify_rejects_other_message() {
-        let scheme = LamportOtsScheme;
+        let scheme = LAMPORT_OTS_SCHEME;
         let mut rng = XorShift64::new(42);
 
         let (public_key, mut secret_key) = scheme.keypair_with_rng(&mut rng);
@@ -368,4 +1082,160 @@ mod tests {
             .expect("verify should succeed");
         assert!(!is_valid, "different message must not verify");
     }
+
+    #[test]
+    fn secret_key_debug_is_redacted() {
+        let scheme = LAMPORT_OTS_SCHEME;
+        let mut rng = XorShift64::new(42);
+
+        let (_public_key, secret_key) = scheme.keypair_with_rng(&mut rng);
+        let rendered = format!("{secret_key:?}");
+        assert!(rendered.contains("REDACTED"));
+        assert!(
+            !rendered.contains(&format!("{:?}", secret_key.elements[0])),
+            "debug output must not leak secret bytes"
+        );
+    }
+
+    #[test]
+    fn sign_wipes_unrevealed_halves() {
+        let scheme = LAMPORT_OTS_SCHEME;
+        let mut rng = XorShift64::new(42);
+        let message = b"lamport-wipe-test";
+
+        let (_public_key, mut secret_key) = scheme.keypair_with_rng(&mut rng);
+        let signature = scheme
+            .sign(message, &mut secret_key)
+            .expect("sign should succeed");
+
+        let digest = hash_bytes(message);
+        for i in 0..SIGNATURE_ELEMENTS {
+            let revealed = selected_secret_index(&digest, i);
+            assert_eq!(
+                secret_key.elements[revealed], signature.elements[i],
+                "revealed half must be untouched"
+            );
+            assert_eq!(
+                secret_key.elements[revealed ^ 1],
+                [0_u8; HASH_SIZE],
+                "unrevealed half must be zeroized"
+            );
+        }
+    }
+
+    #[test]
+    fn randomized_sign_and_verify_roundtrip() {
+        let scheme = LAMPORT_OTS_RANDOMIZED_SCHEME;
+        let mut rng = XorShift64::new(42);
+        let message = b"lamport-randomized-roundtrip";
+
+        let (public_key, mut secret_key) = scheme.keypair_with_rng(&mut rng);
+        let signature = scheme
+            .sign_with_rng(message, &mut secret_key, &mut rng)
+            .expect("sign should succeed");
+
+        assert!(signature.nonce().is_some(), "nonce must be carried");
+        assert_eq!(signature.byte_len(), scheme.sizes().signature_bytes);
+        assert_eq!(public_key.byte_len(), scheme.sizes().public_key_bytes);
+        assert!(scheme
+            .verify(message, &signature, &public_key)
+            .expect("verify should succeed"));
+        assert!(!scheme
+            .verify(b"other-message", &signature, &public_key)
+            .expect("verify should succeed"));
+    }
+
+    #[test]
+    fn randomized_sign_draws_fresh_nonces() {
+        let scheme = LAMPORT_OTS_RANDOMIZED_SCHEME;
+        let message = b"lamport-os-nonce";
+
+        let nonces: Vec<_> = (0..2)
+            .map(|_| {
+                let (_, mut secret_key) = scheme.keypair_with_seed(42);
+                let signature = scheme
+                    .sign(message, &mut secret_key)
+                    .expect("sign should succeed");
+                *signature.nonce().expect("nonce must be carried")
+            })
+            .collect();
+        assert_ne!(nonces[0], nonces[1], "same key and message, new nonce");
+    }
+
+    #[test]
+    fn os_keys_draw_fresh_secrets_and_key_ids() {
+        let scheme = LAMPORT_OTS_RANDOMIZED_SCHEME;
+        let (first_public, _) = scheme.keypair().unwrap();
+        let (second_public, mut secret_key) = scheme.keypair().unwrap();
+        assert_ne!(first_public.key_id(), second_public.key_id());
+        assert_ne!(first_public.elements, second_public.elements);
+
+        let signature =
+            scheme.sign(b"lamport-os-key", &mut secret_key).unwrap();
+        assert!(scheme
+            .verify(b"lamport-os-key", &signature, &second_public)
+            .unwrap());
+    }
+
+    #[test]
+    fn security_is_capped_by_keygen_entropy() {
+        let plain = LamportVariant::Plain.security();
+        assert_eq!((plain.classical_bits, plain.quantum_bits), (128, 85));
+        let randomized = LamportVariant::Randomized.security();
+        assert_eq!(
+            (randomized.classical_bits, randomized.quantum_bits),
+            (256, 128)
+        );
+
+        // A 64-bit seed would cap both variants far below their hash bounds.
+        assert_eq!(keygen_capped(BITS as u16 / 2, 64), 64);
+        assert_eq!(keygen_capped(BITS as u16, 64), 64);
+    }
+
+    #[test]
+    fn randomized_digest_differs_from_plain_digest() {
+        let mut rng = XorShift64::new(42);
+        let message = b"lamport-domain-separation";
+
+        let (_, mut plain_key) = LAMPORT_OTS_SCHEME.keypair_with_rng(&mut rng);
+        let (_, mut randomized_key) =
+            LAMPORT_OTS_RANDOMIZED_SCHEME.keypair_with_rng(&mut rng);
+        let plain = LAMPORT_OTS_SCHEME
+            .sign(message, &mut plain_key)
+            .expect("plain sign should succeed");
+        let randomized = LAMPORT_OTS_RANDOMIZED_SCHEME
+            .sign(message, &mut randomized_key)
+            .expect("randomized sign should succeed");
+
+        let digest = hash_bytes(message);
+        let matches_plain_digest = (0..SIGNATURE_ELEMENTS).all(|i| {
+            let idx = selected_secret_index(&digest, i);
+            randomized.elements[i] == randomized_key.elements[idx]
+        });
+        assert!(plain.nonce().is_none());
+        assert!(!matches_plain_digest, "randomized must not sign H(m)");
+    }
+
+    #[test]
+    fn variants_reject_each_others_keys() {
+        let mut rng = XorShift64::new(42);
+        let message = b"lamport-variant-mismatch";
+
+        let (plain_public, mut plain_secret) =
+            LAMPORT_OTS_SCHEME.keypair_with_rng(&mut rng);
+        let result =
+            LAMPORT_OTS_RANDOMIZED_SCHEME.sign(message, &mut plain_secret);
+        assert!(matches!(result, Err(LamportError::ParamSetMismatch { .. })));
+        assert!(!plain_secret.used(), "failed sign must not burn the key");
+
+        let signature = LAMPORT_OTS_SCHEME
+            .sign(message, &mut plain_secret)
+            .expect("plain sign should succeed");
+        let result = LAMPORT_OTS_RANDOMIZED_SCHEME.verify(
+            message,
+            &signature,
+            &plain_public,
+        );
+        assert!(matches!(result, Err(LamportError::ParamSetMismatch { .. })));
+    }
 }
@@ -1,14 +1,49 @@
-use lamport_ots::{seed_from_str, LamportOtsScheme, XorShift64};
+use lamport_ots::{
+    seed_from_str, LamportVariant, XorShift64, LAMPORT_OTS_RANDOMIZED_SCHEME,
+    LAMPORT_OTS_SCHEME,
+};
+use pq_bench_core::harness::{measure, records};
+use pq_bench_core::memory::MemoryStats;
+use pq_bench_core::report::{self, OutputFormat};
+use pq_bench_core::stats::{sample, SampleConfig, Summary};
+use pq_bench_core::TrackingAllocator;
+use pq_signature::DynScheme;
+use std::alloc::System;
 use std::env;
-use std::time::Instant;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+static SYSTEM_ALLOC: System = System;
+
+#[global_allocator]
+static GLOBAL: TrackingAllocator<System> =
+    TrackingAllocator::new(&SYSTEM_ALLOC);
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let message_size = parse_usize_env("LAMPORT_MESSAGE_SIZE", 1024)?;
     let iterations = parse_usize_env("LAMPORT_ITERATIONS", 100)?;
     let deterministic = parse_bool_env("LAMPORT_DETERMINISTIC", true);
+    let randomized = parse_bool_env("LAMPORT_RANDOMIZED", false);
+    // BENCH_WARMUP and BENCH_OUTLIER_MAD apply; LAMPORT_ITERATIONS sets the
+    // sample count.
+    let config = SampleConfig {
+        samples: iterations.max(1),
+        ..SampleConfig::from_env()?
+    };
+
+    let (variant, scheme): (_, &dyn DynScheme) = if randomized {
+        (LamportVariant::Randomized, &LAMPORT_OTS_RANDOMIZED_SCHEME)
+    } else {
+        (LamportVariant::Plain, &LAMPORT_OTS_SCHEME)
+    };
+
+    // BENCH_FORMAT=json|csv prints records instead of `key: value` lines.
+    let format = OutputFormat::from_env()?;
+    if format != OutputFormat::Text {
+        let measurements = measure(scheme, &[message_size], &config)?;
+        report::print(format, &records(&measurements));
+        return Ok(());
+    }
 
-    let scheme = LamportOtsScheme;
     let sizes = scheme.sizes();
 
     println!("algorithm: {}", scheme.algorithm_name());
@@ -18,7 +53,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("secret_key_bytes: {}", sizes.secret_key_bytes);
     println!("signature_bytes: {}", sizes.signature_bytes);
     println!("message_size: {}", message_size);
-    println!("iterations: {}", iterations);
+    println!("iterations: {}", config.samples);
+    println!("warmup: {}", config.warmup);
     println!("deterministic_rng: {}", deterministic);
 
     let mut message = vec![0_u8; message_size];
@@ -26,91 +62,97 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         *byte = (i % 251) as u8;
     }
 
-    let keygen_elapsed = bench_keygen(scheme, iterations, deterministic)?;
-    print_stats("keygen", iterations, keygen_elapsed.as_nanos());
+    let (keygen_summary, keygen_memory) =
+        bench_keygen(variant, &config, deterministic);
+    print_stats("keygen", keygen_summary, keygen_memory);
 
-    let sign_elapsed = bench_sign(scheme, &message, iterations, deterministic)?;
-    print_stats("sign", iterations, sign_elapsed.as_nanos());
+    let (sign_summary, sign_memory) =
+        bench_sign(variant, &message, &config, deterministic)?;
+    print_stats("sign", sign_summary, sign_memory);
 
-    let verify_elapsed =
-        bench_verify(scheme, &message, iterations, deterministic)?;
-    print_stats("verify", iterations, verify_elapsed.as_nanos());
+    let (verify_summary, verify_memory) =
+        bench_verify(variant, &message, &config, deterministic)?;
+    print_stats("verify", verify_summary, verify_memory);
 
     Ok(())
 }
 
 fn bench_keygen(
-    scheme: LamportOtsScheme,
-    iterations: usize,
+    scheme: LamportVariant,
+    config: &SampleConfig,
     deterministic: bool,
-) -> Result<std::time::Duration, Box<dyn std::error::Error>> {
+) -> (Summary, MemoryStats) {
     let mut rng = bench_rng("keygen", deterministic);
-    let start = Instant::now();
-    for _ in 0..iterations {
-        let keypair = scheme.keypair_with_rng(&mut rng);
-        std::hint::black_box(keypair);
-    }
-    Ok(start.elapsed())
+    sample(config, || scheme.keypair_with_rng(&mut rng))
 }
 
 fn bench_sign(
-    scheme: LamportOtsScheme,
+    scheme: LamportVariant,
     message: &[u8],
-    iterations: usize,
+    config: &SampleConfig,
     deterministic: bool,
-) -> Result<std::time::Duration, Box<dyn std::error::Error>> {
+) -> Result<(Summary, MemoryStats), Box<dyn std::error::Error>> {
+    // One-time keys: every warmup and timed run needs its own.
+    let runs = config.warmup + config.samples.max(1);
     let mut rng = bench_rng("sign-keygen", deterministic);
-    let mut secret_keys = Vec::with_capacity(iterations.max(1));
-    for _ in 0..iterations.max(1) {
+    let mut secret_keys = Vec::with_capacity(runs);
+    for _ in 0..runs {
         let (_, secret_key) = scheme.keypair_with_rng(&mut rng);
         secret_keys.push(secret_key);
     }
 
-    let start = Instant::now();
-    for secret_key in secret_keys.iter_mut().take(iterations) {
-        let signature = scheme.sign(message, secret_key)?;
-        std::hint::black_box(signature);
+    let mut secret_keys = secret_keys.iter_mut();
+    let mut failure = None;
+    let stats = sample(config, || {
+        let secret_key = secret_keys.next().expect("one key per run");
+        if let Err(err) = scheme.sign(message, secret_key) {
+            failure.get_or_insert(err);
+        }
+    });
+    match failure {
+        Some(err) => Err(err.into()),
+        None => Ok(stats),
     }
-    Ok(start.elapsed())
 }
 
 fn bench_verify(
-    scheme: LamportOtsScheme,
+    scheme: LamportVariant,
     message: &[u8],
-    iterations: usize,
+    config: &SampleConfig,
     deterministic: bool,
-) -> Result<std::time::Duration, Box<dyn std::error::Error>> {
+) -> Result<(Summary, MemoryStats), Box<dyn std::error::Error>> {
     let mut rng = bench_rng("verify-keygen", deterministic);
     let (public_key, mut secret_key) = scheme.keypair_with_rng(&mut rng);
     let signature = scheme.sign(message, &mut secret_key)?;
-
-    let start = Instant::now();
-    for _ in 0..iterations {
-        let is_valid = scheme.verify(message, &signature, &public_key)?;
-        if !is_valid {
-            return Err("lamport verify failed during benchmark loop".into());
-        }
-        std::hint::black_box(is_valid);
+    if !scheme.verify(message, &signature, &public_key)? {
+        return Err("lamport verify failed before the benchmark loop".into());
     }
-    Ok(start.elapsed())
-}
 
-fn print_stats(operation: &str, iterations: usize, total_ns: u128) {
-    let avg_ns = if iterations == 0 {
-        0
-    } else {
-        total_ns / iterations as u128
-    };
+    Ok(sample(config, || {
+        scheme.verify(message, &signature, &public_key)
+    }))
+}
 
-    let throughput = if total_ns == 0 {
+fn print_stats(operation: &str, summary: Summary, memory: MemoryStats) {
+    let throughput = if summary.mean.is_zero() {
         0.0
     } else {
-        (iterations as f64 * 1_000_000_000.0) / total_ns as f64
+        1.0 / summary.mean.as_secs_f64()
     };
 
-    println!("{operation}_total_ns: {total_ns}");
-    println!("{operation}_avg_ns: {avg_ns}");
+    println!("{operation}_outliers: {}", summary.outliers);
+    println!("{operation}_avg_ns: {}", summary.mean.as_nanos());
+    println!("{operation}_median_ns: {}", summary.median.as_nanos());
+    println!("{operation}_p90_ns: {}", summary.p90.as_nanos());
+    println!("{operation}_p99_ns: {}", summary.p99.as_nanos());
+    println!("{operation}_stddev_ns: {}", summary.stddev.as_nanos());
     println!("{operation}_throughput_ops_per_s: {:.3}", throughput);
+    println!("{operation}_peak_bytes: {}", memory.peak_bytes);
+    println!("{operation}_allocations_per_op: {}", memory.allocations);
+    println!(
+        "{operation}_allocated_bytes_per_op: {}",
+        memory.allocated_bytes
+    );
 }
 
 fn bench_rng(label: &str, deterministic: bool) -> XorShift64 {
@@ -0,0 +1,757 @@
+//! Classic Merkle Signature Scheme (MSS) over Lamport OTS.
+//!
+//! `2^h` Lamport public keys are hashed into the leaves of a binary tree and
+//! the 32-byte root becomes the long-term public key. Each signature uses the
+//! next unused leaf and carries the leaf's Lamport signature, its Lamport
+//! public key and the authentication path up to the root. This is the
+//! construction LMS (RFC 8554) and XMSS (RFC 8391) refine.
+//!
+//! Leaf secret keys are re-derived on demand from a 32-byte master seed
+//! with the LMS pseudorandom key generation (RFC 8554 Appendix A), so the
+//! secret key only keeps the seed, the tree identifier, the next leaf index
+//! and the tree nodes.
+
+#[cfg(feature = "std")]
+use crate::{
+    keygen_capped, LamportSecretKey, XorShift64, COLLISION_PITFALL,
+    NONCE_PITFALL, SEEDED_PITFALL,
+};
+use crate::{
+    record_sha256, LamportError, LamportSizes, LamportVariant, HASH_SIZE,
+    KEY_ID_SIZE, LAMPORT_OTS_SCHEME, NONCE_SIZE, SECRET_ELEMENTS,
+    SIGNATURE_ELEMENTS,
+};
+#[cfg(feature = "alloc")]
+use crate::{LamportPublicKey, LamportSignature};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use pq_signature::{Capabilities, Scheme, SchemeSizes, SecurityProfile};
+#[cfg(feature = "std")]
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(feature = "std")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+// Node hashing domain tags, borrowed from LMS (RFC 8554, D_LEAF/D_INTR).
+const D_LEAF: [u8; 2] = [0x82, 0x82];
+const D_INTR: [u8; 2] = [0x83, 0x83];
+
+const LEAF_INDEX_SIZE: usize = 4;
+pub const SEED_SIZE: usize = 32;
+#[cfg(feature = "std")]
+const SEED_BITS: u16 = (SEED_SIZE * 8) as u16;
+
+#[cfg(feature = "std")]
+const STATE_PITFALL: &str = "The leaf index must be persisted before a \
+    signature is released; restoring an old secret key reuses one-time keys.";
+#[cfg(feature = "std")]
+const EXHAUSTION_PITFALL: &str =
+    "Keys run out after 2^h signatures and cannot be extended.";
+#[cfg(feature = "std")]
+const TREE_COLLISION_PITFALL: &str = "Tree hashes carry no key identifier \
+    or node index, so a SHA-256 collision between two nodes forges a path.";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MssHeight {
+    H5,
+    #[default]
+    H10,
+    H15,
+}
+
+impl MssHeight {
+    pub const fn height(&self) -> usize {
+        match self {
+            Self::H5 => 5,
+            Self::H10 => 10,
+            Self::H15 => 15,
+        }
+    }
+
+    pub const fn leaves(&self) -> usize {
+        1 << self.height()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MssPublicKey {
+    root: [u8; HASH_SIZE],
+}
+
+impl MssPublicKey {
+    pub fn root(&self) -> &[u8; HASH_SIZE] {
+        &self.root
+    }
+
+    pub fn byte_len(&self) -> usize {
+        self.root.len()
+    }
+}
+
+#[cfg(feature = "std")]
+pub struct MssSecretKey {
+    seed: [u8; SEED_SIZE],
+    /// The tree identifier `I`, also the key identifier of randomized
+    /// leaves.
+    key_id: [u8; KEY_ID_SIZE],
+    next_leaf: usize,
+    nodes: Vec<[u8; HASH_SIZE]>,
+}
+
+#[cfg(feature = "std")]
+impl MssSecretKey {
+    pub fn next_leaf(&self) -> usize {
+        self.next_leaf
+    }
+
+    pub fn remaining_signatures(&self) -> usize {
+        (self.nodes.len() / 2).saturating_sub(self.next_leaf)
+    }
+
+    pub fn key_id(&self) -> &[u8; KEY_ID_SIZE] {
+        &self.key_id
+    }
+
+    /// The persisted size: the master seed, the tree identifier and the
+    /// next leaf index. The tree is re-derived from them when the key is
+    /// loaded.
+    pub fn byte_len(&self) -> usize {
+        SEED_SIZE + KEY_ID_SIZE + LEAF_INDEX_SIZE
+    }
+
+    /// The in-memory size, including the `2 * 2^h` cached tree nodes.
+    pub fn resident_byte_len(&self) -> usize {
+        self.byte_len() + self.nodes.len() * HASH_SIZE
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for MssSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MssSecretKey")
+            .field("seed", &format_args!("REDACTED"))
+            .field("key_id", &self.key_id)
+            .field("next_leaf", &self.next_leaf)
+            .field("leaves", &(self.nodes.len() / 2))
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for MssSecretKey {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+    }
+}
+
+#[cfg(feature = "std")]
+impl ZeroizeOnDrop for MssSecretKey {}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct MssSignature {
+    leaf_index: usize,
+    ots_signature: LamportSignature,
+    ots_public_key: LamportPublicKey,
+    auth_path: Vec<[u8; HASH_SIZE]>,
+}
+
+#[cfg(feature = "alloc")]
+impl MssSignature {
+    pub fn leaf_index(&self) -> usize {
+        self.leaf_index
+    }
+
+    pub fn auth_path(&self) -> &[[u8; HASH_SIZE]] {
+        &self.auth_path
+    }
+
+    pub fn byte_len(&self) -> usize {
+        LEAF_INDEX_SIZE
+            + self.ots_signature.byte_len()
+            + self.ots_public_key.byte_len()
+            + self.auth_path.len() * HASH_SIZE
+    }
+
+    /// The big-endian leaf index, the encoded one-time signature and public
+    /// key, then the authentication path.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.byte_len());
+        bytes.extend((self.leaf_index as u32).to_be_bytes());
+        bytes.extend(self.ots_signature.to_bytes());
+        bytes.extend(self.ots_public_key.to_bytes());
+        bytes.extend(self.auth_path.iter().flatten());
+        bytes
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MssScheme {
+    height: MssHeight,
+    ots: LamportVariant,
+}
+
+pub const MSS_LAMPORT_H10: MssScheme =
+    MssScheme::new(MssHeight::H10, LamportVariant::Plain);
+
+impl MssScheme {
+    pub const fn new(height: MssHeight, ots: LamportVariant) -> Self {
+        Self { height, ots }
+    }
+
+    pub const fn height(&self) -> MssHeight {
+        self.height
+    }
+
+    pub const fn ots(&self) -> LamportVariant {
+        self.ots
+    }
+
+    pub const fn algorithm_name(&self) -> &'static str {
+        "Merkle Signature Scheme"
+    }
+
+    pub const fn backend_name(&self) -> &'static str {
+        LAMPORT_OTS_SCHEME.backend_name()
+    }
+
+    pub const fn param_set_name(&self) -> &'static str {
+        match (self.ots, self.height) {
+            (LamportVariant::Plain, MssHeight::H5) => "MSS-Lamport-256-H5",
+            (LamportVariant::Plain, MssHeight::H10) => "MSS-Lamport-256-H10",
+            (LamportVariant::Plain, MssHeight::H15) => "MSS-Lamport-256-H15",
+            (LamportVariant::Randomized, MssHeight::H5) => {
+                "MSS-Lamport-256-R-H5"
+            }
+            (LamportVariant::Randomized, MssHeight::H10) => {
+                "MSS-Lamport-256-R-H10"
+            }
+            (LamportVariant::Randomized, MssHeight::H15) => {
+                "MSS-Lamport-256-R-H15"
+            }
+        }
+    }
+
+    pub const fn max_signatures_per_key(&self) -> usize {
+        self.height.leaves()
+    }
+
+    /// SHA-256 collision resistance for either leaf variant. The leaf and
+    /// interior hashes are not tweaked with a per-key identifier and node
+    /// index as in LMS, so a collision in one of them forges a path, and
+    /// randomized leaves do not lift the tree above category 2. Every leaf
+    /// secret derives from the [`SEED_SIZE`]-byte seed, so its entropy
+    /// caps the bounds as well.
+    #[cfg(feature = "std")]
+    pub const fn security(&self) -> SecurityProfile {
+        let pitfalls: &[&str] = match self.ots {
+            LamportVariant::Plain => &[
+                STATE_PITFALL,
+                EXHAUSTION_PITFALL,
+                COLLISION_PITFALL,
+                TREE_COLLISION_PITFALL,
+                SEEDED_PITFALL,
+            ],
+            LamportVariant::Randomized => &[
+                STATE_PITFALL,
+                EXHAUSTION_PITFALL,
+                NONCE_PITFALL,
+                TREE_COLLISION_PITFALL,
+                SEEDED_PITFALL,
+            ],
+        };
+        let leaf = LamportVariant::Plain.security();
+        SecurityProfile {
+            classical_bits: keygen_capped(leaf.classical_bits, SEED_BITS),
+            quantum_bits: keygen_capped(leaf.quantum_bits, SEED_BITS / 2),
+            pitfalls,
+            ..leaf
+        }
+    }
+
+    pub const fn sizes(&self) -> LamportSizes {
+        let (key_id_bytes, nonce_bytes) = match self.ots {
+            LamportVariant::Plain => (0, 0),
+            LamportVariant::Randomized => (KEY_ID_SIZE, NONCE_SIZE),
+        };
+        LamportSizes {
+            public_key_bytes: HASH_SIZE,
+            secret_key_bytes: SEED_SIZE + KEY_ID_SIZE + LEAF_INDEX_SIZE,
+            signature_bytes: LEAF_INDEX_SIZE
+                + SIGNATURE_ELEMENTS * HASH_SIZE
+                + nonce_bytes
+                + SECRET_ELEMENTS * HASH_SIZE
+                + key_id_bytes
+                + self.height.height() * HASH_SIZE,
+        }
+    }
+
+    /// Draws the master seed and tree identifier from the operating
+    /// system's CSPRNG.
+    #[cfg(feature = "std")]
+    pub fn keypair(
+        &self,
+    ) -> Result<(MssPublicKey, MssSecretKey), LamportError> {
+        let mut seed = [0_u8; SEED_SIZE];
+        let mut key_id = [0_u8; KEY_ID_SIZE];
+        if OsRng.try_fill_bytes(&mut seed).is_err()
+            || OsRng.try_fill_bytes(&mut key_id).is_err()
+        {
+            seed.zeroize();
+            return Err(LamportError::Randomness);
+        }
+        Ok(self.keypair_from_seed(seed, key_id))
+    }
+
+    /// A tree whose seed and identifier are expanded from a 64-bit seed.
+    /// Only meant for reproducible tests and benchmarks: the seed is all
+    /// the entropy the key has.
+    #[cfg(feature = "std")]
+    pub fn keypair_with_seed(&self, seed: u64) -> (MssPublicKey, MssSecretKey) {
+        let mut rng = XorShift64::new(seed);
+        let mut master_seed = [0_u8; SEED_SIZE];
+        let mut key_id = [0_u8; KEY_ID_SIZE];
+        rng.fill_bytes(&mut master_seed);
+        rng.fill_bytes(&mut key_id);
+        self.keypair_from_seed(master_seed, key_id)
+    }
+
+    /// The tree for a master seed and tree identifier, as [`Self::keypair`]
+    /// draws them. The secret key takes ownership of `seed`.
+    #[cfg(feature = "std")]
+    pub fn keypair_from_seed(
+        &self,
+        seed: [u8; SEED_SIZE],
+        key_id: [u8; KEY_ID_SIZE],
+    ) -> (MssPublicKey, MssSecretKey) {
+        let leaves = self.height.leaves();
+        let mut nodes = vec![[0_u8; HASH_SIZE]; 2 * leaves];
+        let mut secret_key = MssSecretKey {
+            seed,
+            key_id,
+            next_leaf: 0,
+            nodes: Vec::new(),
+        };
+
+        for leaf in 0..leaves {
+            let (public_key, _) = secret_key.leaf_keypair(self.ots, leaf);
+            nodes[leaves + leaf] = leaf_hash(
+                leaf,
+                public_key.key_id.as_ref(),
+                &public_key.elements,
+            );
+        }
+        for node in (1..leaves).rev() {
+            nodes[node] = interior_hash(&nodes[2 * node], &nodes[2 * node + 1]);
+        }
+
+        let public_key = MssPublicKey { root: nodes[1] };
+        secret_key.nodes = nodes;
+        (public_key, secret_key)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn sign(
+        &self,
+        message: &[u8],
+        secret_key: &mut MssSecretKey,
+    ) -> Result<MssSignature, LamportError> {
+        let leaves = self.height.leaves();
+        if secret_key.nodes.len() != 2 * leaves {
+            return Err(LamportError::InvalidSecretKeyLength {
+                expected: 2 * leaves,
+                actual: secret_key.nodes.len(),
+            });
+        }
+        let leaf_index = secret_key.next_leaf;
+        if leaf_index >= leaves {
+            return Err(LamportError::KeyExhausted {
+                max_signatures: leaves,
+            });
+        }
+
+        // Advance the state before signing so a failure never hands out the
+        // same leaf twice.
+        secret_key.next_leaf += 1;
+        let (ots_public_key, mut ots_secret_key) =
+            secret_key.leaf_keypair(self.ots, leaf_index);
+        let ots_signature = self.ots.sign(message, &mut ots_secret_key)?;
+
+        let mut auth_path = Vec::with_capacity(self.height.height());
+        let mut node = leaves + leaf_index;
+        while node > 1 {
+            auth_path.push(secret_key.nodes[node ^ 1]);
+            node /= 2;
+        }
+
+        Ok(MssSignature {
+            leaf_index,
+            ots_signature,
+            ots_public_key,
+            auth_path,
+        })
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn verify(
+        &self,
+        message: &[u8],
+        signature: &MssSignature,
+        public_key: &MssPublicKey,
+    ) -> Result<bool, LamportError> {
+        let height = self.height.height();
+        if signature.auth_path.len() != height {
+            return Err(LamportError::InvalidAuthPathLength {
+                expected: height,
+                actual: signature.auth_path.len(),
+            });
+        }
+        if signature.leaf_index >= self.height.leaves() {
+            return Ok(false);
+        }
+        if !self.ots.verify(
+            message,
+            &signature.ots_signature,
+            &signature.ots_public_key,
+        )? {
+            return Ok(false);
+        }
+
+        let ots_public_key = &signature.ots_public_key;
+        let leaf = leaf_hash(
+            signature.leaf_index,
+            ots_public_key.key_id.as_ref(),
+            &ots_public_key.elements,
+        );
+        Ok(self.root_from_leaf(
+            signature.leaf_index,
+            leaf,
+            &signature.auth_path,
+        ) == public_key.root)
+    }
+
+    /// Verifies an encoded signature (as [`MssSignature::to_bytes`] lays it
+    /// out) against the encoded root without allocating.
+    #[cfg(feature = "verify-only")]
+    pub fn verify_encoded(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, LamportError> {
+        let sizes = self.sizes();
+        if signature.len() != sizes.signature_bytes {
+            return Err(LamportError::InvalidSignatureLength {
+                expected: sizes.signature_bytes,
+                actual: signature.len(),
+            });
+        }
+        let Ok(root) = <&[u8; HASH_SIZE]>::try_from(public_key) else {
+            return Err(LamportError::InvalidPublicKeyLength {
+                expected: sizes.public_key_bytes,
+                actual: public_key.len(),
+            });
+        };
+
+        let ots_sizes = self.ots.sizes();
+        let (leaf_index, rest) = signature
+            .split_first_chunk::<LEAF_INDEX_SIZE>()
+            .expect("length checked above");
+        let leaf_index = u32::from_be_bytes(*leaf_index) as usize;
+        let (ots_signature, rest) = rest.split_at(ots_sizes.signature_bytes);
+        let (ots_public_key, auth_path) =
+            rest.split_at(ots_sizes.public_key_bytes);
+        if leaf_index >= self.height.leaves() {
+            return Ok(false);
+        }
+        if !self
+            .ots
+            .verify_encoded(message, ots_signature, ots_public_key)?
+        {
+            return Ok(false);
+        }
+
+        let (key_id, elements) =
+            self.ots.split_tag::<KEY_ID_SIZE>(ots_public_key);
+        let (elements, _) = elements.as_chunks::<HASH_SIZE>();
+        let (auth_path, _) = auth_path.as_chunks::<HASH_SIZE>();
+        let leaf = leaf_hash(leaf_index, key_id.as_ref(), elements);
+        Ok(self.root_from_leaf(leaf_index, leaf, auth_path) == *root)
+    }
+
+    fn root_from_leaf(
+        &self,
+        leaf_index: usize,
+        leaf: [u8; HASH_SIZE],
+        auth_path: &[[u8; HASH_SIZE]],
+    ) -> [u8; HASH_SIZE] {
+        let mut node = self.height.leaves() + leaf_index;
+        let mut hash = leaf;
+        for sibling in auth_path {
+            hash = if node.is_multiple_of(2) {
+                interior_hash(&hash, sibling)
+            } else {
+                interior_hash(sibling, &hash)
+            };
+            node /= 2;
+        }
+        hash
+    }
+}
+
+#[cfg(feature = "std")]
+impl Scheme for MssScheme {
+    type PublicKey = MssPublicKey;
+    type SecretKey = MssSecretKey;
+    type Signature = MssSignature;
+    type Error = LamportError;
+
+    fn algorithm_name(&self) -> &'static str {
+        MssScheme::algorithm_name(self)
+    }
+
+    fn param_set_name(&self) -> &'static str {
+        MssScheme::param_set_name(self)
+    }
+
+    fn backend_name(&self) -> &'static str {
+        MssScheme::backend_name(self)
+    }
+
+    fn sizes(&self) -> SchemeSizes {
+        MssScheme::sizes(self).into()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::stateful(self.max_signatures_per_key() as u64)
+    }
+
+    fn security(&self) -> SecurityProfile {
+        MssScheme::security(self)
+    }
+
+    fn keypair(
+        &self,
+    ) -> Result<(Self::PublicKey, Self::SecretKey), Self::Error> {
+        MssScheme::keypair(self)
+    }
+
+    fn sign(
+        &self,
+        message: &[u8],
+        secret_key: &mut Self::SecretKey,
+    ) -> Result<Self::Signature, Self::Error> {
+        MssScheme::sign(self, message, secret_key)
+    }
+
+    fn verify(
+        &self,
+        message: &[u8],
+        signature: &Self::Signature,
+        public_key: &Self::PublicKey,
+    ) -> Result<bool, Self::Error> {
+        MssScheme::verify(self, message, signature, public_key)
+    }
+
+    fn signature_len(&self, signature: &Self::Signature) -> usize {
+        signature.byte_len()
+    }
+}
+
+#[cfg(feature = "std")]
+impl MssSecretKey {
+    // Leaf `q`'s one-time key. Secret element `j` is
+    // `SHA-256(I || u32(q) || u16(j) || 0xff || SEED)`, the LMS private key
+    // derivation, and randomized leaves use `I` as their key identifier.
+    fn leaf_keypair(
+        &self,
+        ots: LamportVariant,
+        leaf: usize,
+    ) -> (LamportPublicKey, LamportSecretKey) {
+        record_sha256(KEY_ID_SIZE + 4 + 2 + 1 + SEED_SIZE, SECRET_ELEMENTS);
+        let elements = (0..SECRET_ELEMENTS)
+            .map(|element| {
+                let mut hasher = Sha256::new();
+                hasher.update(self.key_id);
+                hasher.update((leaf as u32).to_be_bytes());
+                hasher.update((element as u16).to_be_bytes());
+                hasher.update([0xff]);
+                hasher.update(self.seed);
+                hasher.finalize().into()
+            })
+            .collect();
+        let key_id = match ots {
+            LamportVariant::Plain => None,
+            LamportVariant::Randomized => Some(self.key_id),
+        };
+        ots.keypair_from_elements(elements, key_id, crate::default_threads())
+    }
+}
+
+// Hashes a leaf's one-time public key: its key identifier, if any, then its
+// elements.
+fn leaf_hash(
+    leaf: usize,
+    key_id: Option<&[u8; KEY_ID_SIZE]>,
+    elements: &[[u8; HASH_SIZE]],
+) -> [u8; HASH_SIZE] {
+    let key_id_bytes = key_id.map_or(0, |key_id| key_id.len());
+    record_sha256(
+        D_LEAF.len() + 4 + key_id_bytes + elements.len() * HASH_SIZE,
+        1,
+    );
+    let mut hasher = Sha256::new();
+    hasher.update(D_LEAF);
+    hasher.update((leaf as u32).to_be_bytes());
+    if let Some(key_id) = key_id {
+        hasher.update(key_id);
+    }
+    for element in elements {
+        hasher.update(element);
+    }
+    hasher.finalize().into()
+}
+
+fn interior_hash(
+    left: &[u8; HASH_SIZE],
+    right: &[u8; HASH_SIZE],
+) -> [u8; HASH_SIZE] {
+    record_sha256(D_INTR.len() + 2 * HASH_SIZE, 1);
+    let mut hasher = Sha256::new();
+    hasher.update(D_INTR);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MssHeight, MssScheme, SEED_SIZE};
+    use crate::{LamportError, LamportVariant, HASH_SIZE, KEY_ID_SIZE};
+    use sha2::{Digest, Sha256};
+
+    const SCHEME: MssScheme =
+        MssScheme::new(MssHeight::H5, LamportVariant::Plain);
+
+    #[test]
+    fn sign_and_verify_every_leaf() {
+        let (public_key, mut secret_key) = SCHEME.keypair_with_seed(42);
+        assert_eq!(public_key.byte_len(), SCHEME.sizes().public_key_bytes);
+
+        for leaf in 0..SCHEME.max_signatures_per_key() {
+            let message = format!("mss-message-{leaf}");
+            let signature = SCHEME
+                .sign(message.as_bytes(), &mut secret_key)
+                .expect("sign should succeed");
+
+            assert_eq!(signature.leaf_index(), leaf);
+            assert_eq!(signature.byte_len(), SCHEME.sizes().signature_bytes);
+            assert!(SCHEME
+                .verify(message.as_bytes(), &signature, &public_key)
+                .expect("verify should succeed"));
+        }
+        assert_eq!(secret_key.remaining_signatures(), 0);
+    }
+
+    #[test]
+    fn secret_key_sizes_separate_persisted_and_resident() {
+        let (_, secret_key) = SCHEME.keypair_with_seed(42);
+
+        assert_eq!(secret_key.byte_len(), SCHEME.sizes().secret_key_bytes);
+        assert_eq!(
+            secret_key.resident_byte_len(),
+            secret_key.byte_len() + 2 * 32 * 32
+        );
+    }
+
+    #[test]
+    fn exhausted_key_is_rejected() {
+        let (_, mut secret_key) = SCHEME.keypair_with_seed(42);
+        for _ in 0..SCHEME.max_signatures_per_key() {
+            SCHEME
+                .sign(b"mss", &mut secret_key)
+                .expect("sign should succeed");
+        }
+
+        let result = SCHEME.sign(b"mss", &mut secret_key);
+        assert!(matches!(result, Err(LamportError::KeyExhausted { .. })));
+    }
+
+    #[test]
+    fn verify_rejects_other_message_and_moved_leaf() {
+        let (public_key, mut secret_key) = SCHEME.keypair_with_seed(42);
+        let mut signature = SCHEME
+            .sign(b"message-a", &mut secret_key)
+            .expect("sign should succeed");
+
+        assert!(!SCHEME
+            .verify(b"message-b", &signature, &public_key)
+            .expect("verify should succeed"));
+
+        signature.leaf_index = 1;
+        assert!(!SCHEME
+            .verify(b"message-a", &signature, &public_key)
+            .expect("verify should succeed"));
+    }
+
+    #[test]
+    fn verify_rejects_other_tree() {
+        let (_, mut secret_key) = SCHEME.keypair_with_seed(42);
+        let (other_public_key, _) = SCHEME.keypair_with_seed(43);
+        let signature = SCHEME
+            .sign(b"mss", &mut secret_key)
+            .expect("sign should succeed");
+
+        assert!(!SCHEME
+            .verify(b"mss", &signature, &other_public_key)
+            .expect("verify should succeed"));
+    }
+
+    #[test]
+    fn randomized_leaves_roundtrip() {
+        let scheme = MssScheme::new(MssHeight::H5, LamportVariant::Randomized);
+        let (public_key, mut secret_key) = scheme.keypair_with_seed(42);
+        let signature = scheme
+            .sign(b"mss-randomized", &mut secret_key)
+            .expect("sign should succeed");
+
+        assert_eq!(scheme.param_set_name(), "MSS-Lamport-256-R-H5");
+        assert_eq!(signature.byte_len(), scheme.sizes().signature_bytes);
+        assert!(scheme
+            .verify(b"mss-randomized", &signature, &public_key)
+            .expect("verify should succeed"));
+    }
+
+    #[test]
+    fn leaf_secrets_follow_the_lms_derivation() {
+        let seed = [0x5e; SEED_SIZE];
+        let key_id = [0x1d; KEY_ID_SIZE];
+        let (_, secret_key) = SCHEME.keypair_from_seed(seed, key_id);
+        let (public_key, _) = secret_key.leaf_keypair(SCHEME.ots(), 3);
+
+        let mut input = Vec::new();
+        input.extend(key_id);
+        input.extend(3_u32.to_be_bytes());
+        input.extend(7_u16.to_be_bytes());
+        input.push(0xff);
+        input.extend(seed);
+        let secret: [u8; HASH_SIZE] = Sha256::digest(&input).into();
+        assert_eq!(
+            public_key.elements[7],
+            <[u8; HASH_SIZE]>::from(Sha256::digest(secret))
+        );
+    }
+
+    #[test]
+    fn os_keys_sign_and_verify() {
+        let (public_key, mut secret_key) = SCHEME.keypair().unwrap();
+        let (other_public_key, _) = SCHEME.keypair().unwrap();
+        assert_ne!(public_key.root(), other_public_key.root());
+
+        let signature = SCHEME.sign(b"mss-os", &mut secret_key).unwrap();
+        assert!(SCHEME.verify(b"mss-os", &signature, &public_key).unwrap());
+    }
+}
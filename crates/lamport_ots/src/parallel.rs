@@ -0,0 +1,72 @@
+use crate::HASH_SIZE;
+use std::thread;
+
+pub(crate) fn hash_elements(
+    elements: &[[u8; HASH_SIZE]],
+    threads: usize,
+) -> Vec<[u8; HASH_SIZE]> {
+    let mut hashes = vec![[0_u8; HASH_SIZE]; elements.len()];
+    let threads = threads.clamp(1, elements.len().max(1));
+    if threads == 1 {
+        hash_chunk(elements, &mut hashes);
+        return hashes;
+    }
+
+    let chunk_len = elements.len().div_ceil(threads);
+    thread::scope(|scope| {
+        for (input, output) in
+            elements.chunks(chunk_len).zip(hashes.chunks_mut(chunk_len))
+        {
+            scope.spawn(|| hash_chunk(input, output));
+        }
+    });
+    hashes
+}
+
+fn hash_chunk(input: &[[u8; HASH_SIZE]], output: &mut [[u8; HASH_SIZE]]) {
+    sha256_multilane::hash_batch(input, output);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hash_elements;
+    use crate::{hash_bytes, XorShift64, HASH_SIZE, LAMPORT_OTS_SCHEME};
+
+    #[test]
+    fn threaded_hashes_match_sequential() {
+        let mut rng = XorShift64::new(7);
+        let mut elements = vec![[0_u8; HASH_SIZE]; 37];
+        for element in &mut elements {
+            rng.fill_bytes(element);
+        }
+        let expected: Vec<_> = elements.iter().map(|e| hash_bytes(e)).collect();
+
+        for threads in [0, 1, 2, 3, 8, 64] {
+            assert_eq!(hash_elements(&elements, threads), expected);
+        }
+    }
+
+    #[test]
+    fn threaded_keygen_and_verify_roundtrip() {
+        let scheme = LAMPORT_OTS_SCHEME;
+        let message = b"lamport-parallel";
+
+        let (sequential_public, _) =
+            scheme.keypair_with_rng_threads(&mut XorShift64::new(42), 1);
+        let (public_key, mut secret_key) =
+            scheme.keypair_with_rng_threads(&mut XorShift64::new(42), 4);
+        assert_eq!(public_key.elements, sequential_public.elements);
+
+        let signature = scheme
+            .sign(message, &mut secret_key)
+            .expect("sign should succeed");
+        for threads in [1, 4] {
+            assert!(scheme
+                .verify_with_threads(message, &signature, &public_key, threads)
+                .expect("verify should succeed"));
+            assert!(!scheme
+                .verify_with_threads(b"other", &signature, &public_key, threads)
+                .expect("verify should succeed"));
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! The human-readable report lines the scheme binaries share, and how they
+//! exit on a bad `BENCH_*` setting.
+
+use crate::memory::MemoryStats;
+use crate::stats::Summary;
+use std::fmt::Display;
+use std::process;
+
+/// Prints the median, sample count and spread of one timed operation.
+pub fn print_timing(label: &str, summary: Summary) {
+    println!(
+        "Time to {label}: {:?} (median; {} samples, {} outliers dropped)",
+        summary.median, summary.iterations, summary.outliers
+    );
+    println!("Time to {label} (ns): {}", summary.median.as_nanos());
+    println!(
+        "Time to {label} p90/p99/stddev (ns): {} / {} / {}",
+        summary.p90.as_nanos(),
+        summary.p99.as_nanos(),
+        summary.stddev.as_nanos()
+    );
+}
+
+pub fn print_memory(label: &str, stats: MemoryStats) {
+    println!("Peak memory during {label}: {} bytes", stats.peak_bytes);
+    println!(
+        "Allocations during {label}: {} ({} bytes total)",
+        stats.allocations, stats.allocated_bytes
+    );
+}
+
+/// `stats` on one line, for the closing summary.
+pub fn memory_summary(stats: MemoryStats) -> String {
+    format!(
+        "{} bytes peak, {} allocations, {} bytes allocated",
+        stats.peak_bytes, stats.allocations, stats.allocated_bytes
+    )
+}
+
+/// Unwraps `result`, or prints the error to stderr and exits with status 1.
+pub fn or_exit<T, E: Display>(result: Result<T, E>) -> T {
+    result.unwrap_or_else(|err| {
+        eprintln!("error: {err}");
+        process::exit(1)
+    })
+}
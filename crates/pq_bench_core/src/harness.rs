@@ -0,0 +1,247 @@
+//! Keygen, sign and verify timings for any [`DynScheme`], shared by
+//! `pq-compare` and the scheme crates' bench binaries.
+//!
+//! Key generation does not depend on the message, so it is sampled once per
+//! scheme and repeated on each of its rows. Stateful schemes get a fresh,
+//! untimed keypair whenever the current key runs out of signatures.
+
+use crate::bench_message;
+use crate::measure_time;
+use crate::memory::{self, MemoryStats};
+use crate::report::BenchRecord;
+use crate::stats::{sample, SampleConfig, Summary};
+use crate::zkvm::{self, OpCounts, Source};
+use pq_signature::{DynKeyPair, DynScheme, Handle, SchemeError, SchemeSizes};
+use std::error::Error;
+use std::fmt;
+use std::hint::black_box;
+
+/// Timings of one operation, with the heap activity of the single call
+/// that peaked highest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OperationStats {
+    pub summary: Summary,
+    pub memory: MemoryStats,
+}
+
+/// One scheme at one message size.
+#[derive(Clone, Debug)]
+pub struct Measurement {
+    pub algorithm: &'static str,
+    pub param_set: &'static str,
+    pub backend: &'static str,
+    pub message_size: usize,
+    pub sizes: SchemeSizes,
+    /// Longest signature actually produced.
+    pub signature_bytes: usize,
+    pub keygen: OperationStats,
+    pub sign: OperationStats,
+    pub verify: OperationStats,
+}
+
+#[derive(Debug)]
+pub enum MeasureError {
+    Scheme {
+        param_set: &'static str,
+        source: SchemeError,
+    },
+    VerificationFailed {
+        param_set: &'static str,
+        message_size: usize,
+    },
+}
+
+impl fmt::Display for MeasureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Scheme { param_set, source } => {
+                write!(f, "{param_set}: {source}")
+            }
+            Self::VerificationFailed {
+                param_set,
+                message_size,
+            } => write!(
+                f,
+                "{param_set}: signature over a {message_size}-byte message failed to verify"
+            ),
+        }
+    }
+}
+
+impl Error for MeasureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Scheme { source, .. } => Some(source),
+            Self::VerificationFailed { .. } => None,
+        }
+    }
+}
+
+/// Samples keygen, then sign and verify of a [`bench_message`] for each
+/// message size, each with `config`'s warmup, sample count and outlier
+/// filter.
+pub fn measure(
+    scheme: &dyn DynScheme,
+    message_sizes: &[usize],
+    config: &SampleConfig,
+) -> Result<Vec<Measurement>, MeasureError> {
+    let samples = config.samples.max(1);
+    let param_set = scheme.param_set_name();
+    let wrap = |source| MeasureError::Scheme { param_set, source };
+
+    // Checked up front so a failing keygen is reported, not just timed.
+    scheme.keypair().map_err(wrap)?;
+    let (summary, memory) = sample(config, || scheme.keypair());
+    let keygen = OperationStats { summary, memory };
+
+    let max_signatures = scheme.capabilities().max_signatures_per_key;
+    let mut rows = Vec::with_capacity(message_sizes.len());
+    for &message_size in message_sizes {
+        let message = bench_message(message_size);
+        let mut public_keys: Vec<Handle> = Vec::new();
+        let mut signatures: Vec<(Handle, usize)> = Vec::with_capacity(samples);
+        let mut secret_key: Option<Handle> = None;
+        let mut signed_with_key = 0_u64;
+
+        let mut sign_samples = Vec::with_capacity(samples);
+        let mut sign_memory = MemoryStats::default();
+        for run in 0..config.warmup + samples {
+            let exhausted =
+                max_signatures.is_some_and(|max| signed_with_key >= max);
+            if secret_key.is_none() || exhausted {
+                let DynKeyPair {
+                    public_key,
+                    secret_key: fresh,
+                } = scheme.keypair().map_err(wrap)?;
+                public_keys.push(public_key);
+                secret_key = Some(fresh);
+                signed_with_key = 0;
+            }
+            let key = secret_key.as_mut().expect("a key was just generated");
+
+            memory::reset();
+            let (signature, duration) =
+                measure_time(|| scheme.sign(&message, &mut **key));
+            let stats = memory::stats();
+            let signature = signature.map_err(wrap)?;
+            signed_with_key += 1;
+            if run >= config.warmup {
+                sign_memory = highest_peak(sign_memory, stats);
+                signatures.push((signature, public_keys.len() - 1));
+                sign_samples.push(duration);
+            }
+        }
+
+        let (signature, key_index) = &signatures[0];
+        for _ in 0..config.warmup {
+            black_box(scheme.verify(
+                &message,
+                &**signature,
+                &*public_keys[*key_index],
+            ))
+            .map_err(wrap)?;
+        }
+        let mut verify_samples = Vec::with_capacity(samples);
+        let mut verify_memory = MemoryStats::default();
+        let mut signature_bytes = 0;
+        for (signature, key_index) in &signatures {
+            let public_key = &*public_keys[*key_index];
+            memory::reset();
+            let (valid, duration) = measure_time(|| {
+                scheme.verify(&message, &**signature, public_key)
+            });
+            verify_memory = highest_peak(verify_memory, memory::stats());
+            if !valid.map_err(wrap)? {
+                return Err(MeasureError::VerificationFailed {
+                    param_set,
+                    message_size,
+                });
+            }
+            verify_samples.push(duration);
+            signature_bytes = signature_bytes
+                .max(scheme.signature_len(&**signature).map_err(wrap)?);
+        }
+
+        rows.push(Measurement {
+            algorithm: scheme.algorithm_name(),
+            param_set,
+            backend: scheme.backend_name(),
+            message_size,
+            sizes: scheme.sizes(),
+            signature_bytes,
+            keygen,
+            sign: OperationStats {
+                summary: Summary::filtered(&sign_samples, config.outlier_mad),
+                memory: sign_memory,
+            },
+            verify: OperationStats {
+                summary: Summary::filtered(&verify_samples, config.outlier_mad),
+                memory: verify_memory,
+            },
+        });
+    }
+    Ok(rows)
+}
+
+/// Primitive operations one verify of a [`bench_message`] of
+/// `message_size` bytes records, under a fresh key and signature, and
+/// whether the scheme counted them or estimated them from its specification.
+pub fn verify_ops(
+    scheme: &dyn DynScheme,
+    message_size: usize,
+) -> Result<(OpCounts, Source), MeasureError> {
+    let param_set = scheme.param_set_name();
+    let wrap = |source| MeasureError::Scheme { param_set, source };
+    let message = bench_message(message_size);
+    let DynKeyPair {
+        public_key,
+        mut secret_key,
+    } = scheme.keypair().map_err(wrap)?;
+    let signature = scheme.sign(&message, &mut *secret_key).map_err(wrap)?;
+    let (valid, counts, source) = zkvm::count_with_source(|| {
+        scheme.verify(&message, &*signature, &*public_key)
+    });
+    if !valid.map_err(wrap)? {
+        return Err(MeasureError::VerificationFailed {
+            param_set,
+            message_size,
+        });
+    }
+    Ok((counts, source))
+}
+
+fn highest_peak(current: MemoryStats, sample: MemoryStats) -> MemoryStats {
+    if sample.peak_bytes > current.peak_bytes {
+        sample
+    } else {
+        current
+    }
+}
+
+/// Flattens measurements into records. Keygen is emitted once per
+/// parameter set, with a message size of 0.
+pub fn records(measurements: &[Measurement]) -> Vec<BenchRecord> {
+    let mut records = Vec::with_capacity(measurements.len() * 3);
+    for (index, row) in measurements.iter().enumerate() {
+        let record = |operation, message_size, stats: OperationStats| {
+            BenchRecord::new(
+                row.algorithm,
+                row.param_set,
+                row.backend,
+                operation,
+                message_size,
+                stats.summary,
+                stats.memory,
+            )
+        };
+        let first_of_param_set = measurements[..index]
+            .iter()
+            .all(|earlier| earlier.param_set != row.param_set);
+        if first_of_param_set {
+            records.push(record("keygen", 0, row.keygen));
+        }
+        records.push(record("sign", row.message_size, row.sign));
+        records.push(record("verify", row.message_size, row.verify));
+    }
+    records
+}
@@ -0,0 +1,516 @@
+//! An append-only store of benchmark records, one JSON object per line,
+//! and regression checks between two runs.
+//!
+//! Each line is a [`BenchRecord`] flattened together with the
+//! [`Environment`] it was measured in, so a record is identified by its
+//! scheme, operation, message size, thread count and git commit.
+
+use crate::memory::MemoryStats;
+use crate::report::{to_json_line, BenchRecord, Environment};
+use crate::stats::Summary;
+use pq_formats::json::{self, JsonValue};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write as _};
+use std::path::Path;
+use std::time::Duration;
+
+pub const DEFAULT_DIR: &str = "bench-history";
+/// The file records are appended to inside the history directory.
+pub const FILE_NAME: &str = "results.jsonl";
+/// Allowed slowdown or growth, in percent, before a metric counts as a
+/// regression.
+pub const DEFAULT_THRESHOLD_PERCENT: f64 = 10.0;
+
+/// One stored line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub environment: Environment,
+    pub record: BenchRecord,
+}
+
+#[derive(Debug)]
+pub enum HistoryError {
+    Io(io::Error),
+    Malformed {
+        line: usize,
+    },
+    UnknownCommit(String),
+    AmbiguousCommit(String),
+    /// No commit was recorded before the one being compared, so there is
+    /// no default baseline.
+    NoBaseline,
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "history I/O error: {err}"),
+            Self::Malformed { line } => {
+                write!(f, "malformed history record on line {line}")
+            }
+            Self::UnknownCommit(commit) => {
+                write!(f, "no records for commit {commit:?}")
+            }
+            Self::AmbiguousCommit(commit) => {
+                write!(f, "commit prefix {commit:?} matches several commits")
+            }
+            Self::NoBaseline => {
+                write!(f, "history needs records from two commits to compare")
+            }
+        }
+    }
+}
+
+impl Error for HistoryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HistoryError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Appends `records` to `dir/results.jsonl`, creating both if needed.
+pub fn append(
+    dir: &Path,
+    environment: &Environment,
+    records: &[BenchRecord],
+) -> Result<(), HistoryError> {
+    fs::create_dir_all(dir)?;
+    let mut out = String::new();
+    for record in records {
+        out.push_str(&to_json_line(environment, record));
+        out.push('\n');
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(FILE_NAME))?
+        .write_all(out.as_bytes())?;
+    Ok(())
+}
+
+/// Every entry in `dir/results.jsonl`, oldest first. A missing file is an
+/// empty history.
+pub fn load(dir: &Path) -> Result<Vec<Entry>, HistoryError> {
+    let contents = match fs::read_to_string(dir.join(FILE_NAME)) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            parse_entry(line).ok_or(HistoryError::Malformed { line: index + 1 })
+        })
+        .collect()
+}
+
+/// Stored commits in the order they were first recorded.
+pub fn commits(entries: &[Entry]) -> Vec<&str> {
+    let mut commits: Vec<&str> = Vec::new();
+    for entry in entries {
+        let commit = entry.environment.git_commit.as_str();
+        if !commits.contains(&commit) {
+            commits.push(commit);
+        }
+    }
+    commits
+}
+
+/// The stored commit equal to, or uniquely starting with, `prefix`.
+pub fn resolve_commit<'a>(
+    entries: &'a [Entry],
+    prefix: &str,
+) -> Result<&'a str, HistoryError> {
+    let commits = commits(entries);
+    if let Some(exact) = commits.iter().find(|&&commit| commit == prefix) {
+        return Ok(exact);
+    }
+    let mut matching = commits
+        .into_iter()
+        .filter(|commit| commit.starts_with(prefix));
+    match (matching.next(), matching.next()) {
+        (Some(commit), None) => Ok(commit),
+        (Some(_), Some(_)) => {
+            Err(HistoryError::AmbiguousCommit(prefix.to_owned()))
+        }
+        (None, _) => Err(HistoryError::UnknownCommit(prefix.to_owned())),
+    }
+}
+
+/// The commit first recorded just before `commit`.
+pub fn previous_commit<'a>(
+    entries: &'a [Entry],
+    commit: &str,
+) -> Option<&'a str> {
+    let commits = commits(entries);
+    let position = commits.iter().position(|&stored| stored == commit)?;
+    commits.get(position.checked_sub(1)?).copied()
+}
+
+/// The environment `commit` was first measured in.
+pub fn environment_at<'a>(
+    entries: &'a [Entry],
+    commit: &str,
+) -> Option<&'a Environment> {
+    entries
+        .iter()
+        .find(|entry| entry.environment.git_commit == commit)
+        .map(|entry| &entry.environment)
+}
+
+/// The records measured at `commit`. When a key was measured more than
+/// once, the last run wins.
+pub fn at_commit(entries: &[Entry], commit: &str) -> Vec<BenchRecord> {
+    let mut records: Vec<BenchRecord> = Vec::new();
+    for entry in entries
+        .iter()
+        .filter(|entry| entry.environment.git_commit == commit)
+    {
+        match records
+            .iter_mut()
+            .find(|record| same_key(record, &entry.record))
+        {
+            Some(record) => *record = entry.record.clone(),
+            None => records.push(entry.record.clone()),
+        }
+    }
+    records
+}
+
+fn same_key(a: &BenchRecord, b: &BenchRecord) -> bool {
+    a.param_set == b.param_set
+        && a.operation == b.operation
+        && a.message_size == b.message_size
+        && a.threads == b.threads
+}
+
+/// A compared quantity, where larger is worse.
+#[derive(Clone, Copy, Debug)]
+pub struct Metric {
+    pub name: &'static str,
+    pub value: fn(&BenchRecord) -> u128,
+}
+
+/// Median time plus per-operation heap use. The tails and the mean move
+/// with machine load and are left out.
+pub const METRICS: [Metric; 3] = [
+    Metric {
+        name: "median_ns",
+        value: |record| record.summary.median.as_nanos(),
+    },
+    Metric {
+        name: "peak_bytes",
+        value: |record| record.memory.peak_bytes as u128,
+    },
+    Metric {
+        name: "allocations",
+        value: |record| record.memory.allocations as u128,
+    },
+];
+
+/// One metric of one record, before and after.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    pub param_set: String,
+    pub operation: String,
+    pub message_size: usize,
+    pub threads: usize,
+    pub metric: &'static str,
+    pub baseline: u128,
+    pub current: u128,
+    /// `current / baseline - 1`, infinite when the baseline is zero and the
+    /// current value is not.
+    pub ratio: f64,
+    pub regressed: bool,
+}
+
+/// Compares every [`METRICS`] entry of the records present in both runs.
+/// A metric regresses when it grew by more than `threshold_percent`.
+pub fn compare(
+    baseline: &[BenchRecord],
+    current: &[BenchRecord],
+    threshold_percent: f64,
+) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for record in current {
+        let Some(before) =
+            baseline.iter().find(|before| same_key(before, record))
+        else {
+            continue;
+        };
+        for metric in METRICS {
+            let (old, new) = ((metric.value)(before), (metric.value)(record));
+            let ratio = match (old, new) {
+                (0, 0) => 0.0,
+                (0, _) => f64::INFINITY,
+                _ => new as f64 / old as f64 - 1.0,
+            };
+            changes.push(Change {
+                param_set: record.param_set.clone(),
+                operation: record.operation.clone(),
+                message_size: record.message_size,
+                threads: record.threads,
+                metric: metric.name,
+                baseline: old,
+                current: new,
+                ratio,
+                regressed: ratio * 100.0 > threshold_percent,
+            });
+        }
+    }
+    changes
+}
+
+/// An environment field that differs between two runs, so their timings
+/// are not comparable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub field: &'static str,
+    pub baseline: String,
+    pub current: String,
+}
+
+/// The fields of [`Environment`] that change timings: CPU model, compiler
+/// and core count.
+pub fn environment_mismatches(
+    baseline: &Environment,
+    current: &Environment,
+) -> Vec<Mismatch> {
+    [
+        ("cpu_model", &baseline.cpu_model, &current.cpu_model),
+        (
+            "rustc_version",
+            &baseline.rustc_version,
+            &current.rustc_version,
+        ),
+        (
+            "cores",
+            &baseline.cores.to_string(),
+            &current.cores.to_string(),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, before, after)| before != after)
+    .map(|(field, before, after)| Mismatch {
+        field,
+        baseline: before.clone(),
+        current: after.clone(),
+    })
+    .collect()
+}
+
+/// Baseline records with no counterpart in `current`, which [`compare`]
+/// therefore skips.
+pub fn missing<'a>(
+    baseline: &'a [BenchRecord],
+    current: &[BenchRecord],
+) -> Vec<&'a BenchRecord> {
+    baseline
+        .iter()
+        .filter(|before| !current.iter().any(|record| same_key(before, record)))
+        .collect()
+}
+
+fn parse_entry(line: &str) -> Option<Entry> {
+    let fields: HashMap<String, JsonValue> =
+        json::parse_flat_object(line).ok()?.into_iter().collect();
+    let text = |key: &str| match fields.get(key)? {
+        JsonValue::String(text) => Some(text.clone()),
+        JsonValue::Number(_) => None,
+    };
+    let number = |key: &str| match fields.get(key)? {
+        JsonValue::Number(number) => Some(*number),
+        JsonValue::String(_) => None,
+    };
+    let count = |key: &str| usize::try_from(number(key)?).ok();
+    let nanos =
+        |key: &str| u64::try_from(number(key)?).ok().map(Duration::from_nanos);
+    let environment = Environment {
+        rustc_version: text("rustc_version")?,
+        cpu_model: text("cpu_model")?,
+        kernel: text("kernel")?,
+        arch: text("arch")?,
+        cores: count("cores")?,
+        git_commit: text("git_commit")?,
+        timestamp_unix: u64::try_from(number("timestamp_unix")?).ok()?,
+    };
+    let summary = Summary {
+        iterations: count("iterations")?,
+        outliers: count("outliers")?,
+        mean: nanos("mean_ns")?,
+        median: nanos("median_ns")?,
+        p90: nanos("p90_ns")?,
+        p99: nanos("p99_ns")?,
+        min: nanos("min_ns")?,
+        max: nanos("max_ns")?,
+        stddev: nanos("stddev_ns")?,
+    };
+    let memory = MemoryStats {
+        peak_bytes: count("peak_bytes")?,
+        allocations: count("allocations")?,
+        allocated_bytes: count("allocated_bytes")?,
+    };
+    let record = BenchRecord::new(
+        &text("scheme")?,
+        &text("param_set")?,
+        &text("backend")?,
+        &text("operation")?,
+        count("message_size")?,
+        summary,
+        memory,
+    )
+    .with_threads(count("threads")?);
+    Some(Entry {
+        environment,
+        record,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        append, at_commit, commits, compare, environment_at,
+        environment_mismatches, load, missing, previous_commit, resolve_commit,
+        HistoryError, Mismatch,
+    };
+    use crate::memory::MemoryStats;
+    use crate::report::{BenchRecord, Environment};
+    use crate::stats::Summary;
+    use std::time::Duration;
+    use std::{env, fs, process};
+
+    fn environment(commit: &str) -> Environment {
+        Environment {
+            rustc_version: "rustc 1.92.0".into(),
+            cpu_model: "AMD EPYC 9B14, \"Genoa\"\tv2".into(),
+            kernel: "Linux 6.8.0".into(),
+            arch: "x86_64".into(),
+            cores: 4,
+            git_commit: commit.into(),
+            timestamp_unix: 1_700_000_000,
+        }
+    }
+
+    fn record(operation: &str, median_micros: u64, peak: usize) -> BenchRecord {
+        let samples = [
+            Duration::from_micros(median_micros - 1),
+            Duration::from_micros(median_micros),
+            Duration::from_micros(median_micros + 1),
+        ];
+        BenchRecord::new(
+            "Falcon",
+            "Falcon-512",
+            "pqcrypto-falcon",
+            operation,
+            32,
+            Summary::from_samples(&samples),
+            MemoryStats {
+                peak_bytes: peak,
+                allocations: 1,
+                allocated_bytes: peak,
+            },
+        )
+    }
+
+    #[test]
+    fn records_round_trip_through_the_store() {
+        let dir =
+            env::temp_dir().join(format!("pq-bench-history-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert!(load(&dir).unwrap().is_empty());
+
+        append(&dir, &environment("aaa111"), &[record("sign", 300, 800)])
+            .unwrap();
+        append(&dir, &environment("aaa111"), &[record("sign", 310, 800)])
+            .unwrap();
+        append(&dir, &environment("bbb222"), &[record("sign", 400, 800)])
+            .unwrap();
+        let entries = load(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].environment, environment("aaa111"));
+        assert_eq!(entries[0].record, record("sign", 300, 800));
+        assert_eq!(commits(&entries), ["aaa111", "bbb222"]);
+        assert_eq!(previous_commit(&entries, "bbb222"), Some("aaa111"));
+        assert_eq!(previous_commit(&entries, "aaa111"), None);
+        assert_eq!(resolve_commit(&entries, "bbb").unwrap(), "bbb222");
+        assert!(matches!(
+            resolve_commit(&entries, "ccc"),
+            Err(HistoryError::UnknownCommit(_))
+        ));
+        assert_eq!(at_commit(&entries, "aaa111"), [record("sign", 310, 800)]);
+        assert_eq!(
+            environment_at(&entries, "bbb222"),
+            Some(&environment("bbb222"))
+        );
+    }
+
+    #[test]
+    fn growth_beyond_the_threshold_is_a_regression() {
+        let baseline = [record("sign", 300, 800), record("verify", 60, 0)];
+        let current = [
+            record("sign", 320, 800),
+            record("verify", 80, 64),
+            record("keygen", 9_000, 0),
+        ];
+        let changes = compare(&baseline, &current, 10.0);
+
+        // Keygen has no baseline, so only sign and verify are compared.
+        assert_eq!(changes.len(), 6);
+        let regressed: Vec<_> = changes
+            .iter()
+            .filter(|change| change.regressed)
+            .map(|change| (change.operation.as_str(), change.metric))
+            .collect();
+        assert_eq!(
+            regressed,
+            [("verify", "median_ns"), ("verify", "peak_bytes")]
+        );
+        assert!(changes[1].ratio == 0.0);
+        assert!(changes[4].ratio.is_infinite());
+    }
+
+    #[test]
+    fn missing_records_and_environment_changes_are_reported() {
+        let baseline = [record("sign", 300, 800), record("verify", 60, 0)];
+        let current = [record("sign", 300, 800)];
+        assert_eq!(missing(&baseline, &current), [&baseline[1]]);
+        assert!(missing(&current, &baseline).is_empty());
+
+        let before = environment("aaa111");
+        let mut after = environment("bbb222");
+        assert!(environment_mismatches(&before, &after).is_empty());
+        after.cores = 8;
+        after.rustc_version = "rustc 1.93.0".into();
+        after.kernel = "Linux 6.9.0".into();
+        assert_eq!(
+            environment_mismatches(&before, &after),
+            [
+                Mismatch {
+                    field: "rustc_version",
+                    baseline: "rustc 1.92.0".into(),
+                    current: "rustc 1.93.0".into(),
+                },
+                Mismatch {
+                    field: "cores",
+                    baseline: "4".into(),
+                    current: "8".into(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Benchmark support shared by the scheme crates: a heap-tracking global
+//! allocator, the bench message inputs, a wall-clock timer, timing
+//! statistics, a measuring loop over any `DynScheme`, the binaries' report
+//! lines, structured result records and their history, and zkVM operation
+//! counts.
+
+pub mod console;
+pub mod harness;
+pub mod history;
+pub mod report;
+pub mod stats;
+pub mod zkvm;
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+pub const BENCH_MESSAGE_SIZES: [usize; 4] = [32, 256, 1024, 4096];
+pub const BENCH_MESSAGE_BYTE: u8 = 0x42;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static BASELINE: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static TOTAL_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+struct ThreadCounters {
+    // Signed: a thread may free memory another thread allocated.
+    current: Cell<isize>,
+    baseline: Cell<isize>,
+    peak: Cell<usize>,
+    allocations: Cell<usize>,
+    total: Cell<usize>,
+}
+
+thread_local! {
+    static THREAD: ThreadCounters = const {
+        ThreadCounters {
+            current: Cell::new(0),
+            baseline: Cell::new(0),
+            peak: Cell::new(0),
+            allocations: Cell::new(0),
+            total: Cell::new(0),
+        }
+    };
+}
+
+/// Global allocator wrapper that records live heap bytes, the peak since the
+/// last [`memory::reset`], and allocation counts, both process-wide and per
+/// thread.
+pub struct TrackingAllocator<A: GlobalAlloc + Sync + 'static> {
+    inner: &'static A,
+}
+
+impl<A: GlobalAlloc + Sync + 'static> TrackingAllocator<A> {
+    pub const fn new(inner: &'static A) -> Self {
+        Self { inner }
+    }
+}
+
+unsafe impl<A: GlobalAlloc + Sync + 'static> GlobalAlloc
+    for TrackingAllocator<A>
+{
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc(layout) };
+        if !ptr.is_null() {
+            track_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.inner.dealloc(ptr, layout) };
+        track_dealloc(layout.size());
+    }
+}
+
+fn track_alloc(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+    TOTAL_ALLOCATED.fetch_add(size, Ordering::SeqCst);
+    let current = ALLOCATED.fetch_add(size, Ordering::SeqCst) + size;
+    let baseline = BASELINE.load(Ordering::SeqCst);
+    let relative_current = current.saturating_sub(baseline);
+    let mut peak = PEAK_ALLOCATED.load(Ordering::SeqCst);
+
+    while relative_current > peak {
+        match PEAK_ALLOCATED.compare_exchange_weak(
+            peak,
+            relative_current,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        ) {
+            Ok(_) => break,
+            Err(observed) => peak = observed,
+        }
+    }
+
+    // `try_with` fails only while the thread's locals are being torn down.
+    let _ = THREAD.try_with(|thread| {
+        let current = thread.current.get() + size as isize;
+        thread.current.set(current);
+        thread.allocations.set(thread.allocations.get() + 1);
+        thread.total.set(thread.total.get() + size);
+        let relative_current =
+            current.saturating_sub(thread.baseline.get()).max(0) as usize;
+        if relative_current > thread.peak.get() {
+            thread.peak.set(relative_current);
+        }
+    });
+}
+
+fn track_dealloc(size: usize) {
+    ALLOCATED.fetch_sub(size, Ordering::SeqCst);
+    let _ = THREAD.try_with(|thread| {
+        thread.current.set(thread.current.get() - size as isize);
+    });
+}
+
+pub mod memory {
+    use super::{
+        Ordering, ALLOCATED, ALLOCATIONS, BASELINE, PEAK_ALLOCATED, THREAD,
+        TOTAL_ALLOCATED,
+    };
+
+    /// Heap activity since the last [`reset`].
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct MemoryStats {
+        /// Highest live heap usage above the level at reset.
+        pub peak_bytes: usize,
+        pub allocations: usize,
+        /// Sum of all allocation sizes, including memory already freed.
+        pub allocated_bytes: usize,
+    }
+
+    /// Starts a new measurement window for the process-wide counters and
+    /// for the calling thread's counters.
+    pub fn reset() {
+        let current = ALLOCATED.load(Ordering::SeqCst);
+        BASELINE.store(current, Ordering::SeqCst);
+        PEAK_ALLOCATED.store(0, Ordering::SeqCst);
+        ALLOCATIONS.store(0, Ordering::SeqCst);
+        TOTAL_ALLOCATED.store(0, Ordering::SeqCst);
+        THREAD.with(|thread| {
+            thread.baseline.set(thread.current.get());
+            thread.peak.set(0);
+            thread.allocations.set(0);
+            thread.total.set(0);
+        });
+    }
+
+    pub fn peak_bytes() -> usize {
+        PEAK_ALLOCATED.load(Ordering::SeqCst)
+    }
+
+    /// Process-wide counters: every thread's allocations are included.
+    pub fn stats() -> MemoryStats {
+        MemoryStats {
+            peak_bytes: peak_bytes(),
+            allocations: ALLOCATIONS.load(Ordering::SeqCst),
+            allocated_bytes: TOTAL_ALLOCATED.load(Ordering::SeqCst),
+        }
+    }
+
+    /// The calling thread's counters, unaffected by concurrent work on other
+    /// threads.
+    pub fn thread_stats() -> MemoryStats {
+        THREAD.with(|thread| MemoryStats {
+            peak_bytes: thread.peak.get(),
+            allocations: thread.allocations.get(),
+            allocated_bytes: thread.total.get(),
+        })
+    }
+
+    /// Runs `operation` in a fresh window and returns the calling thread's
+    /// stats for it.
+    pub fn measure<T>(operation: impl FnOnce() -> T) -> (T, MemoryStats) {
+        reset();
+        let value = operation();
+        (value, thread_stats())
+    }
+}
+
+pub fn bench_message(size: usize) -> Vec<u8> {
+    vec![BENCH_MESSAGE_BYTE; size]
+}
+
+pub fn measure_time<T, F>(operation: F) -> (T, Duration)
+where
+    F: FnOnce() -> T,
+{
+    let start = Instant::now();
+    let value = operation();
+    (value, start.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bench_message, memory, TrackingAllocator, BENCH_MESSAGE_BYTE};
+    use std::alloc::System;
+    use std::hint::black_box;
+
+    static SYSTEM_ALLOC: System = System;
+
+    #[global_allocator]
+    static GLOBAL: TrackingAllocator<System> =
+        TrackingAllocator::new(&SYSTEM_ALLOC);
+
+    #[test]
+    fn bench_message_uses_expected_fill_byte() {
+        let message = bench_message(16);
+        assert_eq!(message.len(), 16);
+        assert!(message.iter().all(|&byte| byte == BENCH_MESSAGE_BYTE));
+    }
+
+    #[test]
+    fn thread_stats_count_allocations_and_peak() {
+        let ((), stats) = memory::measure(|| {
+            let first = black_box(vec![0_u8; 1000]);
+            drop(first);
+            let second = black_box(vec![0_u8; 400]);
+            let third = black_box(vec![0_u8; 200]);
+            drop((second, third));
+        });
+        assert_eq!(stats.allocations, 3);
+        assert_eq!(stats.allocated_bytes, 1600);
+        assert_eq!(stats.peak_bytes, 1000);
+    }
+
+    #[test]
+    fn thread_stats_ignore_other_threads() {
+        let ((), stats) = memory::measure(|| {
+            std::thread::scope(|scope| {
+                scope.spawn(|| black_box(vec![0_u8; 1 << 20]));
+            });
+        });
+        assert!(stats.peak_bytes < 1 << 20);
+    }
+}
@@ -0,0 +1,464 @@
+//! Structured benchmark records, the machine they were taken on, and their
+//! JSON and CSV renderings.
+//!
+//! Every bench binary prints these instead of its human-readable output
+//! when `BENCH_FORMAT=json` or `BENCH_FORMAT=csv` is set.
+
+use crate::memory::MemoryStats;
+use crate::stats::Summary;
+use pq_formats::json;
+use std::error::Error;
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::process::Command;
+use std::str::FromStr;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const FORMAT_ENV: &str = "BENCH_FORMAT";
+
+const UNKNOWN: &str = "unknown";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The binary's own human-readable output.
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+impl OutputFormat {
+    /// Reads [`FORMAT_ENV`]; unset means [`OutputFormat::Text`].
+    pub fn from_env() -> Result<Self, UnknownFormat> {
+        match std::env::var(FORMAT_ENV) {
+            Ok(value) => value.parse(),
+            Err(_) => Ok(Self::Text),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = UnknownFormat;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "text" | "table" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(UnknownFormat(value.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownFormat(pub String);
+
+impl fmt::Display for UnknownFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown output format {:?}; expected text, json or csv",
+            self.0
+        )
+    }
+}
+
+impl Error for UnknownFormat {}
+
+/// Where and with what a benchmark ran. Fields that cannot be determined
+/// are `"unknown"` rather than missing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Environment {
+    /// `rustc --version` of the compiler that built the binary.
+    pub rustc_version: String,
+    pub cpu_model: String,
+    pub kernel: String,
+    pub arch: String,
+    pub cores: usize,
+    /// `GIT_COMMIT` if set, otherwise `git rev-parse HEAD` in the working
+    /// directory, with `-dirty` appended when tracked files are modified.
+    pub git_commit: String,
+    pub timestamp_unix: u64,
+}
+
+impl Environment {
+    pub fn capture() -> Self {
+        Self {
+            rustc_version: env!("PQ_BENCH_RUSTC_VERSION").to_owned(),
+            cpu_model: cpu_model().unwrap_or_else(|| UNKNOWN.to_owned()),
+            kernel: kernel().unwrap_or_else(|| UNKNOWN.to_owned()),
+            arch: std::env::consts::ARCH.to_owned(),
+            cores: thread::available_parallelism().map_or(0, |n| n.get()),
+            git_commit: git_commit().unwrap_or_else(|| UNKNOWN.to_owned()),
+            timestamp_unix: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+        }
+    }
+}
+
+fn cpu_model() -> Option<String> {
+    if let Ok(cpuinfo) = fs::read_to_string("/proc/cpuinfo") {
+        if let Some(model) = parse_cpu_model(&cpuinfo) {
+            return Some(model);
+        }
+    }
+    command_output("sysctl", &["-n", "machdep.cpu.brand_string"])
+}
+
+/// First `model name` (x86), then `Hardware` or `Model` (Arm boards).
+fn parse_cpu_model(cpuinfo: &str) -> Option<String> {
+    ["model name", "Hardware", "Model"].iter().find_map(|key| {
+        cpuinfo.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            let value = value.trim();
+            (name.trim() == *key && !value.is_empty()).then(|| value.to_owned())
+        })
+    })
+}
+
+fn kernel() -> Option<String> {
+    match fs::read_to_string("/proc/sys/kernel/osrelease") {
+        Ok(release) => Some(format!("Linux {}", release.trim())),
+        Err(_) => command_output("uname", &["-sr"]),
+    }
+}
+
+fn git_commit() -> Option<String> {
+    if let Ok(commit) = std::env::var("GIT_COMMIT") {
+        return Some(commit);
+    }
+    let commit = command_output("git", &["rev-parse", "HEAD"])?;
+    let dirty = Command::new("git")
+        .args(["diff", "--quiet", "HEAD"])
+        .status()
+        .is_ok_and(|status| status.code() == Some(1));
+    Some(if dirty {
+        format!("{commit}-dirty")
+    } else {
+        commit
+    })
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let stdout = stdout.trim();
+    (output.status.success() && !stdout.is_empty()).then(|| stdout.to_owned())
+}
+
+/// One operation of one scheme at one message size.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BenchRecord {
+    pub scheme: String,
+    pub param_set: String,
+    pub backend: String,
+    /// `keygen`, `sign` or `verify`.
+    pub operation: String,
+    pub message_size: usize,
+    pub threads: usize,
+    pub summary: Summary,
+    /// Heap activity of a single operation.
+    pub memory: MemoryStats,
+}
+
+impl BenchRecord {
+    pub fn new(
+        scheme: &str,
+        param_set: &str,
+        backend: &str,
+        operation: &str,
+        message_size: usize,
+        summary: Summary,
+        memory: MemoryStats,
+    ) -> Self {
+        Self {
+            scheme: scheme.to_owned(),
+            param_set: param_set.to_owned(),
+            backend: backend.to_owned(),
+            operation: operation.to_owned(),
+            message_size,
+            threads: 1,
+            summary,
+            memory,
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+}
+
+const ENVIRONMENT_COLUMNS: [&str; 7] = [
+    "rustc_version",
+    "cpu_model",
+    "kernel",
+    "arch",
+    "cores",
+    "git_commit",
+    "timestamp_unix",
+];
+
+const RECORD_COLUMNS: [&str; 18] = [
+    "scheme",
+    "param_set",
+    "backend",
+    "operation",
+    "message_size",
+    "threads",
+    "iterations",
+    "outliers",
+    "mean_ns",
+    "median_ns",
+    "p90_ns",
+    "p99_ns",
+    "min_ns",
+    "max_ns",
+    "stddev_ns",
+    "peak_bytes",
+    "allocations",
+    "allocated_bytes",
+];
+
+fn environment_values(environment: &Environment) -> [Value<'_>; 7] {
+    [
+        Value::Text(&environment.rustc_version),
+        Value::Text(&environment.cpu_model),
+        Value::Text(&environment.kernel),
+        Value::Text(&environment.arch),
+        Value::Number(environment.cores as u128),
+        Value::Text(&environment.git_commit),
+        Value::Number(environment.timestamp_unix as u128),
+    ]
+}
+
+fn record_values(record: &BenchRecord) -> [Value<'_>; 18] {
+    let summary = &record.summary;
+    [
+        Value::Text(&record.scheme),
+        Value::Text(&record.param_set),
+        Value::Text(&record.backend),
+        Value::Text(&record.operation),
+        Value::Number(record.message_size as u128),
+        Value::Number(record.threads as u128),
+        Value::Number(summary.iterations as u128),
+        Value::Number(summary.outliers as u128),
+        Value::Number(summary.mean.as_nanos()),
+        Value::Number(summary.median.as_nanos()),
+        Value::Number(summary.p90.as_nanos()),
+        Value::Number(summary.p99.as_nanos()),
+        Value::Number(summary.min.as_nanos()),
+        Value::Number(summary.max.as_nanos()),
+        Value::Number(summary.stddev.as_nanos()),
+        Value::Number(record.memory.peak_bytes as u128),
+        Value::Number(record.memory.allocations as u128),
+        Value::Number(record.memory.allocated_bytes as u128),
+    ]
+}
+
+enum Value<'a> {
+    Text(&'a str),
+    Number(u128),
+}
+
+/// `None` for [`OutputFormat::Text`], which each binary renders itself.
+pub fn render(
+    format: OutputFormat,
+    environment: &Environment,
+    records: &[BenchRecord],
+) -> Option<String> {
+    match format {
+        OutputFormat::Text => None,
+        OutputFormat::Json => Some(to_json(environment, records)),
+        OutputFormat::Csv => Some(to_csv(environment, records)),
+    }
+}
+
+/// Captures the environment and prints `records` in `format`. Does nothing
+/// for [`OutputFormat::Text`].
+pub fn print(format: OutputFormat, records: &[BenchRecord]) {
+    if let Some(out) = render(format, &Environment::capture(), records) {
+        print!("{out}");
+    }
+}
+
+/// `{"environment": {...}, "records": [{...}, ...]}` with one record per
+/// line.
+pub fn to_json(environment: &Environment, records: &[BenchRecord]) -> String {
+    let mut out = String::from("{\n  \"environment\": ");
+    json_object(
+        &mut out,
+        &ENVIRONMENT_COLUMNS,
+        &environment_values(environment),
+    );
+    out.push_str(",\n  \"records\": [");
+    for (index, record) in records.iter().enumerate() {
+        out.push_str(if index == 0 { "\n    " } else { ",\n    " });
+        json_object(&mut out, &RECORD_COLUMNS, &record_values(record));
+    }
+    out.push_str(if records.is_empty() {
+        "]\n}\n"
+    } else {
+        "\n  ]\n}\n"
+    });
+    out
+}
+
+/// `record` and `environment` as one flat JSON object on a single line, the
+/// form [`crate::history`] stores.
+pub(crate) fn to_json_line(
+    environment: &Environment,
+    record: &BenchRecord,
+) -> String {
+    let keys = RECORD_COLUMNS
+        .iter()
+        .chain(&ENVIRONMENT_COLUMNS)
+        .copied()
+        .collect::<Vec<_>>();
+    let values = record_values(record)
+        .into_iter()
+        .chain(environment_values(environment))
+        .collect::<Vec<_>>();
+    let mut out = String::new();
+    json_object(&mut out, &keys, &values);
+    out
+}
+
+/// One row per record, with the environment repeated on every row so rows
+/// from different runs can be concatenated.
+pub fn to_csv(environment: &Environment, records: &[BenchRecord]) -> String {
+    let header = RECORD_COLUMNS
+        .iter()
+        .chain(&ENVIRONMENT_COLUMNS)
+        .copied()
+        .collect::<Vec<_>>();
+    let mut out = header.join(",");
+    out.push('\n');
+    let environment = environment_values(environment);
+    for record in records {
+        let cells = record_values(record)
+            .iter()
+            .chain(&environment)
+            .map(csv_cell)
+            .collect::<Vec<_>>();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn json_object(out: &mut String, keys: &[&str], values: &[Value<'_>]) {
+    out.push('{');
+    for (index, (key, value)) in keys.iter().zip(values).enumerate() {
+        if index > 0 {
+            out.push_str(", ");
+        }
+        json::push_string(out, key);
+        out.push_str(": ");
+        match value {
+            Value::Text(text) => json::push_string(out, text),
+            Value::Number(number) => {
+                let _ = write!(out, "{number}");
+            }
+        }
+    }
+    out.push('}');
+}
+
+fn csv_cell(value: &Value<'_>) -> String {
+    match value {
+        Value::Number(number) => number.to_string(),
+        Value::Text(text) if text.contains([',', '"', '\n', '\r']) => {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        }
+        Value::Text(text) => (*text).to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_cpu_model, to_csv, to_json, BenchRecord, Environment,
+        OutputFormat, UnknownFormat,
+    };
+    use crate::memory::MemoryStats;
+    use crate::stats::Summary;
+    use std::time::Duration;
+
+    fn environment() -> Environment {
+        Environment {
+            rustc_version: "rustc 1.92.0 (ded5c06cf 2025-12-08)".into(),
+            cpu_model: "AMD EPYC 9B14, \"Genoa\"".into(),
+            kernel: "Linux 6.8.0".into(),
+            arch: "x86_64".into(),
+            cores: 4,
+            git_commit: "abc123".into(),
+            timestamp_unix: 1_700_000_000,
+        }
+    }
+
+    fn record() -> BenchRecord {
+        let samples = [Duration::from_nanos(100), Duration::from_nanos(300)];
+        BenchRecord::new(
+            "ML-DSA",
+            "ML-DSA-65",
+            "ml-dsa",
+            "sign",
+            32,
+            Summary::from_samples(&samples),
+            MemoryStats {
+                peak_bytes: 6704,
+                allocations: 3,
+                allocated_bytes: 9000,
+            },
+        )
+    }
+
+    #[test]
+    fn json_and_csv_carry_records_and_environment() {
+        let json = to_json(&environment(), &[record()]);
+        assert!(json.starts_with("{\n  \"environment\": {\"rustc_version\""));
+        assert!(
+            json.contains("\"cpu_model\": \"AMD EPYC 9B14, \\\"Genoa\\\"\"")
+        );
+        assert!(json.contains(
+            "{\"scheme\": \"ML-DSA\", \"param_set\": \"ML-DSA-65\", \"backend\": \"ml-dsa\", \"operation\": \"sign\", \"message_size\": 32, \"threads\": 1, \"iterations\": 2, \"outliers\": 0, \"mean_ns\": 200, \"median_ns\": 200, \"p90_ns\": 300, \"p99_ns\": 300"
+        ));
+        assert!(json.ends_with("}\n  ]\n}\n"));
+        assert!(to_json(&environment(), &[]).ends_with("\"records\": []\n}\n"));
+
+        let csv = to_csv(&environment(), &[record()]);
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("scheme,param_set,backend,operation,"));
+        assert!(lines[0].ends_with(",git_commit,timestamp_unix"));
+        assert!(
+            lines[1].starts_with("ML-DSA,ML-DSA-65,ml-dsa,sign,32,1,2,0,200,")
+        );
+        assert!(lines[1].contains(",\"AMD EPYC 9B14, \"\"Genoa\"\"\","));
+    }
+
+    #[test]
+    fn cpu_model_and_format_are_parsed() {
+        let x86 = "processor\t: 0\nvendor_id\t: GenuineIntel\nmodel name\t: Intel(R) Xeon(R) CPU @ 2.20GHz\n";
+        assert_eq!(
+            parse_cpu_model(x86).as_deref(),
+            Some("Intel(R) Xeon(R) CPU @ 2.20GHz")
+        );
+        let arm = "processor\t: 0\nBogoMIPS\t: 108.00\nModel\t\t: Raspberry Pi 5 Model B Rev 1.0\n";
+        assert_eq!(
+            parse_cpu_model(arm).as_deref(),
+            Some("Raspberry Pi 5 Model B Rev 1.0")
+        );
+        assert_eq!(parse_cpu_model("processor\t: 0\n"), None);
+
+        assert_eq!("JSON".parse(), Ok(OutputFormat::Json));
+        assert_eq!("csv".parse(), Ok(OutputFormat::Csv));
+        assert_eq!(
+            "xml".parse::<OutputFormat>(),
+            Err(UnknownFormat("xml".into()))
+        );
+    }
+}
@@ -0,0 +1,307 @@
+//! The shared timing loop: untimed warmup runs, then timed samples whose
+//! outliers are rejected by median absolute deviation (MAD) before the
+//! summary statistics are computed.
+
+use crate::measure_time;
+use crate::memory::{self, MemoryStats};
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::hint::black_box;
+use std::time::Duration;
+
+pub const WARMUP_ENV: &str = "BENCH_WARMUP";
+pub const SAMPLES_ENV: &str = "BENCH_ITERATIONS";
+/// MAD multiplier beyond which a sample is rejected; `0` or `off` keeps
+/// every sample.
+pub const OUTLIER_MAD_ENV: &str = "BENCH_OUTLIER_MAD";
+
+pub const DEFAULT_WARMUP: usize = 3;
+pub const DEFAULT_SAMPLES: usize = 10;
+pub const DEFAULT_OUTLIER_MAD: f64 = 3.0;
+
+/// Scales the MAD to a standard-deviation estimate for normal data.
+const MAD_TO_SIGMA: f64 = 1.4826;
+/// Below this the MAD is too noisy to judge outliers by.
+const MIN_FILTERED_SAMPLES: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampleConfig {
+    /// Untimed runs before sampling starts.
+    pub warmup: usize,
+    /// Timed runs, at least one.
+    pub samples: usize,
+    /// Samples further than `outlier_mad * 1.4826 * MAD` from the median
+    /// are rejected. `None` keeps every sample.
+    pub outlier_mad: Option<f64>,
+}
+
+impl Default for SampleConfig {
+    fn default() -> Self {
+        Self {
+            warmup: DEFAULT_WARMUP,
+            samples: DEFAULT_SAMPLES,
+            outlier_mad: Some(DEFAULT_OUTLIER_MAD),
+        }
+    }
+}
+
+impl SampleConfig {
+    /// Defaults overridden by [`WARMUP_ENV`], [`SAMPLES_ENV`] and
+    /// [`OUTLIER_MAD_ENV`].
+    pub fn from_env() -> Result<Self, InvalidEnv> {
+        let mut config = Self::default();
+        if let Some(warmup) = read_env(WARMUP_ENV, parse_warmup)? {
+            config.warmup = warmup;
+        }
+        if let Some(samples) = read_env(SAMPLES_ENV, parse_samples)? {
+            config.samples = samples;
+        }
+        if let Some(outlier_mad) = read_env(OUTLIER_MAD_ENV, parse_outlier_mad)?
+        {
+            config.outlier_mad = outlier_mad;
+        }
+        Ok(config)
+    }
+}
+
+pub fn parse_warmup(value: &str) -> Option<usize> {
+    value.trim().parse().ok()
+}
+
+pub fn parse_samples(value: &str) -> Option<usize> {
+    value.trim().parse().ok().filter(|&samples| samples > 0)
+}
+
+/// `0` or `off` disables filtering.
+pub fn parse_outlier_mad(value: &str) -> Option<Option<f64>> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("off") {
+        return Some(None);
+    }
+    match value.parse::<f64>() {
+        Ok(0.0) => Some(None),
+        Ok(k) if k.is_finite() && k > 0.0 => Some(Some(k)),
+        _ => None,
+    }
+}
+
+fn read_env<T>(
+    name: &'static str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<Option<T>, InvalidEnv> {
+    match env::var(name) {
+        Ok(value) => match parse(&value) {
+            Some(parsed) => Ok(Some(parsed)),
+            None => Err(InvalidEnv { name, value }),
+        },
+        Err(_) => Ok(None),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidEnv {
+    pub name: &'static str,
+    pub value: String,
+}
+
+impl fmt::Display for InvalidEnv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid value {:?} for {}", self.value, self.name)
+    }
+}
+
+impl Error for InvalidEnv {}
+
+/// Distribution of one operation's timings after outlier rejection.
+/// Percentiles use the nearest-rank method, so they are always an observed
+/// sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    /// Samples taken, including rejected outliers.
+    pub iterations: usize,
+    pub outliers: usize,
+    pub mean: Duration,
+    pub median: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    /// Sample standard deviation of the kept samples.
+    pub stddev: Duration,
+}
+
+impl Summary {
+    /// Summarizes every sample, rejecting none.
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        Self::filtered(samples, None)
+    }
+
+    /// Summarizes `samples` after dropping those further than
+    /// `outlier_mad` scaled MADs from the median. Nothing is rejected when
+    /// the MAD is zero or there are fewer than five samples.
+    pub fn filtered(samples: &[Duration], outlier_mad: Option<f64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        if let Some(k) =
+            outlier_mad.filter(|_| samples.len() >= MIN_FILTERED_SAMPLES)
+        {
+            let center = nanos(median(&sorted));
+            let mut deviations: Vec<Duration> = sorted
+                .iter()
+                .map(|&sample| {
+                    Duration::from_nanos((nanos(sample) - center).abs() as u64)
+                })
+                .collect();
+            deviations.sort_unstable();
+            let limit = k * MAD_TO_SIGMA * nanos(median(&deviations));
+            if limit > 0.0 {
+                sorted
+                    .retain(|&sample| (nanos(sample) - center).abs() <= limit);
+            }
+        }
+
+        let kept = sorted.len();
+        let total: Duration = sorted.iter().sum();
+        let mean = total / kept as u32;
+        let variance = if kept > 1 {
+            sorted
+                .iter()
+                .map(|&sample| (nanos(sample) - nanos(mean)).powi(2))
+                .sum::<f64>()
+                / (kept - 1) as f64
+        } else {
+            0.0
+        };
+        Self {
+            iterations: samples.len(),
+            outliers: samples.len() - kept,
+            mean,
+            median: median(&sorted),
+            p90: percentile(&sorted, 90),
+            p99: percentile(&sorted, 99),
+            min: sorted[0],
+            max: sorted[kept - 1],
+            stddev: Duration::from_nanos(variance.sqrt() as u64),
+        }
+    }
+}
+
+fn nanos(duration: Duration) -> f64 {
+    duration.as_nanos() as f64
+}
+
+fn median(sorted: &[Duration]) -> Duration {
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2
+    } else {
+        sorted[middle]
+    }
+}
+
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Runs `operation` `config.warmup` times untimed, then times it
+/// `config.samples` times. The memory stats are those of the timed call
+/// with the highest heap peak.
+pub fn sample<T>(
+    config: &SampleConfig,
+    mut operation: impl FnMut() -> T,
+) -> (Summary, MemoryStats) {
+    for _ in 0..config.warmup {
+        black_box(operation());
+    }
+    let mut samples = Vec::with_capacity(config.samples.max(1));
+    let mut highest = MemoryStats::default();
+    for _ in 0..config.samples.max(1) {
+        memory::reset();
+        let (value, duration) = measure_time(&mut operation);
+        let stats = memory::stats();
+        black_box(value);
+        if stats.peak_bytes >= highest.peak_bytes {
+            highest = stats;
+        }
+        samples.push(duration);
+    }
+    (Summary::filtered(&samples, config.outlier_mad), highest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_outlier_mad, sample, SampleConfig, Summary};
+    use std::time::Duration;
+
+    #[test]
+    fn summary_reports_nearest_rank_percentiles() {
+        let samples: Vec<_> =
+            (1..=100).rev().map(Duration::from_micros).collect();
+        let summary = Summary::from_samples(&samples);
+
+        assert_eq!(summary.iterations, 100);
+        assert_eq!(summary.outliers, 0);
+        assert_eq!(summary.min, Duration::from_micros(1));
+        assert_eq!(summary.max, Duration::from_micros(100));
+        assert_eq!(summary.mean, Duration::from_nanos(50_500));
+        assert_eq!(summary.median, Duration::from_nanos(50_500));
+        assert_eq!(summary.p90, Duration::from_micros(90));
+        assert_eq!(summary.p99, Duration::from_micros(99));
+        // sqrt(sum((i - 50.5)^2) / 99) µs for i in 1..=100.
+        assert_eq!(summary.stddev, Duration::from_nanos(29_011));
+
+        let single = Summary::from_samples(&[Duration::from_nanos(7)]);
+        assert_eq!(single.p99, Duration::from_nanos(7));
+        assert_eq!(single.stddev, Duration::ZERO);
+        assert_eq!(Summary::from_samples(&[]), Summary::default());
+    }
+
+    #[test]
+    fn mad_filter_drops_only_far_outliers() {
+        let mut samples: Vec<_> = [98, 99, 100, 100, 101, 102, 100, 99]
+            .into_iter()
+            .map(Duration::from_micros)
+            .collect();
+        samples.push(Duration::from_millis(5));
+
+        let filtered = Summary::filtered(&samples, Some(3.0));
+        assert_eq!(filtered.iterations, 9);
+        assert_eq!(filtered.outliers, 1);
+        assert_eq!(filtered.max, Duration::from_micros(102));
+        assert_eq!(filtered.mean, Duration::from_nanos(99_875));
+
+        let unfiltered = Summary::filtered(&samples, None);
+        assert_eq!(unfiltered.outliers, 0);
+        assert_eq!(unfiltered.max, Duration::from_millis(5));
+
+        // A zero MAD (most samples identical) rejects nothing.
+        let flat = [100, 100, 100, 100, 900].map(Duration::from_nanos);
+        assert_eq!(Summary::filtered(&flat, Some(3.0)).outliers, 0);
+        // So do too few samples to estimate a MAD from.
+        let few = [100, 101, 900].map(Duration::from_nanos);
+        assert_eq!(Summary::filtered(&few, Some(3.0)).outliers, 0);
+    }
+
+    #[test]
+    fn sample_runs_warmup_untimed() {
+        let mut calls = 0;
+        let config = SampleConfig {
+            warmup: 2,
+            samples: 5,
+            outlier_mad: None,
+        };
+        let (summary, _) = sample(&config, || calls += 1);
+        assert_eq!(calls, 7);
+        assert_eq!(summary.iterations, 5);
+
+        assert_eq!(parse_outlier_mad("off"), Some(None));
+        assert_eq!(parse_outlier_mad("0"), Some(None));
+        assert_eq!(parse_outlier_mad("3.5"), Some(Some(3.5)));
+        assert_eq!(parse_outlier_mad("-1"), None);
+    }
+}
@@ -0,0 +1,473 @@
+//! Primitive-operation counts for estimating what verifying a signature
+//! costs inside a zkVM, without running one.
+//!
+//! Verifiers report what they do through [`record`] and [`sha256`]; the
+//! calls are no-ops unless a [`count`] scope is active on the thread. The
+//! pure-Rust schemes record each hash as it happens. Backends that cannot be
+//! instrumented (C code, or a dependency's internals) report the counts
+//! their specification implies for one verify through [`estimate`], and
+//! [`count_with_source`] tells the two apart.
+//!
+//! A [`CostTable`] turns counts into estimated zkVM cycles.
+
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, AddAssign};
+
+/// SHAKE128 absorbs and squeezes this many bytes per permutation.
+pub const SHAKE128_RATE: usize = 168;
+pub const SHAKE256_RATE: usize = 136;
+
+const SHA256_BLOCK: usize = 64;
+/// The `0x80` byte and the 64-bit length SHA-256 padding appends.
+const SHA256_PADDING: usize = 9;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpCounts {
+    pub sha256_compressions: u64,
+    /// Keccak-f[1600] permutations, for SHA-3 and SHAKE.
+    pub keccak_permutations: u64,
+    pub ntt_butterflies: u64,
+    /// Modular multiplications, including the one in each butterfly.
+    pub modmuls: u64,
+    /// Bytes absorbed by any hash function.
+    pub bytes_hashed: u64,
+}
+
+impl Add for OpCounts {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            sha256_compressions: self.sha256_compressions
+                + other.sha256_compressions,
+            keccak_permutations: self.keccak_permutations
+                + other.keccak_permutations,
+            ntt_butterflies: self.ntt_butterflies + other.ntt_butterflies,
+            modmuls: self.modmuls + other.modmuls,
+            bytes_hashed: self.bytes_hashed + other.bytes_hashed,
+        }
+    }
+}
+
+impl AddAssign for OpCounts {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl OpCounts {
+    /// One SHA-256 hash of `len` bytes.
+    pub const fn sha256(len: usize) -> Self {
+        Self {
+            sha256_compressions: sha256_compressions(len),
+            keccak_permutations: 0,
+            ntt_butterflies: 0,
+            modmuls: 0,
+            bytes_hashed: len as u64,
+        }
+    }
+
+    /// One SHAKE hash absorbing `len` bytes at `rate` and squeezing
+    /// `output` bytes.
+    pub const fn shake(len: usize, rate: usize, output: usize) -> Self {
+        Self {
+            sha256_compressions: 0,
+            keccak_permutations: keccak_permutations(len, rate, output),
+            ntt_butterflies: 0,
+            modmuls: 0,
+            bytes_hashed: len as u64,
+        }
+    }
+
+    /// `transforms` forward or inverse NTTs of `n` coefficients, with one
+    /// modular multiplication per butterfly.
+    pub const fn ntt(n: usize, transforms: usize) -> Self {
+        let butterflies = (n / 2 * n.ilog2() as usize * transforms) as u64;
+        Self {
+            sha256_compressions: 0,
+            keccak_permutations: 0,
+            ntt_butterflies: butterflies,
+            modmuls: butterflies,
+            bytes_hashed: 0,
+        }
+    }
+
+    pub const fn modmuls(modmuls: usize) -> Self {
+        Self {
+            sha256_compressions: 0,
+            keccak_permutations: 0,
+            ntt_butterflies: 0,
+            modmuls: modmuls as u64,
+            bytes_hashed: 0,
+        }
+    }
+
+    /// `self` repeated `times` times.
+    pub const fn times(self, times: usize) -> Self {
+        let times = times as u64;
+        Self {
+            sha256_compressions: self.sha256_compressions * times,
+            keccak_permutations: self.keccak_permutations * times,
+            ntt_butterflies: self.ntt_butterflies * times,
+            modmuls: self.modmuls * times,
+            bytes_hashed: self.bytes_hashed * times,
+        }
+    }
+}
+
+/// Compression-function calls for one SHA-256 hash of `len` bytes,
+/// padding included.
+pub const fn sha256_compressions(len: usize) -> u64 {
+    (len + SHA256_PADDING).div_ceil(SHA256_BLOCK) as u64
+}
+
+/// Permutations for one sponge hash absorbing `len` bytes and squeezing
+/// `output` bytes. Padding always adds at least one byte.
+pub const fn keccak_permutations(
+    len: usize,
+    rate: usize,
+    output: usize,
+) -> u64 {
+    let absorb = len / rate + 1;
+    // The final absorb permutation already yields the first output block.
+    let squeeze = output.div_ceil(rate).saturating_sub(1);
+    (absorb + squeeze) as u64
+}
+
+/// How a scope's [`OpCounts`] were obtained.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// Every operation was recorded as it ran.
+    Counted,
+    /// At least part came from [`estimate`].
+    Estimated,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Counted => "counted",
+            Self::Estimated => "spec estimate",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Scope {
+    counts: OpCounts,
+    estimated: bool,
+}
+
+thread_local! {
+    static ACTIVE: Cell<Option<Scope>> = const { Cell::new(None) };
+}
+
+/// Adds `counts` to the calling thread's active [`count`] scope, if any.
+pub fn record(counts: OpCounts) {
+    add(counts, false);
+}
+
+/// Like [`record`], for counts a specification implies rather than counts
+/// of operations that ran. Marks the scope [`Source::Estimated`].
+pub fn estimate(counts: OpCounts) {
+    add(counts, true);
+}
+
+fn add(counts: OpCounts, estimated: bool) {
+    ACTIVE.with(|active| {
+        if let Some(scope) = active.get() {
+            active.set(Some(Scope {
+                counts: scope.counts + counts,
+                estimated: scope.estimated || estimated,
+            }));
+        }
+    });
+}
+
+/// Records one SHA-256 hash of `len` bytes.
+pub fn sha256(len: usize) {
+    record(OpCounts::sha256(len));
+}
+
+/// Runs `operation` and returns what it recorded on this thread. Work
+/// recorded by other threads is not included, so parallel code records on
+/// the thread that starts it. Nested scopes also count toward the outer
+/// one.
+pub fn count<T>(operation: impl FnOnce() -> T) -> (T, OpCounts) {
+    let (value, counts, _) = count_with_source(operation);
+    (value, counts)
+}
+
+/// Like [`count`], also telling whether any of the counts were estimates.
+pub fn count_with_source<T>(
+    operation: impl FnOnce() -> T,
+) -> (T, OpCounts, Source) {
+    let outer = ACTIVE.with(|active| active.replace(Some(Scope::default())));
+    let value = operation();
+    let scope = ACTIVE
+        .with(|active| active.replace(outer))
+        .unwrap_or_default();
+    if outer.is_some() {
+        add(scope.counts, scope.estimated);
+    }
+    let source = if scope.estimated {
+        Source::Estimated
+    } else {
+        Source::Counted
+    };
+    (value, scope.counts, source)
+}
+
+/// Estimated zkVM cycles per primitive operation. The presets are rough
+/// orders of magnitude; load measured numbers for a specific zkVM with
+/// [`CostTable::parse`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CostTable {
+    pub sha256_compression: u64,
+    pub keccak_permutation: u64,
+    /// Loads, add, subtract and stores; the multiplication is counted in
+    /// `modmul`.
+    pub ntt_butterfly: u64,
+    pub modmul: u64,
+    /// Moving and padding input, on top of the compressions.
+    pub byte_hashed: u64,
+}
+
+impl CostTable {
+    /// Everything in software on a 32-bit RISC-V guest (`rv32im`).
+    pub const RV32IM: Self = Self {
+        sha256_compression: 3_000,
+        keccak_permutation: 16_000,
+        ntt_butterfly: 12,
+        modmul: 8,
+        byte_hashed: 2,
+    };
+
+    /// SHA-256 and Keccak handled by zkVM precompiles; arithmetic stays in
+    /// software.
+    pub const PRECOMPILES: Self = Self {
+        sha256_compression: 80,
+        keccak_permutation: 300,
+        ntt_butterfly: 12,
+        modmul: 8,
+        byte_hashed: 1,
+    };
+
+    pub const PRESETS: [(&'static str, Self); 2] =
+        [("rv32im", Self::RV32IM), ("precompiles", Self::PRECOMPILES)];
+
+    const KEYS: [&'static str; 5] = [
+        "sha256_compression",
+        "keccak_permutation",
+        "ntt_butterfly",
+        "modmul",
+        "byte_hashed",
+    ];
+
+    pub fn preset(name: &str) -> Option<Self> {
+        Self::PRESETS
+            .iter()
+            .find(|(preset, _)| preset.eq_ignore_ascii_case(name))
+            .map(|&(_, table)| table)
+    }
+
+    pub fn cycles(&self, counts: &OpCounts) -> u64 {
+        counts.sha256_compressions * self.sha256_compression
+            + counts.keccak_permutations * self.keccak_permutation
+            + counts.ntt_butterflies * self.ntt_butterfly
+            + counts.modmuls * self.modmul
+            + counts.bytes_hashed * self.byte_hashed
+    }
+
+    /// Reads `key = value` lines; `#` starts a comment. `base = <preset>`
+    /// picks the preset the other keys override (default `rv32im`), and
+    /// must come first.
+    pub fn parse(text: &str) -> Result<Self, CostTableError> {
+        let mut table = Self::RV32IM;
+        let mut seen_key = false;
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(CostTableError::Malformed { line: line_number });
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if key == "base" {
+                if seen_key {
+                    return Err(CostTableError::LateBase { line: line_number });
+                }
+                table = Self::preset(value).ok_or_else(|| {
+                    CostTableError::UnknownPreset(value.to_owned())
+                })?;
+                continue;
+            }
+            let cost =
+                value.parse().map_err(|_| CostTableError::InvalidValue {
+                    line: line_number,
+                    value: value.to_owned(),
+                })?;
+            let field = match key {
+                "sha256_compression" => &mut table.sha256_compression,
+                "keccak_permutation" => &mut table.keccak_permutation,
+                "ntt_butterfly" => &mut table.ntt_butterfly,
+                "modmul" => &mut table.modmul,
+                "byte_hashed" => &mut table.byte_hashed,
+                _ => {
+                    return Err(CostTableError::UnknownKey {
+                        line: line_number,
+                        key: key.to_owned(),
+                    })
+                }
+            };
+            *field = cost;
+            seen_key = true;
+        }
+        Ok(table)
+    }
+}
+
+impl Default for CostTable {
+    fn default() -> Self {
+        Self::RV32IM
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CostTableError {
+    Malformed {
+        line: usize,
+    },
+    UnknownKey {
+        line: usize,
+        key: String,
+    },
+    InvalidValue {
+        line: usize,
+        value: String,
+    },
+    UnknownPreset(String),
+    /// `base` after a cost key, which it would silently overwrite.
+    LateBase {
+        line: usize,
+    },
+}
+
+impl fmt::Display for CostTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed { line } => {
+                write!(f, "line {line}: expected `key = value`")
+            }
+            Self::UnknownKey { line, key } => write!(
+                f,
+                "line {line}: unknown cost {key:?}; expected one of {}",
+                CostTable::KEYS.join(", ")
+            ),
+            Self::InvalidValue { line, value } => write!(
+                f,
+                "line {line}: invalid cycle count {value:?}"
+            ),
+            Self::UnknownPreset(name) => write!(
+                f,
+                "unknown cost table preset {name:?}; expected rv32im or precompiles"
+            ),
+            Self::LateBase { line } => {
+                write!(f, "line {line}: `base` must come before any cost")
+            }
+        }
+    }
+}
+
+impl Error for CostTableError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        count, count_with_source, estimate, keccak_permutations, record,
+        sha256, sha256_compressions, CostTable, CostTableError, OpCounts,
+        Source, SHAKE256_RATE,
+    };
+
+    #[test]
+    fn padding_decides_block_counts() {
+        assert_eq!(sha256_compressions(0), 1);
+        assert_eq!(sha256_compressions(55), 1);
+        assert_eq!(sha256_compressions(56), 2);
+        assert_eq!(sha256_compressions(64), 2);
+        assert_eq!(keccak_permutations(0, SHAKE256_RATE, 32), 1);
+        assert_eq!(keccak_permutations(135, SHAKE256_RATE, 32), 1);
+        assert_eq!(keccak_permutations(136, SHAKE256_RATE, 32), 2);
+        assert_eq!(keccak_permutations(0, SHAKE256_RATE, 272), 2);
+
+        let ntt = OpCounts::ntt(256, 2);
+        assert_eq!(ntt.ntt_butterflies, 2 * 128 * 8);
+        assert_eq!(ntt.modmuls, ntt.ntt_butterflies);
+    }
+
+    #[test]
+    fn only_active_scopes_record() {
+        sha256(100);
+        let ((), outer) = count(|| {
+            sha256(32);
+            let ((), inner) = count(|| record(OpCounts::modmuls(5)));
+            assert_eq!(inner, OpCounts::modmuls(5));
+        });
+        assert_eq!(outer, OpCounts::sha256(32) + OpCounts::modmuls(5));
+        assert_eq!(count(|| ()).1, OpCounts::default());
+    }
+
+    #[test]
+    fn estimates_mark_their_scope_and_the_outer_one() {
+        let ((), counts, source) = count_with_source(|| sha256(32));
+        assert_eq!((counts, source), (OpCounts::sha256(32), Source::Counted));
+
+        let ((), outer, outer_source) = count_with_source(|| {
+            sha256(32);
+            let ((), _, inner) =
+                count_with_source(|| estimate(OpCounts::modmuls(5)));
+            assert_eq!(inner, Source::Estimated);
+        });
+        assert_eq!(outer, OpCounts::sha256(32) + OpCounts::modmuls(5));
+        assert_eq!(outer_source, Source::Estimated);
+    }
+
+    #[test]
+    fn cost_tables_override_a_base_preset() {
+        let table = CostTable::parse(
+            "# measured\nbase = precompiles\nsha256_compression = 68 # ok\n\nmodmul=4\n",
+        )
+        .unwrap();
+        assert_eq!(
+            table,
+            CostTable {
+                sha256_compression: 68,
+                modmul: 4,
+                ..CostTable::PRECOMPILES
+            }
+        );
+        let counts = OpCounts::sha256(32).times(2) + OpCounts::modmuls(10);
+        assert_eq!(table.cycles(&counts), 2 * 68 + 10 * 4 + 64);
+
+        assert_eq!(
+            CostTable::parse("modmul = 1\nbase = rv32im"),
+            Err(CostTableError::LateBase { line: 2 })
+        );
+        assert!(matches!(
+            CostTable::parse("blake3 = 10"),
+            Err(CostTableError::UnknownKey { line: 1, .. })
+        ));
+        assert!(matches!(
+            CostTable::parse("modmul = -1"),
+            Err(CostTableError::InvalidValue { line: 1, .. })
+        ));
+        assert_eq!(
+            CostTable::parse("base = sp1"),
+            Err(CostTableError::UnknownPreset("sp1".into()))
+        );
+    }
+}
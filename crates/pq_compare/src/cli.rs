@@ -0,0 +1,705 @@
+use crate::Filter;
+use pq_bench_core::history::{DEFAULT_DIR, DEFAULT_THRESHOLD_PERCENT};
+use pq_bench_core::report::OutputFormat;
+use pq_bench_core::stats::{
+    parse_outlier_mad, parse_samples, parse_warmup, SampleConfig,
+};
+use pq_bench_core::BENCH_MESSAGE_SIZES;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: pq-compare <command> [options]
+
+Commands:
+  list                 List the schemes that can be benchmarked
+  run                  Benchmark schemes and print one comparison table
+  compare              Compare two stored runs and fail on a regression
+  history              List the commits with stored results
+  report               Render a stored run as Markdown, or rewrite the
+                       generated README sections with it
+  zkvm                 Count the primitive operations of one verify and
+                       estimate its zkVM cycles
+  help                 Show this message
+
+Options for `list`:
+  --pitfalls           Also list each scheme's known implementation pitfalls
+  and the filters below
+
+Filters for `list`, `run` and `zkvm`:
+  --min-category <n>   Keep NIST security category n (1-5) and above
+  --min-quantum-bits <n>
+                       Keep schemes estimated at n bits or more against a
+                       quantum attacker
+  --stateful           Keep stateful schemes only
+  --stateless          Keep stateless schemes only
+  --assumption <text>  Keep schemes whose hardness assumption or its family
+                       (lattice, hash-based) contains the text
+  --standardized       Keep schemes implemented as a published FIPS, RFC
+                       or SP specifies
+
+Options for `run`:
+  --scheme <names>     Comma-separated parameter sets or algorithm names
+                       (repeatable; default: every scheme)
+  --sizes <bytes>      Comma-separated message sizes (default: 32,256,1024,4096)
+  --iterations <n>     Operations timed per scheme and size (default: 10)
+  --warmup <n>         Untimed operations before timing starts (default: 3)
+  --outlier-mad <k>    Drop samples more than k scaled MADs from the median,
+                       or `off` to keep every sample (default: 3)
+  --format <format>    table, json or csv (default: table)
+  --history <dir>      Also append the results to <dir>/results.jsonl
+  --baseline <commit>  Compare against this stored commit and fail on a
+                       regression (history default: bench-history)
+  --threshold <pct>    Allowed growth per metric (default: 10)
+
+Options for `zkvm`:
+  --scheme <names>     As for `run`
+  --sizes <bytes>      As for `run`
+  --cost-table <table> Cycles per operation: rv32im, precompiles, or a file
+                       of `key = value` lines (default: rv32im)
+
+Options for `compare`:
+  --history <dir>      Results directory (default: bench-history)
+  --baseline <commit>  Stored commit or unique prefix (default: the commit
+                       recorded before --current)
+  --current <commit>   Stored commit or unique prefix (default: the newest)
+  --threshold <pct>    Allowed growth per metric (default: 10)
+
+Options for `history`:
+  --history <dir>      Results directory (default: bench-history)
+
+Options for `report`:
+  --history <dir>      Results directory (default: bench-history)
+  --commit <commit>    Stored commit or unique prefix (default: the newest)
+  --write              Rewrite the marked sections of README files instead
+                       of printing the table
+  --readme <path>      README to rewrite (repeatable; default: README.md
+                       and crates/*/README.md)
+";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    List(ListOptions),
+    Run(RunOptions),
+    Compare(CompareOptions),
+    History(PathBuf),
+    Report(ReportOptions),
+    Zkvm(ZkvmOptions),
+    Help,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ListOptions {
+    pub filter: Filter,
+    pub pitfalls: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunOptions {
+    /// Empty selects every scheme.
+    pub schemes: Vec<String>,
+    /// Applied after `schemes`.
+    pub filter: Filter,
+    pub message_sizes: Vec<usize>,
+    pub sampling: SampleConfig,
+    pub format: OutputFormat,
+    /// Where results are appended, if anywhere.
+    pub history: Option<PathBuf>,
+    pub baseline: Option<String>,
+    pub threshold_percent: f64,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            schemes: Vec::new(),
+            filter: Filter::default(),
+            message_sizes: BENCH_MESSAGE_SIZES.to_vec(),
+            sampling: SampleConfig::default(),
+            format: OutputFormat::Text,
+            history: None,
+            baseline: None,
+            threshold_percent: DEFAULT_THRESHOLD_PERCENT,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZkvmOptions {
+    /// Empty selects every scheme.
+    pub schemes: Vec<String>,
+    /// Applied after `schemes`.
+    pub filter: Filter,
+    pub message_sizes: Vec<usize>,
+    /// A preset name or a cost table file; `None` is the default preset.
+    pub cost_table: Option<String>,
+}
+
+impl Default for ZkvmOptions {
+    fn default() -> Self {
+        Self {
+            schemes: Vec::new(),
+            filter: Filter::default(),
+            message_sizes: BENCH_MESSAGE_SIZES.to_vec(),
+            cost_table: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompareOptions {
+    pub history: PathBuf,
+    /// `None` picks the commit recorded before `current`.
+    pub baseline: Option<String>,
+    /// `None` picks the newest commit.
+    pub current: Option<String>,
+    pub threshold_percent: f64,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            history: PathBuf::from(DEFAULT_DIR),
+            baseline: None,
+            current: None,
+            threshold_percent: DEFAULT_THRESHOLD_PERCENT,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ArgsError {
+    UnknownCommand(String),
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidValue { option: String, value: String },
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownCommand(command) => {
+                write!(f, "unknown command {command:?}")
+            }
+            Self::UnknownOption(option) => {
+                write!(f, "unknown option {option:?}")
+            }
+            Self::MissingValue(option) => {
+                write!(f, "{option} needs a value")
+            }
+            Self::InvalidValue { option, value } => {
+                write!(f, "invalid value {value:?} for {option}")
+            }
+        }
+    }
+}
+
+impl Error for ArgsError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReportOptions {
+    pub history: PathBuf,
+    /// `None` picks the newest commit.
+    pub commit: Option<String>,
+    pub write: bool,
+    /// Empty means the workspace READMEs.
+    pub readmes: Vec<PathBuf>,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            history: PathBuf::from(DEFAULT_DIR),
+            commit: None,
+            write: false,
+            readmes: Vec::new(),
+        }
+    }
+}
+
+/// Options that take no value.
+const FLAGS: [&str; 5] = [
+    "--write",
+    "--pitfalls",
+    "--stateful",
+    "--stateless",
+    "--standardized",
+];
+
+const FILTER_OPTIONS: [&str; 6] = [
+    "--min-category",
+    "--min-quantum-bits",
+    "--stateful",
+    "--stateless",
+    "--assumption",
+    "--standardized",
+];
+
+/// Parses the arguments after the program name. `--option value` and
+/// `--option=value` are both accepted.
+pub fn parse_args<I>(args: I) -> Result<Command, ArgsError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let command = match args.next().as_deref() {
+        None | Some("help" | "--help" | "-h") => return Ok(Command::Help),
+        Some("list") => Command::List(ListOptions::default()),
+        Some("run") => Command::Run(RunOptions::default()),
+        Some("compare") => Command::Compare(CompareOptions::default()),
+        Some("history") => Command::History(PathBuf::from(DEFAULT_DIR)),
+        Some("report") => Command::Report(ReportOptions::default()),
+        Some("zkvm") => Command::Zkvm(ZkvmOptions::default()),
+        Some(other) => return Err(ArgsError::UnknownCommand(other.to_owned())),
+    };
+    let allowed: &[&str] = match command {
+        Command::List(_) => &["--pitfalls"],
+        Command::Run(_) => &[
+            "--scheme",
+            "--sizes",
+            "--iterations",
+            "--warmup",
+            "--outlier-mad",
+            "--format",
+            "--history",
+            "--baseline",
+            "--threshold",
+        ],
+        Command::Compare(_) => {
+            &["--history", "--baseline", "--current", "--threshold"]
+        }
+        Command::History(_) => &["--history"],
+        Command::Report(_) => &["--history", "--commit", "--write", "--readme"],
+        Command::Zkvm(_) => &["--scheme", "--sizes", "--cost-table"],
+        _ => &[],
+    };
+    let filterable = matches!(
+        command,
+        Command::List(_) | Command::Run(_) | Command::Zkvm(_)
+    );
+
+    let mut options = Vec::new();
+    while let Some(arg) = args.next() {
+        let (option, inline_value) = match arg.split_once('=') {
+            Some((option, value)) => {
+                (option.to_owned(), Some(value.to_owned()))
+            }
+            None => (arg, None),
+        };
+        let known = allowed.contains(&option.as_str())
+            || filterable && FILTER_OPTIONS.contains(&option.as_str());
+        if !known {
+            return Err(ArgsError::UnknownOption(option));
+        }
+        if FLAGS.contains(&option.as_str()) {
+            match inline_value {
+                Some(value) => return Err(invalid(&option, &value)),
+                None => options.push((option, String::new())),
+            }
+            continue;
+        }
+        let value = inline_value
+            .or_else(|| args.next())
+            .ok_or_else(|| ArgsError::MissingValue(option.clone()))?;
+        options.push((option, value));
+    }
+
+    match command {
+        Command::List(mut list) => {
+            for (option, value) in options {
+                if option == "--pitfalls" {
+                    list.pitfalls = true;
+                } else {
+                    apply_filter_option(&mut list.filter, &option, &value)?;
+                }
+            }
+            Ok(Command::List(list))
+        }
+        Command::Run(run) => apply_run_options(run, options).map(Command::Run),
+        Command::Zkvm(mut zkvm) => {
+            let mut sizes = Sizes::default();
+            for (option, value) in options {
+                match option.as_str() {
+                    "--scheme" => zkvm.schemes.extend(split_list(&value)),
+                    "--sizes" => {
+                        sizes.push(&mut zkvm.message_sizes, &option, &value)?
+                    }
+                    "--cost-table" => zkvm.cost_table = Some(value),
+                    _ => {
+                        apply_filter_option(&mut zkvm.filter, &option, &value)?
+                    }
+                }
+            }
+            if zkvm.message_sizes.is_empty() {
+                return Err(ArgsError::MissingValue("--sizes".to_owned()));
+            }
+            Ok(Command::Zkvm(zkvm))
+        }
+        Command::Compare(mut compare) => {
+            for (option, value) in options {
+                match option.as_str() {
+                    "--history" => compare.history = PathBuf::from(value),
+                    "--baseline" => compare.baseline = Some(value),
+                    "--current" => compare.current = Some(value),
+                    _ => {
+                        compare.threshold_percent =
+                            parse_threshold(&option, &value)?
+                    }
+                }
+            }
+            Ok(Command::Compare(compare))
+        }
+        Command::History(mut dir) => {
+            if let Some((_, value)) = options.pop() {
+                dir = PathBuf::from(value);
+            }
+            Ok(Command::History(dir))
+        }
+        Command::Report(mut report) => {
+            for (option, value) in options {
+                match option.as_str() {
+                    "--history" => report.history = PathBuf::from(value),
+                    "--commit" => report.commit = Some(value),
+                    "--write" => report.write = true,
+                    _ => report.readmes.push(PathBuf::from(value)),
+                }
+            }
+            Ok(Command::Report(report))
+        }
+        other => Ok(other),
+    }
+}
+
+fn apply_run_options(
+    mut run: RunOptions,
+    options: Vec<(String, String)>,
+) -> Result<RunOptions, ArgsError> {
+    let mut sizes = Sizes::default();
+    for (option, value) in options {
+        if FILTER_OPTIONS.contains(&option.as_str()) {
+            apply_filter_option(&mut run.filter, &option, &value)?;
+            continue;
+        }
+        match option.as_str() {
+            "--scheme" => run.schemes.extend(split_list(&value)),
+            "--sizes" => sizes.push(&mut run.message_sizes, &option, &value)?,
+            "--format" => {
+                run.format =
+                    value.parse().map_err(|_| invalid(&option, &value))?;
+            }
+            "--warmup" => {
+                run.sampling.warmup = parse_warmup(&value)
+                    .ok_or_else(|| invalid(&option, &value))?;
+            }
+            "--outlier-mad" => {
+                run.sampling.outlier_mad = parse_outlier_mad(&value)
+                    .ok_or_else(|| invalid(&option, &value))?;
+            }
+            "--iterations" => {
+                run.sampling.samples = parse_samples(&value)
+                    .ok_or_else(|| invalid(&option, &value))?;
+            }
+            "--history" => run.history = Some(PathBuf::from(value)),
+            "--baseline" => run.baseline = Some(value),
+            _ => run.threshold_percent = parse_threshold(&option, &value)?,
+        }
+    }
+    if run.message_sizes.is_empty() {
+        return Err(ArgsError::MissingValue("--sizes".to_owned()));
+    }
+    Ok(run)
+}
+
+/// Collects `--sizes` values; the first occurrence replaces the defaults.
+#[derive(Default)]
+struct Sizes {
+    given: bool,
+}
+
+impl Sizes {
+    fn push(
+        &mut self,
+        sizes: &mut Vec<usize>,
+        option: &str,
+        value: &str,
+    ) -> Result<(), ArgsError> {
+        if !self.given {
+            sizes.clear();
+            self.given = true;
+        }
+        for size in split_list(value) {
+            sizes.push(size.parse().map_err(|_| invalid(option, value))?);
+        }
+        Ok(())
+    }
+}
+
+fn apply_filter_option(
+    filter: &mut Filter,
+    option: &str,
+    value: &str,
+) -> Result<(), ArgsError> {
+    match option {
+        "--min-category" => {
+            filter.min_category = Some(
+                value
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|category| (1..=5).contains(category))
+                    .ok_or_else(|| invalid(option, value))?,
+            );
+        }
+        "--min-quantum-bits" => {
+            filter.min_quantum_bits =
+                Some(value.trim().parse().map_err(|_| invalid(option, value))?);
+        }
+        "--stateful" => filter.stateful = Some(true),
+        "--stateless" => filter.stateful = Some(false),
+        "--assumption" => filter.assumption = Some(value.to_owned()),
+        _ => filter.standardized = true,
+    }
+    Ok(())
+}
+
+fn parse_threshold(option: &str, value: &str) -> Result<f64, ArgsError> {
+    value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|percent: &f64| percent.is_finite() && *percent >= 0.0)
+        .ok_or_else(|| invalid(option, value))
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_owned)
+}
+
+fn invalid(option: &str, value: &str) -> ArgsError {
+    ArgsError::InvalidValue {
+        option: option.to_owned(),
+        value: value.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_args, ArgsError, Command, CompareOptions, ListOptions,
+        ReportOptions, RunOptions, ZkvmOptions,
+    };
+    use crate::Filter;
+    use pq_bench_core::report::OutputFormat;
+    use pq_bench_core::stats::SampleConfig;
+
+    fn parse(args: &[&str]) -> Result<Command, ArgsError> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn run_options_accept_lists_and_inline_values() {
+        let command = parse(&[
+            "run",
+            "--scheme",
+            "Falcon-512,ML-DSA-65",
+            "--scheme=Lamport OTS",
+            "--sizes=32, 4096",
+            "--iterations",
+            "5",
+            "--warmup=0",
+            "--outlier-mad",
+            "off",
+            "--format",
+            "json",
+            "--history=results",
+            "--baseline",
+            "abc123",
+            "--threshold",
+            "5",
+            "--stateless",
+        ])
+        .unwrap();
+        assert_eq!(
+            command,
+            Command::Run(RunOptions {
+                schemes: vec![
+                    "Falcon-512".into(),
+                    "ML-DSA-65".into(),
+                    "Lamport OTS".into()
+                ],
+                filter: Filter {
+                    stateful: Some(false),
+                    ..Filter::default()
+                },
+                message_sizes: vec![32, 4096],
+                sampling: SampleConfig {
+                    warmup: 0,
+                    samples: 5,
+                    outlier_mad: None,
+                },
+                format: OutputFormat::Json,
+                history: Some("results".into()),
+                baseline: Some("abc123".into()),
+                threshold_percent: 5.0,
+            })
+        );
+        assert_eq!(parse(&["run"]), Ok(Command::Run(RunOptions::default())));
+        assert_eq!(parse(&[]), Ok(Command::Help));
+        assert_eq!(parse(&["list"]), Ok(Command::List(ListOptions::default())));
+    }
+
+    #[test]
+    fn list_takes_the_security_filters() {
+        assert_eq!(
+            parse(&[
+                "list",
+                "--min-category=3",
+                "--min-quantum-bits",
+                "128",
+                "--stateful",
+                "--stateless",
+                "--assumption",
+                "lattice",
+                "--standardized",
+                "--pitfalls",
+            ]),
+            Ok(Command::List(ListOptions {
+                filter: Filter {
+                    min_category: Some(3),
+                    min_quantum_bits: Some(128),
+                    stateful: Some(false),
+                    assumption: Some("lattice".into()),
+                    standardized: true,
+                },
+                pitfalls: true,
+            }))
+        );
+        assert_eq!(
+            parse(&["list", "--min-category", "6"]),
+            Err(ArgsError::InvalidValue {
+                option: "--min-category".into(),
+                value: "6".into()
+            })
+        );
+        assert!(matches!(
+            parse(&["list", "--standardized=yes"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+        assert_eq!(
+            parse(&["compare", "--stateful"]),
+            Err(ArgsError::UnknownOption("--stateful".into()))
+        );
+    }
+
+    #[test]
+    fn zkvm_takes_schemes_sizes_filters_and_a_cost_table() {
+        assert_eq!(
+            parse(&[
+                "zkvm",
+                "--scheme=ML-DSA",
+                "--sizes",
+                "32",
+                "--cost-table",
+                "precompiles",
+                "--stateless",
+            ]),
+            Ok(Command::Zkvm(ZkvmOptions {
+                schemes: vec!["ML-DSA".into()],
+                filter: Filter {
+                    stateful: Some(false),
+                    ..Filter::default()
+                },
+                message_sizes: vec![32],
+                cost_table: Some("precompiles".into()),
+            }))
+        );
+        assert_eq!(parse(&["zkvm"]), Ok(Command::Zkvm(ZkvmOptions::default())));
+        assert_eq!(
+            parse(&["zkvm", "--iterations", "5"]),
+            Err(ArgsError::UnknownOption("--iterations".into()))
+        );
+    }
+
+    #[test]
+    fn compare_and_history_take_their_own_options() {
+        assert_eq!(
+            parse(&["compare", "--baseline", "abc", "--threshold=2.5"]),
+            Ok(Command::Compare(CompareOptions {
+                baseline: Some("abc".into()),
+                threshold_percent: 2.5,
+                ..CompareOptions::default()
+            }))
+        );
+        assert_eq!(
+            parse(&["history", "--history", "out"]),
+            Ok(Command::History("out".into()))
+        );
+        assert_eq!(
+            parse(&["compare", "--sizes", "32"]),
+            Err(ArgsError::UnknownOption("--sizes".into()))
+        );
+        assert!(matches!(
+            parse(&["compare", "--threshold", "-1"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+        assert_eq!(
+            parse(&[
+                "report",
+                "--write",
+                "--readme",
+                "README.md",
+                "--commit=ab"
+            ]),
+            Ok(Command::Report(ReportOptions {
+                commit: Some("ab".into()),
+                write: true,
+                readmes: vec!["README.md".into()],
+                ..ReportOptions::default()
+            }))
+        );
+        assert!(matches!(
+            parse(&["report", "--write=yes"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn bad_arguments_are_reported() {
+        assert_eq!(
+            parse(&["bench"]),
+            Err(ArgsError::UnknownCommand("bench".into()))
+        );
+        assert_eq!(
+            parse(&["list", "--scheme", "x"]),
+            Err(ArgsError::UnknownOption("--scheme".into()))
+        );
+        assert_eq!(
+            parse(&["run", "--iterations"]),
+            Err(ArgsError::MissingValue("--iterations".into()))
+        );
+        assert_eq!(
+            parse(&["run", "--iterations", "0"]),
+            Err(ArgsError::InvalidValue {
+                option: "--iterations".into(),
+                value: "0".into()
+            })
+        );
+        assert!(matches!(
+            parse(&["run", "--outlier-mad", "-2"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse(&["run", "--format", "xml"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse(&["run", "--sizes", "32,big"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+    }
+}
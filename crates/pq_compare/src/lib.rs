@@ -0,0 +1,375 @@
+//! The schemes `pq-compare` can benchmark, name selection and the tables it
+//! prints. Measuring is [`pq_bench_core::harness`].
+
+pub mod cli;
+pub mod readme;
+mod table;
+
+pub use table::{
+    render_changes, render_commits, render_pitfalls, render_report,
+    render_schemes, render_table, render_verify_ops,
+};
+
+use pq_bench_core::report::{BenchRecord, Environment};
+use pq_bench_core::zkvm::{OpCounts, Source};
+use pq_signature::DynScheme;
+use std::error::Error;
+use std::fmt;
+
+/// Every scheme with a [`pq_signature::Scheme`] impl, in table order.
+pub fn schemes() -> Vec<Box<dyn DynScheme>> {
+    vec![
+        Box::new(falcon::FALCON512),
+        Box::new(dilithium::ML_DSA_65),
+        Box::new(dilithium::ML_DSA_65_DETERMINISTIC),
+        Box::new(sphincs_plus::SPHINCS_PLUS_SHAKE_128F_SIMPLE),
+        Box::new(lamport_ots::LAMPORT_OTS_SCHEME),
+        Box::new(lamport_ots::LAMPORT_OTS_RANDOMIZED_SCHEME),
+        Box::new(lamport_ots::mss::MSS_LAMPORT_H10),
+    ]
+}
+
+/// Keeps the schemes whose parameter set or algorithm name matches one of
+/// `names`, ignoring ASCII case. An empty `names` keeps every scheme.
+pub fn select(
+    schemes: Vec<Box<dyn DynScheme>>,
+    names: &[String],
+) -> Result<Vec<Box<dyn DynScheme>>, UnknownScheme> {
+    if let Some(unknown) = names.iter().find(|name| {
+        !schemes
+            .iter()
+            .any(|scheme| matches_name(scheme.as_ref(), name))
+    }) {
+        return Err(UnknownScheme(unknown.clone()));
+    }
+    if names.is_empty() {
+        return Ok(schemes);
+    }
+    Ok(schemes
+        .into_iter()
+        .filter(|scheme| {
+            names.iter().any(|name| matches_name(scheme.as_ref(), name))
+        })
+        .collect())
+}
+
+/// Primitive operations of one verify, for one scheme and message size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifyOps {
+    pub param_set: &'static str,
+    pub message_size: usize,
+    pub counts: OpCounts,
+    /// Counted as the verifier ran, or estimated from the specification.
+    pub source: Source,
+}
+
+/// Keeps schemes by their [`pq_signature::SecurityProfile`] and
+/// statefulness. The default keeps every scheme.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Filter {
+    pub min_category: Option<u8>,
+    pub min_quantum_bits: Option<u16>,
+    /// `Some(false)` keeps only stateless schemes.
+    pub stateful: Option<bool>,
+    /// Matched, ignoring ASCII case, against the assumption and its family.
+    pub assumption: Option<String>,
+    /// Keeps only schemes implemented as a published standard specifies.
+    pub standardized: bool,
+}
+
+impl Filter {
+    pub fn matches(&self, scheme: &dyn DynScheme) -> bool {
+        let security = scheme.security();
+        self.min_category
+            .is_none_or(|min| security.nist_category >= min)
+            && self
+                .min_quantum_bits
+                .is_none_or(|min| security.quantum_bits >= min)
+            && self.stateful.is_none_or(|stateful| {
+                scheme.capabilities().stateful == stateful
+            })
+            && self.assumption.as_deref().is_none_or(|needle| {
+                let needle = needle.to_ascii_lowercase();
+                security.assumption.to_ascii_lowercase().contains(&needle)
+                    || security.family.to_string().contains(&needle)
+            })
+            && (!self.standardized
+                || security.standardization.is_standardized())
+    }
+
+    pub fn apply(
+        &self,
+        schemes: Vec<Box<dyn DynScheme>>,
+    ) -> Vec<Box<dyn DynScheme>> {
+        schemes
+            .into_iter()
+            .filter(|scheme| self.matches(scheme.as_ref()))
+            .collect()
+    }
+}
+
+/// The body of a generated README section: [`render_report`] over the
+/// schemes `spec` names, `all` or a comma-separated list as for
+/// `--scheme`.
+pub fn render_section(
+    spec: &str,
+    environment: &Environment,
+    records: &[BenchRecord],
+) -> Result<String, UnknownScheme> {
+    let names: Vec<String> = if spec.eq_ignore_ascii_case("all") {
+        Vec::new()
+    } else {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_owned)
+            .collect()
+    };
+    let selected = select(schemes(), &names)?;
+    Ok(render_report(&selected, environment, records))
+}
+
+fn matches_name(scheme: &dyn DynScheme, name: &str) -> bool {
+    scheme.param_set_name().eq_ignore_ascii_case(name)
+        || scheme.algorithm_name().eq_ignore_ascii_case(name)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownScheme(pub String);
+
+impl fmt::Display for UnknownScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown scheme {:?}; run `pq-compare list` for the available names",
+            self.0
+        )
+    }
+}
+
+impl Error for UnknownScheme {}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_section, schemes, select, Filter, UnknownScheme};
+    use lamport_ots::mss::{MssHeight, MssScheme};
+    use lamport_ots::{LamportVariant, LAMPORT_OTS_SCHEME};
+    use pq_bench_core::harness::{measure, records, verify_ops};
+    use pq_bench_core::report::Environment;
+    use pq_bench_core::stats::SampleConfig;
+    use pq_bench_core::zkvm::{OpCounts, Source};
+    use pq_bench_core::TrackingAllocator;
+    use pq_signature::AssumptionFamily;
+    use std::alloc::System;
+
+    static SYSTEM_ALLOC: System = System;
+
+    #[global_allocator]
+    static GLOBAL: TrackingAllocator<System> =
+        TrackingAllocator::new(&SYSTEM_ALLOC);
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn select_matches_param_sets_and_algorithms_case_insensitively() {
+        let selected =
+            select(schemes(), &names(&["falcon-512", "ML-DSA"])).unwrap();
+        let param_sets: Vec<_> = selected
+            .iter()
+            .map(|scheme| scheme.param_set_name())
+            .collect();
+        assert_eq!(
+            param_sets,
+            ["Falcon-512", "ML-DSA-65", "ML-DSA-65-deterministic"]
+        );
+
+        assert_eq!(select(schemes(), &[]).unwrap().len(), schemes().len());
+        assert!(matches!(
+            select(schemes(), &names(&["RSA-2048"])),
+            Err(UnknownScheme(name)) if name == "RSA-2048"
+        ));
+    }
+
+    #[test]
+    fn filters_select_on_security_metadata() {
+        let param_sets = |filter: Filter| {
+            filter
+                .apply(schemes())
+                .iter()
+                .map(|scheme| scheme.param_set_name())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(param_sets(Filter::default()).len(), schemes().len());
+        assert_eq!(
+            param_sets(Filter {
+                min_category: Some(3),
+                stateful: Some(false),
+                ..Filter::default()
+            }),
+            ["ML-DSA-65", "ML-DSA-65-deterministic"]
+        );
+        assert_eq!(
+            param_sets(Filter {
+                assumption: Some("Hash-Based".into()),
+                min_quantum_bits: Some(100),
+                ..Filter::default()
+            }),
+            ["Lamport-OTS-256-R"]
+        );
+        assert_eq!(
+            param_sets(Filter {
+                assumption: Some("ntru".into()),
+                ..Filter::default()
+            }),
+            ["Falcon-512"]
+        );
+        assert_eq!(
+            param_sets(Filter {
+                standardized: true,
+                stateful: Some(true),
+                ..Filter::default()
+            }),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn every_scheme_records_its_verify_operations() {
+        // H5 instead of H10 keeps key generation quick in debug builds.
+        let mss_h5 = MssScheme::new(MssHeight::H5, LamportVariant::Plain);
+        let mut all = schemes();
+        all.retain(|scheme| scheme.algorithm_name() != mss_h5.algorithm_name());
+        all.push(Box::new(mss_h5));
+        for scheme in &all {
+            let name = scheme.param_set_name();
+            let (counts, source) = verify_ops(scheme.as_ref(), 32).expect(name);
+            assert!(counts.bytes_hashed > 0, "{name}");
+            assert_eq!(
+                counts.ntt_butterflies == 0,
+                scheme.security().family == AssumptionFamily::HashBased,
+                "{name}"
+            );
+            // Only the pure-Rust Lamport verifiers can be instrumented.
+            assert_eq!(
+                source == Source::Counted,
+                name.contains("Lamport"),
+                "{name}"
+            );
+        }
+
+        // ML-DSA-65 (k = 6, l = 5) on a 32-byte message, worked out from
+        // FIPS 204 Algorithm 8. Keccak-f: ExpandA squeezes 5 SHAKE128 blocks
+        // for each of the 30 matrix entries (150), tr absorbs the 1952-byte
+        // key (15), mu absorbs 64 + 2 + 32 bytes (1), SampleInBall one
+        // (1), and the challenge absorbs mu and the 768-byte w1 (7).
+        // Butterflies: 18 NTTs of 128 * 8, for z (5), c (1), t1 (6) and the
+        // inverse per row (6). Modmuls add A * z (30 * 256), c * t1, t1 *
+        // 2^d and the inverse scaling (3 * 6 * 256).
+        let reference = OpCounts {
+            sha256_compressions: 0,
+            keccak_permutations: 174,
+            ntt_butterflies: 18_432,
+            modmuls: 30_720,
+            bytes_hashed: 3_950,
+        };
+        assert_eq!(
+            verify_ops(&dilithium::ML_DSA_65, 32).unwrap(),
+            (reference, Source::Estimated)
+        );
+        // The MSS verify adds the leaf hash and one two-block hash per
+        // level to the one-time verify.
+        let (lamport, _) = verify_ops(&LAMPORT_OTS_SCHEME, 32).unwrap();
+        assert_eq!(
+            verify_ops(&mss_h5, 32).unwrap(),
+            (
+                lamport
+                    + OpCounts::sha256(6 + 512 * 32)
+                    + OpCounts::sha256(66).times(5),
+                Source::Counted
+            )
+        );
+    }
+
+    #[test]
+    fn one_time_schemes_get_a_fresh_key_per_signature() {
+        let scheme = select(schemes(), &names(&["Lamport-OTS-256"]))
+            .unwrap()
+            .remove(0);
+        let config = SampleConfig {
+            warmup: 1,
+            samples: 3,
+            outlier_mad: None,
+        };
+        let rows = measure(scheme.as_ref(), &[32, 256], &config).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        for (row, message_size) in rows.iter().zip([32, 256]) {
+            assert_eq!(row.param_set, "Lamport-OTS-256");
+            assert_eq!(row.message_size, message_size);
+            assert_eq!(row.sign.summary.iterations, 3);
+            assert_eq!(row.verify.summary.iterations, 3);
+            assert_eq!(row.signature_bytes, row.sizes.signature_bytes);
+            assert!(row.sign.memory.allocations > 0);
+        }
+        assert_eq!(rows[0].keygen, rows[1].keygen);
+
+        let records = records(&rows);
+        let keys: Vec<_> = records
+            .iter()
+            .map(|record| (record.operation.as_str(), record.message_size))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                ("keygen", 0),
+                ("sign", 32),
+                ("verify", 32),
+                ("sign", 256),
+                ("verify", 256)
+            ]
+        );
+    }
+
+    #[test]
+    fn report_sections_cover_only_the_named_schemes() {
+        let scheme = select(schemes(), &names(&["Lamport-OTS-256"]))
+            .unwrap()
+            .remove(0);
+        let config = SampleConfig {
+            warmup: 0,
+            samples: 1,
+            outlier_mad: None,
+        };
+        let rows = measure(scheme.as_ref(), &[32], &config).unwrap();
+        let environment = Environment {
+            rustc_version: "rustc 1.92.0".into(),
+            cpu_model: "Test CPU".into(),
+            kernel: "Linux 6.8.0".into(),
+            arch: "x86_64".into(),
+            cores: 1,
+            git_commit: "abc123".into(),
+            timestamp_unix: 0,
+        };
+
+        let section =
+            render_section("lamport ots", &environment, &records(&rows))
+                .unwrap();
+        let lines: Vec<_> = section.lines().collect();
+        assert!(lines[0].contains("from commit `abc123`"));
+        assert!(lines[0].contains("medians of 1 samples"));
+        // Provenance, blank line, header, separator, one row: the
+        // randomized variant has no records.
+        assert_eq!(lines.len(), 5);
+        assert!(lines[4].starts_with("| Lamport-OTS-256 | yes (1/key) |"));
+        assert!(render_section("all", &environment, &[])
+            .unwrap()
+            .ends_with("|\n"));
+        assert_eq!(
+            render_section("RSA", &environment, &[]),
+            Err(UnknownScheme("RSA".into()))
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use pq_bench_core::harness::{measure, records, verify_ops};
+use pq_bench_core::history::{self, Change, HistoryError, DEFAULT_DIR};
+use pq_bench_core::report::{self, BenchRecord, Environment, OutputFormat};
+use pq_bench_core::zkvm::CostTable;
+use pq_bench_core::TrackingAllocator;
+use pq_compare::cli::{
+    parse_args, Command, CompareOptions, ReportOptions, ZkvmOptions, USAGE,
+};
+use pq_compare::readme::rewrite_sections;
+use pq_compare::{
+    render_changes, render_commits, render_pitfalls, render_schemes,
+    render_section, render_table, render_verify_ops, schemes, select,
+    VerifyOps,
+};
+use std::alloc::System;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::{env, fs};
+
+static SYSTEM_ALLOC: System = System;
+
+#[global_allocator]
+static GLOBAL: TrackingAllocator<System> =
+    TrackingAllocator::new(&SYSTEM_ALLOC);
+
+fn main() -> ExitCode {
+    let command = match parse_args(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match command {
+        Command::Help => print!("{USAGE}"),
+        Command::List(options) => {
+            let listed = options.filter.apply(schemes());
+            print!("{}", render_schemes(&listed));
+            if options.pitfalls {
+                print!("{}", render_pitfalls(&listed));
+            }
+        }
+        Command::History(dir) => match history::load(&dir) {
+            Ok(entries) => print!("{}", render_commits(&entries)),
+            Err(err) => {
+                eprintln!("error: {err}");
+                return ExitCode::FAILURE;
+            }
+        },
+        Command::Report(options) => {
+            if let Err(err) = report(&options) {
+                eprintln!("error: {err}");
+                return ExitCode::FAILURE;
+            }
+        }
+        Command::Zkvm(options) => {
+            if let Err(err) = zkvm(&options) {
+                eprintln!("error: {err}");
+                return ExitCode::FAILURE;
+            }
+        }
+        Command::Compare(options) => {
+            return match compare_stored(&options) {
+                Ok(changes) => {
+                    print!("{}", render_changes(&changes));
+                    verdict(&changes)
+                }
+                Err(err) => {
+                    eprintln!("error: {err}");
+                    ExitCode::FAILURE
+                }
+            };
+        }
+        Command::Run(options) => {
+            let selected = match select(schemes(), &options.schemes) {
+                Ok(selected) => options.filter.apply(selected),
+                Err(err) => {
+                    eprintln!("error: {err}");
+                    return ExitCode::from(2);
+                }
+            };
+            if selected.is_empty() {
+                eprintln!("error: no selected scheme matches the filters");
+                return ExitCode::from(2);
+            }
+            // Loaded up front so a bad history fails before the long run.
+            let baseline = match &options.baseline {
+                Some(commit) => {
+                    let dir = options
+                        .history
+                        .as_deref()
+                        .unwrap_or(Path::new(DEFAULT_DIR));
+                    match stored_run(dir, commit) {
+                        Ok(run) => Some(run),
+                        Err(err) => {
+                            eprintln!("error: {err}");
+                            return ExitCode::FAILURE;
+                        }
+                    }
+                }
+                None => None,
+            };
+
+            let mut rows = Vec::new();
+            for scheme in &selected {
+                eprintln!(
+                    "measuring {} ({} warmup, {} samples)...",
+                    scheme.param_set_name(),
+                    options.sampling.warmup,
+                    options.sampling.samples
+                );
+                match measure(
+                    scheme.as_ref(),
+                    &options.message_sizes,
+                    &options.sampling,
+                ) {
+                    Ok(measurements) => rows.extend(measurements),
+                    Err(err) => {
+                        eprintln!("error: {err}");
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+
+            let environment = Environment::capture();
+            let records = records(&rows);
+            match options.format {
+                OutputFormat::Text => print!("{}", render_table(&rows)),
+                format => {
+                    if let Some(out) =
+                        report::render(format, &environment, &records)
+                    {
+                        print!("{out}");
+                    }
+                }
+            }
+            if let Some(dir) = &options.history {
+                if let Err(err) = history::append(dir, &environment, &records) {
+                    eprintln!("error: {err}");
+                    return ExitCode::FAILURE;
+                }
+                eprintln!(
+                    "appended {} records to {}",
+                    records.len(),
+                    dir.display()
+                );
+            }
+            if let Some((baseline_environment, baseline)) = baseline {
+                warn_incomparable(
+                    &baseline_environment,
+                    &environment,
+                    &baseline,
+                    &records,
+                );
+                let changes = history::compare(
+                    &baseline,
+                    &records,
+                    options.threshold_percent,
+                );
+                // Stdout may be carrying JSON or CSV.
+                eprint!("{}", render_changes(&changes));
+                return verdict(&changes);
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn stored_run(
+    dir: &Path,
+    commit: &str,
+) -> Result<(Environment, Vec<BenchRecord>), HistoryError> {
+    let entries = history::load(dir)?;
+    let commit = history::resolve_commit(&entries, commit)?;
+    eprintln!("baseline: {commit}");
+    let environment = history::environment_at(&entries, commit)
+        .expect("resolved commits are stored")
+        .clone();
+    Ok((environment, history::at_commit(&entries, commit)))
+}
+
+fn compare_stored(
+    options: &CompareOptions,
+) -> Result<Vec<Change>, HistoryError> {
+    let entries = history::load(&options.history)?;
+    let commits = history::commits(&entries);
+    let current = match &options.current {
+        Some(current) => history::resolve_commit(&entries, current)?,
+        None => commits.last().copied().ok_or(HistoryError::NoBaseline)?,
+    };
+    let baseline = match &options.baseline {
+        Some(baseline) => history::resolve_commit(&entries, baseline)?,
+        None => history::previous_commit(&entries, current)
+            .ok_or(HistoryError::NoBaseline)?,
+    };
+    eprintln!("baseline: {baseline}\ncurrent:  {current}");
+    let environment = |commit| {
+        history::environment_at(&entries, commit)
+            .expect("resolved commits are stored")
+    };
+    let (baseline_records, current_records) = (
+        history::at_commit(&entries, baseline),
+        history::at_commit(&entries, current),
+    );
+    warn_incomparable(
+        environment(baseline),
+        environment(current),
+        &baseline_records,
+        &current_records,
+    );
+    Ok(history::compare(
+        &baseline_records,
+        &current_records,
+        options.threshold_percent,
+    ))
+}
+
+/// Warns about what `history::compare` cannot see: a different machine or
+/// toolchain, and baseline records the current run did not measure.
+fn warn_incomparable(
+    baseline_environment: &Environment,
+    current_environment: &Environment,
+    baseline: &[BenchRecord],
+    current: &[BenchRecord],
+) {
+    for mismatch in history::environment_mismatches(
+        baseline_environment,
+        current_environment,
+    ) {
+        eprintln!(
+            "warning: {} differs (baseline {:?}, current {:?}); timings \
+             are not comparable",
+            mismatch.field, mismatch.baseline, mismatch.current
+        );
+    }
+    for record in history::missing(baseline, current) {
+        eprintln!(
+            "warning: {} {} ({} B, {} threads) is in the baseline but not \
+             the current run",
+            record.param_set,
+            record.operation,
+            record.message_size,
+            record.threads
+        );
+    }
+}
+
+fn report(options: &ReportOptions) -> Result<(), Box<dyn Error>> {
+    let entries = history::load(&options.history)?;
+    let commit = match &options.commit {
+        Some(commit) => history::resolve_commit(&entries, commit)?,
+        None => {
+            history::commits(&entries).last().copied().ok_or_else(|| {
+                format!("no results in {}", options.history.display())
+            })?
+        }
+    };
+    let environment = history::environment_at(&entries, commit)
+        .expect("resolved commits are stored");
+    let records = history::at_commit(&entries, commit);
+    let render = |spec: &str| render_section(spec, environment, &records);
+
+    if !options.write {
+        print!("{}", render("all")?);
+        return Ok(());
+    }
+    let readmes = if options.readmes.is_empty() {
+        workspace_readmes()?
+    } else {
+        options.readmes.clone()
+    };
+    for path in readmes {
+        let text = fs::read_to_string(&path)
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+        let (rewritten, sections) = rewrite_sections(&text, render)
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+        if rewritten != text {
+            fs::write(&path, rewritten)?;
+            eprintln!("updated {} ({sections} sections)", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn zkvm(options: &ZkvmOptions) -> Result<(), Box<dyn Error>> {
+    let selected = options.filter.apply(select(schemes(), &options.schemes)?);
+    if selected.is_empty() {
+        return Err("no selected scheme matches the filters".into());
+    }
+    let name = options.cost_table.as_deref().unwrap_or("rv32im");
+    let costs = match CostTable::preset(name) {
+        Some(costs) => costs,
+        None => {
+            let text = fs::read_to_string(name)
+                .map_err(|err| format!("{name}: {err}"))?;
+            CostTable::parse(&text).map_err(|err| format!("{name}: {err}"))?
+        }
+    };
+
+    let mut rows = Vec::new();
+    for scheme in &selected {
+        for &message_size in &options.message_sizes {
+            let (counts, source) = verify_ops(scheme.as_ref(), message_size)?;
+            rows.push(VerifyOps {
+                param_set: scheme.param_set_name(),
+                message_size,
+                counts,
+                source,
+            });
+        }
+    }
+    eprintln!("cost table: {name}");
+    print!("{}", render_verify_ops(&rows, &costs));
+    Ok(())
+}
+
+/// `README.md` and `crates/*/README.md` under the working directory.
+fn workspace_readmes() -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut readmes = vec![PathBuf::from("README.md")];
+    let mut crates = fs::read_dir("crates")?
+        .map(|entry| Ok(entry?.path().join("README.md")))
+        .collect::<Result<Vec<_>, std::io::Error>>()?;
+    crates.retain(|path| path.is_file());
+    crates.sort();
+    readmes.extend(crates);
+    Ok(readmes)
+}
+
+/// Fails when any metric regressed.
+fn verdict(changes: &[Change]) -> ExitCode {
+    let regressions = changes.iter().filter(|change| change.regressed).count();
+    if regressions == 0 {
+        eprintln!("no regressions in {} compared metrics", changes.len());
+        ExitCode::SUCCESS
+    } else {
+        eprintln!("{regressions} of {} metrics regressed", changes.len());
+        ExitCode::FAILURE
+    }
+}
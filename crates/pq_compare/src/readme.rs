@@ -0,0 +1,161 @@
+//! Generated README sections. A section is delimited by marker lines
+//!
+//! ```text
+//! <!-- pq-compare:begin all -->
+//! ...
+//! <!-- pq-compare:end -->
+//! ```
+//!
+//! where the begin marker names the schemes to include: `all`, or
+//! comma-separated parameter sets or algorithm names as accepted by
+//! `--scheme`. Everything between the markers is replaced; the markers stay.
+//! Markers must be alone on their line and outside fenced code blocks, so
+//! documentation can show them.
+
+use std::error::Error;
+use std::fmt;
+
+const BEGIN_PREFIX: &str = "<!-- pq-compare:begin ";
+const MARKER_SUFFIX: &str = "-->";
+pub const END_MARKER: &str = "<!-- pq-compare:end -->";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SectionError {
+    /// A begin marker on this line has no end marker after it.
+    Unterminated {
+        line: usize,
+    },
+    /// An end marker on this line has no begin marker before it.
+    Unopened {
+        line: usize,
+    },
+    Render {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for SectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unterminated { line } => {
+                write!(f, "section starting on line {line} has no end marker")
+            }
+            Self::Unopened { line } => {
+                write!(f, "end marker on line {line} has no begin marker")
+            }
+            Self::Render { line, message } => {
+                write!(f, "section starting on line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl Error for SectionError {}
+
+/// Replaces the body of every section in `text` with `render(spec)`, where
+/// `spec` is the text after `pq-compare:begin`. Returns the new text and
+/// the number of sections rewritten.
+pub fn rewrite_sections<E: fmt::Display>(
+    text: &str,
+    mut render: impl FnMut(&str) -> Result<String, E>,
+) -> Result<(String, usize), SectionError> {
+    let mut out = String::with_capacity(text.len());
+    let mut in_fence = false;
+    // Line number of the begin marker of the section being skipped.
+    let mut open: Option<usize> = None;
+    let mut sections = 0;
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        let number = index + 1;
+        let trimmed = line.trim();
+        if open.is_none() && trimmed.starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence {
+            out.push_str(line);
+            continue;
+        }
+
+        if trimmed == END_MARKER {
+            if open.take().is_none() {
+                return Err(SectionError::Unopened { line: number });
+            }
+        } else if open.is_some() {
+            continue;
+        } else if let Some(spec) = trimmed
+            .strip_prefix(BEGIN_PREFIX)
+            .and_then(|rest| rest.strip_suffix(MARKER_SUFFIX))
+        {
+            let body =
+                render(spec.trim()).map_err(|err| SectionError::Render {
+                    line: number,
+                    message: err.to_string(),
+                })?;
+            out.push_str(line);
+            out.push('\n');
+            out.push_str(body.trim_end());
+            out.push_str("\n\n");
+            open = Some(number);
+            sections += 1;
+            continue;
+        }
+        out.push_str(line);
+    }
+    match open {
+        Some(line) => Err(SectionError::Unterminated { line }),
+        None => Ok((out, sections)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rewrite_sections, SectionError};
+
+    #[test]
+    fn section_bodies_are_replaced_and_markers_kept() {
+        let text = "# Title\n\n<!-- pq-compare:begin Falcon-512 -->\nold\nnumbers\n<!-- pq-compare:end -->\n\n```markdown\n<!-- pq-compare:begin all -->\n<!-- pq-compare:end -->\n```\n  <!-- pq-compare:begin all -->\n  <!-- pq-compare:end -->\n";
+        let mut specs = Vec::new();
+        let (out, sections) = rewrite_sections(text, |spec| {
+            specs.push(spec.to_owned());
+            Ok::<_, String>(format!("| {spec} |\n"))
+        })
+        .unwrap();
+
+        // The fenced example is left alone.
+        assert_eq!(sections, 2);
+        assert_eq!(specs, ["Falcon-512", "all"]);
+        assert_eq!(
+            out,
+            "# Title\n\n<!-- pq-compare:begin Falcon-512 -->\n\n| Falcon-512 |\n\n<!-- pq-compare:end -->\n\n```markdown\n<!-- pq-compare:begin all -->\n<!-- pq-compare:end -->\n```\n  <!-- pq-compare:begin all -->\n\n| all |\n\n  <!-- pq-compare:end -->\n"
+        );
+        // Rewriting is idempotent.
+        let (again, _) = rewrite_sections(&out, |spec| {
+            Ok::<_, String>(format!("| {spec} |"))
+        })
+        .unwrap();
+        assert_eq!(again, out);
+    }
+
+    #[test]
+    fn broken_sections_are_reported_with_their_line() {
+        let render = |_: &str| Ok::<_, String>(String::new());
+        assert_eq!(
+            rewrite_sections("a\n<!-- pq-compare:begin all -->\nb\n", render),
+            Err(SectionError::Unterminated { line: 2 })
+        );
+        assert_eq!(
+            rewrite_sections("a\n\n<!-- pq-compare:end -->\n", render),
+            Err(SectionError::Unopened { line: 3 })
+        );
+        assert_eq!(
+            rewrite_sections(
+                "\n\n<!-- pq-compare:begin RSA -->\n<!-- pq-compare:end -->",
+                |spec| Err(format!("unknown scheme {spec}"))
+            ),
+            Err(SectionError::Render {
+                line: 3,
+                message: "unknown scheme RSA".into()
+            })
+        );
+    }
+}
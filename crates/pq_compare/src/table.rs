@@ -0,0 +1,411 @@
+use crate::VerifyOps;
+use pq_bench_core::harness::Measurement;
+use pq_bench_core::history::{commits, Change, Entry};
+use pq_bench_core::report::{BenchRecord, Environment};
+use pq_bench_core::zkvm::CostTable;
+use pq_signature::DynScheme;
+use std::time::Duration;
+
+/// Markdown table of median timings, sizes and peak heap usage, one row per
+/// scheme and message size.
+pub fn render_table(measurements: &[Measurement]) -> String {
+    let headers = [
+        "Scheme",
+        "Backend",
+        "Msg (B)",
+        "Keygen",
+        "Sign",
+        "Verify",
+        "PK (B)",
+        "SK (B)",
+        "Sig (B)",
+        "Keygen peak (B)",
+        "Sign peak (B)",
+        "Verify peak (B)",
+    ];
+    let rows = measurements
+        .iter()
+        .map(|row| {
+            vec![
+                row.param_set.to_owned(),
+                row.backend.to_owned(),
+                row.message_size.to_string(),
+                format_duration(row.keygen.summary.median),
+                format_duration(row.sign.summary.median),
+                format_duration(row.verify.summary.median),
+                row.sizes.public_key_bytes.to_string(),
+                row.sizes.secret_key_bytes.to_string(),
+                row.signature_bytes.to_string(),
+                row.keygen.memory.peak_bytes.to_string(),
+                row.sign.memory.peak_bytes.to_string(),
+                row.verify.memory.peak_bytes.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    render(&headers, 2, &rows)
+}
+
+/// Markdown table of verify operation counts, where they came from, and the
+/// cycles `costs` estimates for them.
+pub fn render_verify_ops(rows: &[VerifyOps], costs: &CostTable) -> String {
+    let headers = [
+        "Scheme",
+        "Counts",
+        "Msg (B)",
+        "SHA-256",
+        "Keccak-f",
+        "Butterflies",
+        "Modmuls",
+        "Hashed (B)",
+        "Est. cycles",
+    ];
+    let rows = rows
+        .iter()
+        .map(|row| {
+            let counts = row.counts;
+            vec![
+                row.param_set.to_owned(),
+                row.source.to_string(),
+                row.message_size.to_string(),
+                counts.sha256_compressions.to_string(),
+                counts.keccak_permutations.to_string(),
+                counts.ntt_butterflies.to_string(),
+                counts.modmuls.to_string(),
+                counts.bytes_hashed.to_string(),
+                costs.cycles(&counts).to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    render(&headers, 2, &rows)
+}
+
+/// Markdown table of the schemes `pq-compare list` can select.
+pub fn render_schemes(schemes: &[Box<dyn DynScheme>]) -> String {
+    let headers = [
+        "Scheme",
+        "Algorithm",
+        "Backend",
+        "Stateful",
+        "Assumption",
+        "Standard",
+        "NIST",
+        "Classical (bits)",
+        "Quantum (bits)",
+        "Max sigs/key",
+        "PK (B)",
+        "SK (B)",
+        "Max sig (B)",
+    ];
+    let rows = schemes
+        .iter()
+        .map(|scheme| {
+            let capabilities = scheme.capabilities();
+            let security = scheme.security();
+            let sizes = scheme.sizes();
+            vec![
+                scheme.param_set_name().to_owned(),
+                scheme.algorithm_name().to_owned(),
+                scheme.backend_name().to_owned(),
+                if capabilities.stateful { "yes" } else { "no" }.to_owned(),
+                security.assumption.to_owned(),
+                security.standardization.to_string(),
+                security.nist_category.to_string(),
+                security.classical_bits.to_string(),
+                security.quantum_bits.to_string(),
+                capabilities
+                    .max_signatures_per_key
+                    .map_or_else(|| "unlimited".to_owned(), |n| n.to_string()),
+                sizes.public_key_bytes.to_string(),
+                sizes.secret_key_bytes.to_string(),
+                sizes.signature_bytes.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    render(&headers, 6, &rows)
+}
+
+/// Markdown list of each scheme's known implementation pitfalls, under one
+/// heading per parameter set.
+pub fn render_pitfalls(schemes: &[Box<dyn DynScheme>]) -> String {
+    let mut out = String::new();
+    for scheme in schemes {
+        out.push_str(&format!("\n### {}\n\n", scheme.param_set_name()));
+        for pitfall in scheme.security().pitfalls {
+            out.push_str(&format!("- {pitfall}\n"));
+        }
+    }
+    out
+}
+
+/// A provenance line and a Markdown table of the stored `records` of
+/// `schemes`: median timings, declared sizes, per-operation peak heap
+/// usage, statefulness, NIST category, assumption and standard. Schemes
+/// without records are left out.
+pub fn render_report(
+    schemes: &[Box<dyn DynScheme>],
+    environment: &Environment,
+    records: &[BenchRecord],
+) -> String {
+    let headers = [
+        "Scheme",
+        "Stateful",
+        "Security",
+        "Assumption",
+        "Standard",
+        "Msg (B)",
+        "Keygen",
+        "Sign",
+        "Verify",
+        "PK (B)",
+        "SK (B)",
+        "Sig (B)",
+        "Sign peak (B)",
+        "Verify peak (B)",
+    ];
+    let mut rows = Vec::new();
+    let mut samples = 0;
+    for scheme in schemes {
+        let param_set = scheme.param_set_name();
+        let find = |operation: &str, message_size: usize| {
+            records.iter().find(|record| {
+                record.param_set == param_set
+                    && record.operation == operation
+                    && record.message_size == message_size
+            })
+        };
+        let mut message_sizes: Vec<usize> = records
+            .iter()
+            .filter(|record| {
+                record.param_set == param_set && record.operation == "sign"
+            })
+            .map(|record| record.message_size)
+            .collect();
+        message_sizes.sort_unstable();
+        message_sizes.dedup();
+
+        let capabilities = scheme.capabilities();
+        let sizes = scheme.sizes();
+        let security = scheme.security();
+        for message_size in message_sizes {
+            let (Some(sign), Some(verify)) =
+                (find("sign", message_size), find("verify", message_size))
+            else {
+                continue;
+            };
+            samples = samples.max(sign.summary.iterations);
+            let median = |record: Option<&BenchRecord>| match record {
+                Some(record) => format_duration(record.summary.median),
+                None => "-".to_owned(),
+            };
+            rows.push(vec![
+                param_set.to_owned(),
+                match (
+                    capabilities.stateful,
+                    capabilities.max_signatures_per_key,
+                ) {
+                    (false, _) => "no".to_owned(),
+                    (true, Some(max)) => format!("yes ({max}/key)"),
+                    (true, None) => "yes".to_owned(),
+                },
+                format!("NIST {}", security.nist_category),
+                security.assumption.to_owned(),
+                security.standardization.to_string(),
+                message_size.to_string(),
+                median(find("keygen", 0)),
+                median(Some(sign)),
+                median(Some(verify)),
+                sizes.public_key_bytes.to_string(),
+                sizes.secret_key_bytes.to_string(),
+                sizes.signature_bytes.to_string(),
+                sign.memory.peak_bytes.to_string(),
+                verify.memory.peak_bytes.to_string(),
+            ]);
+        }
+    }
+
+    format!(
+        "_Generated by `pq-compare report` from commit `{}` ({}, {}, {} {}). Times are medians of {samples} samples; sizes are the declared maximums._\n\n{}",
+        environment.git_commit,
+        environment.rustc_version,
+        environment.cpu_model,
+        environment.kernel,
+        environment.arch,
+        render(&headers, 5, &rows)
+    )
+}
+
+/// Markdown table of a baseline comparison, one row per metric.
+pub fn render_changes(changes: &[Change]) -> String {
+    let headers = [
+        "Status",
+        "Scheme",
+        "Operation",
+        "Metric",
+        "Msg (B)",
+        "Threads",
+        "Baseline",
+        "Current",
+        "Change",
+    ];
+    let rows = changes
+        .iter()
+        .map(|change| {
+            vec![
+                if change.regressed { "REGRESSED" } else { "ok" }.to_owned(),
+                change.param_set.clone(),
+                change.operation.clone(),
+                change.metric.to_owned(),
+                change.message_size.to_string(),
+                change.threads.to_string(),
+                change.baseline.to_string(),
+                change.current.to_string(),
+                format_ratio(change.ratio),
+            ]
+        })
+        .collect::<Vec<_>>();
+    render(&headers, 4, &rows)
+}
+
+/// Markdown table of the commits in a results history, oldest first.
+pub fn render_commits(entries: &[Entry]) -> String {
+    let headers = ["Commit", "rustc", "CPU", "Records", "Recorded (unix)"];
+    let rows = commits(entries)
+        .into_iter()
+        .map(|commit| {
+            let mut matching = entries
+                .iter()
+                .filter(|entry| entry.environment.git_commit == commit);
+            let first = matching.next().expect("commit came from entries");
+            vec![
+                commit.to_owned(),
+                first.environment.rustc_version.clone(),
+                first.environment.cpu_model.clone(),
+                (matching.count() + 1).to_string(),
+                first.environment.timestamp_unix.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    render(&headers, 3, &rows)
+}
+
+/// Pads every column to its widest cell. Columns from `first_numeric` on
+/// are right-aligned.
+fn render(
+    headers: &[&str],
+    first_numeric: usize,
+    rows: &[Vec<String>],
+) -> String {
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: Vec<String>| {
+        let cells = cells
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, &width))| {
+                if column >= first_numeric {
+                    format!("{cell:>width$}")
+                } else {
+                    format!("{cell:<width$}")
+                }
+            })
+            .collect::<Vec<_>>();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut out = line(headers.iter().map(|h| h.to_string()).collect());
+    let separator = widths
+        .iter()
+        .enumerate()
+        .map(|(column, &width)| {
+            if column >= first_numeric {
+                format!("{}:", "-".repeat(width + 1))
+            } else {
+                "-".repeat(width + 2)
+            }
+        })
+        .collect::<Vec<_>>();
+    out.push_str(&format!("|{}|\n", separator.join("|")));
+    for row in rows {
+        out.push_str(&line(row.clone()));
+    }
+    out
+}
+
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    if nanos < 1_000 {
+        format!("{nanos} ns")
+    } else if nanos < 1_000_000 {
+        format!("{:.2} µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.2} ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2} s", nanos as f64 / 1e9)
+    }
+}
+
+fn format_ratio(ratio: f64) -> String {
+    if ratio.is_infinite() {
+        "new".to_owned()
+    } else {
+        format!("{:+.1}%", ratio * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        format_duration, format_ratio, render_pitfalls, render_schemes,
+    };
+    use crate::schemes;
+    use std::time::Duration;
+
+    #[test]
+    fn durations_pick_a_readable_unit() {
+        assert_eq!(format_duration(Duration::from_nanos(850)), "850 ns");
+        assert_eq!(format_duration(Duration::from_nanos(41_250)), "41.25 µs");
+        assert_eq!(format_duration(Duration::from_micros(3_100)), "3.10 ms");
+        assert_eq!(format_duration(Duration::from_millis(2_500)), "2.50 s");
+
+        assert_eq!(format_ratio(0.1234), "+12.3%");
+        assert_eq!(format_ratio(-0.05), "-5.0%");
+        assert_eq!(format_ratio(f64::INFINITY), "new");
+    }
+
+    #[test]
+    fn scheme_list_is_an_aligned_markdown_table() {
+        let table = render_schemes(&schemes());
+        let lines: Vec<_> = table.lines().collect();
+
+        assert_eq!(lines.len(), 2 + schemes().len());
+        assert!(lines[0].starts_with("| Scheme "));
+        assert!(lines[1].starts_with("|---"));
+        assert!(lines[1].ends_with(":|"));
+        let width = lines[0].chars().count();
+        assert!(lines.iter().all(|line| line.chars().count() == width));
+        assert!(lines.iter().any(|line| {
+            line.starts_with("| MSS-Lamport-256-H10 ")
+                && line.contains(" 1024 ")
+        }));
+        assert!(lines.iter().any(|line| {
+            line.starts_with("| ML-DSA-65 ")
+                && line.contains(" Module-LWE and Module-SIS ")
+                && line.contains(" FIPS 204 ")
+                && line.contains(" 182 |")
+                && line.contains(" 165 |")
+        }));
+
+        let pitfalls = render_pitfalls(&schemes()[..1]);
+        assert!(pitfalls.starts_with("\n### Falcon-512\n\n- "));
+        assert_eq!(pitfalls.lines().filter(|l| l.starts_with("- ")).count(), 3);
+    }
+}
@@ -0,0 +1,497 @@
+//! CMS `SignedData` (RFC 5652) carrying a single post-quantum signature.
+//!
+//! Each document has one `SignerInfo`, identified by subject key
+//! identifier, with `contentType` and `messageDigest` signed attributes. The
+//! signature covers the DER `SET OF` those attributes, as RFC 5652 §5.4
+//! requires, and its algorithm identifier is the same OID [`crate::pkix`]
+//! uses for the key. Digests follow the per-algorithm CMS profiles:
+//! SHA-512 for ML-DSA (RFC 9882), SHAKE128 with 256-bit output for
+//! SLH-DSA-SHAKE-128f (RFC 9814) and SHA-256 for HSS/LMS and XMSS
+//! (RFC 9708, RFC 9802). Round-3 SPHINCS+ has no CMS profile; it takes the
+//! SLH-DSA-SHAKE-128f digest under its own experimental OID, since its
+//! signatures are not SLH-DSA signatures.
+//!
+//! As in [`crate::cose`], signing and verification are delegated to the
+//! caller, so any scheme that signs a byte string plugs in. ML-DSA and
+//! SLH-DSA sign with an empty context string.
+
+use crate::pkix::KeyAlgorithm;
+use cms::cert::x509::attr::Attribute;
+use cms::cert::x509::ext::pkix::SubjectKeyIdentifier;
+use cms::content_info::{CmsVersion, ContentInfo};
+use cms::signed_data::{
+    EncapsulatedContentInfo, SignedAttributes, SignedData, SignerIdentifier,
+    SignerInfo, SignerInfos,
+};
+use der::asn1::{Any, ObjectIdentifier, OctetString, SetOfVec};
+use der::{Decode, Encode};
+use sha2::{Digest, Sha256, Sha512};
+use sha3::digest::{ExtendableOutput, Update};
+use sha3::Shake128;
+use spki::AlgorithmIdentifierOwned;
+use std::error::Error;
+use std::fmt;
+
+const ID_DATA: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.1");
+const ID_SIGNED_DATA: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+const ID_CONTENT_TYPE: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.3");
+const ID_MESSAGE_DIGEST: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.4");
+
+const ID_SHA256: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+const ID_SHA512: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.3");
+const ID_SHAKE128: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.11");
+
+const SHAKE128_DIGEST_SIZE: usize = 32;
+
+/// The digest algorithm paired with `algorithm` in its CMS profile.
+pub fn digest_algorithm(algorithm: KeyAlgorithm) -> ObjectIdentifier {
+    match algorithm {
+        KeyAlgorithm::MlDsa65 => ID_SHA512,
+        KeyAlgorithm::SlhDsaShake128f
+        | KeyAlgorithm::SphincsPlusShake128fSimple => ID_SHAKE128,
+        KeyAlgorithm::HssLms | KeyAlgorithm::Xmss | KeyAlgorithm::XmssMt => {
+            ID_SHA256
+        }
+    }
+}
+
+fn digest(algorithm: KeyAlgorithm, content: &[u8]) -> Vec<u8> {
+    match algorithm {
+        KeyAlgorithm::MlDsa65 => Sha512::digest(content).to_vec(),
+        KeyAlgorithm::SlhDsaShake128f
+        | KeyAlgorithm::SphincsPlusShake128fSimple => {
+            let mut hasher = Shake128::default();
+            hasher.update(content);
+            hasher.finalize_boxed(SHAKE128_DIGEST_SIZE).into_vec()
+        }
+        KeyAlgorithm::HssLms | KeyAlgorithm::Xmss | KeyAlgorithm::XmssMt => {
+            Sha256::digest(content).to_vec()
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CmsError {
+    Der(der::Error),
+    Malformed(&'static str),
+    UnknownAlgorithm(ObjectIdentifier),
+    UnexpectedParameters {
+        algorithm: KeyAlgorithm,
+    },
+    UnsupportedDigest {
+        algorithm: KeyAlgorithm,
+        digest: ObjectIdentifier,
+    },
+    /// The content is detached; supply it to verify.
+    DetachedContent,
+    DigestMismatch,
+    Signer(Box<dyn Error + Send + Sync>),
+    VerificationFailed,
+}
+
+impl fmt::Display for CmsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Der(err) => write!(f, "DER error: {err}"),
+            Self::Malformed(what) => {
+                write!(f, "malformed CMS structure: {what}")
+            }
+            Self::UnknownAlgorithm(oid) => {
+                write!(f, "unknown signature algorithm OID {oid}")
+            }
+            Self::UnexpectedParameters { algorithm } => write!(
+                f,
+                "{} algorithm identifier must not carry parameters",
+                algorithm.name()
+            ),
+            Self::UnsupportedDigest { algorithm, digest } => write!(
+                f,
+                "digest algorithm {digest} is not used with {}",
+                algorithm.name()
+            ),
+            Self::DetachedContent => {
+                write!(f, "content is detached; supply it to verify")
+            }
+            Self::DigestMismatch => {
+                write!(f, "messageDigest does not match the content")
+            }
+            Self::Signer(err) => write!(f, "signer failed: {err}"),
+            Self::VerificationFailed => {
+                write!(f, "signature verification failed")
+            }
+        }
+    }
+}
+
+impl Error for CmsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Signer(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<der::Error> for CmsError {
+    fn from(err: der::Error) -> Self {
+        Self::Der(err)
+    }
+}
+
+/// A CMS `SignedData` with one signer and `id-data` content.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CmsSignedData {
+    algorithm: KeyAlgorithm,
+    subject_key_id: Vec<u8>,
+    content: Option<Vec<u8>>,
+    signed_attrs: SignedAttributes,
+    message_digest: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+impl CmsSignedData {
+    /// Signs `content` and encapsulates it in the document.
+    ///
+    /// `signer` receives the DER-encoded signed attributes and returns the
+    /// raw signature bytes of `algorithm`.
+    pub fn sign<E>(
+        algorithm: KeyAlgorithm,
+        subject_key_id: &[u8],
+        content: &[u8],
+        signer: impl FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+    ) -> Result<Self, CmsError>
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let mut signed_data =
+            Self::sign_detached(algorithm, subject_key_id, content, signer)?;
+        signed_data.content = Some(content.to_vec());
+        Ok(signed_data)
+    }
+
+    /// Signs `content` without encapsulating it (`eContent` is absent).
+    pub fn sign_detached<E>(
+        algorithm: KeyAlgorithm,
+        subject_key_id: &[u8],
+        content: &[u8],
+        signer: impl FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+    ) -> Result<Self, CmsError>
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let message_digest = digest(algorithm, content);
+        let signed_attrs = SetOfVec::try_from(vec![
+            Attribute {
+                oid: ID_CONTENT_TYPE,
+                values: SetOfVec::try_from(vec![Any::encode_from(&ID_DATA)?])?,
+            },
+            Attribute {
+                oid: ID_MESSAGE_DIGEST,
+                values: SetOfVec::try_from(vec![Any::encode_from(
+                    &OctetString::new(message_digest.as_slice())?,
+                )?])?,
+            },
+        ])?;
+        let signature = signer(&signed_attrs.to_der()?)
+            .map_err(|err| CmsError::Signer(err.into()))?;
+        Ok(Self {
+            algorithm,
+            subject_key_id: subject_key_id.to_vec(),
+            content: None,
+            signed_attrs,
+            message_digest,
+            signature,
+        })
+    }
+
+    pub fn algorithm(&self) -> KeyAlgorithm {
+        self.algorithm
+    }
+
+    pub fn subject_key_id(&self) -> &[u8] {
+        &self.subject_key_id
+    }
+
+    pub fn content(&self) -> Option<&[u8]> {
+        self.content.as_deref()
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Verifies against the encapsulated content. `verifier` receives the
+    /// DER-encoded signed attributes and the signature.
+    pub fn verify(
+        &self,
+        verifier: impl FnOnce(&[u8], &[u8]) -> bool,
+    ) -> Result<(), CmsError> {
+        let content =
+            self.content.as_deref().ok_or(CmsError::DetachedContent)?;
+        self.verify_detached(content, verifier)
+    }
+
+    /// Checks `messageDigest` against `content`, then the signature.
+    pub fn verify_detached(
+        &self,
+        content: &[u8],
+        verifier: impl FnOnce(&[u8], &[u8]) -> bool,
+    ) -> Result<(), CmsError> {
+        if digest(self.algorithm, content) != self.message_digest {
+            return Err(CmsError::DigestMismatch);
+        }
+        if verifier(&self.signed_attrs.to_der()?, &self.signature) {
+            Ok(())
+        } else {
+            Err(CmsError::VerificationFailed)
+        }
+    }
+
+    /// Encodes the document as a DER `ContentInfo`.
+    pub fn to_der(&self) -> Result<Vec<u8>, CmsError> {
+        let digest_alg = AlgorithmIdentifierOwned {
+            oid: digest_algorithm(self.algorithm),
+            parameters: None,
+        };
+        let signer_info = SignerInfo {
+            version: CmsVersion::V3,
+            sid: SignerIdentifier::SubjectKeyIdentifier(SubjectKeyIdentifier(
+                OctetString::new(self.subject_key_id.as_slice())?,
+            )),
+            digest_alg: digest_alg.clone(),
+            signed_attrs: Some(self.signed_attrs.clone()),
+            signature_algorithm: AlgorithmIdentifierOwned {
+                oid: self.algorithm.oid(),
+                parameters: None,
+            },
+            signature: OctetString::new(self.signature.as_slice())?,
+            unsigned_attrs: None,
+        };
+        let econtent = self
+            .content
+            .as_deref()
+            .map(|content| Any::encode_from(&OctetString::new(content)?))
+            .transpose()?;
+        let signed_data = SignedData {
+            version: CmsVersion::V3,
+            digest_algorithms: SetOfVec::try_from(vec![digest_alg])?,
+            encap_content_info: EncapsulatedContentInfo {
+                econtent_type: ID_DATA,
+                econtent,
+            },
+            certificates: None,
+            crls: None,
+            signer_infos: SignerInfos(SetOfVec::try_from(vec![signer_info])?),
+        };
+        Ok(ContentInfo {
+            content_type: ID_SIGNED_DATA,
+            content: Any::encode_from(&signed_data)?,
+        }
+        .to_der()?)
+    }
+
+    /// Decodes a DER `ContentInfo` holding a single-signer `SignedData`.
+    pub fn from_der(bytes: &[u8]) -> Result<Self, CmsError> {
+        let content_info = ContentInfo::from_der(bytes)?;
+        if content_info.content_type != ID_SIGNED_DATA {
+            return Err(CmsError::Malformed("content type is not SignedData"));
+        }
+        let signed_data: SignedData = content_info.content.decode_as()?;
+        let encap = signed_data.encap_content_info;
+        if encap.econtent_type != ID_DATA {
+            return Err(CmsError::Malformed("eContentType is not id-data"));
+        }
+        let content = encap
+            .econtent
+            .map(|econtent| {
+                econtent
+                    .decode_as::<OctetString>()
+                    .map(OctetString::into_bytes)
+            })
+            .transpose()?;
+
+        let [signer_info] =
+            <[SignerInfo; 1]>::try_from(signed_data.signer_infos.0.into_vec())
+                .map_err(|_| {
+                    CmsError::Malformed("expected exactly one SignerInfo")
+                })?;
+        let subject_key_id = match signer_info.sid {
+            SignerIdentifier::SubjectKeyIdentifier(ski) => ski.0.into_bytes(),
+            SignerIdentifier::IssuerAndSerialNumber(_) => {
+                return Err(CmsError::Malformed(
+                    "signer is not identified by subject key identifier",
+                ))
+            }
+        };
+
+        let oid = signer_info.signature_algorithm.oid;
+        let algorithm = KeyAlgorithm::from_oid(&oid)
+            .ok_or(CmsError::UnknownAlgorithm(oid))?;
+        if signer_info.signature_algorithm.parameters.is_some() {
+            return Err(CmsError::UnexpectedParameters { algorithm });
+        }
+        if signer_info.digest_alg.oid != digest_algorithm(algorithm) {
+            return Err(CmsError::UnsupportedDigest {
+                algorithm,
+                digest: signer_info.digest_alg.oid,
+            });
+        }
+
+        let signed_attrs = signer_info
+            .signed_attrs
+            .ok_or(CmsError::Malformed("signed attributes are missing"))?;
+        let content_type: ObjectIdentifier =
+            single_value(&signed_attrs, ID_CONTENT_TYPE, "contentType")?
+                .decode_as()?;
+        if content_type != ID_DATA {
+            return Err(CmsError::Malformed(
+                "contentType attribute does not match eContentType",
+            ));
+        }
+        let message_digest =
+            single_value(&signed_attrs, ID_MESSAGE_DIGEST, "messageDigest")?
+                .decode_as::<OctetString>()?
+                .into_bytes();
+
+        Ok(Self {
+            algorithm,
+            subject_key_id,
+            content,
+            signed_attrs,
+            message_digest,
+            signature: signer_info.signature.into_bytes(),
+        })
+    }
+
+    /// Encoded size minus the signature and any encapsulated content: the
+    /// bytes CMS adds on top of the raw signature.
+    pub fn envelope_overhead(&self) -> Result<usize, CmsError> {
+        let content_len = self.content.as_ref().map_or(0, Vec::len);
+        Ok(self.to_der()?.len() - self.signature.len() - content_len)
+    }
+}
+
+fn single_value<'a>(
+    attrs: &'a SignedAttributes,
+    oid: ObjectIdentifier,
+    name: &'static str,
+) -> Result<&'a Any, CmsError> {
+    let mut matching = attrs.iter().filter(|attr| attr.oid == oid);
+    match (matching.next(), matching.next()) {
+        (Some(attr), None) if attr.values.len() == 1 => {
+            Ok(attr.values.iter().next().expect("length checked"))
+        }
+        (None, _) => Err(CmsError::Malformed(name)),
+        _ => Err(CmsError::Malformed("attribute must have a single value")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CmsError, CmsSignedData};
+    use crate::pkix::KeyAlgorithm;
+    use dilithium::{SignatureScheme as _, ML_DSA_65};
+    use pqcrypto_sphincsplus::sphincsshake128fsimple::{
+        detached_sign, verify_detached_signature, DetachedSignature,
+    };
+    use pqcrypto_traits::sign::DetachedSignature as _;
+    use sphincs_plus::{SignatureScheme as _, SPHINCS_PLUS_SHAKE_128F_SIMPLE};
+    use std::convert::Infallible;
+
+    const CONTEXT: &[u8] = &[];
+
+    #[test]
+    fn ml_dsa_detached_signed_data_round_trips_and_verifies() {
+        let keypair = ML_DSA_65.keypair(&dilithium::default_seed());
+        let image = b"bootloader stage 2";
+
+        let signed_data = CmsSignedData::sign_detached(
+            KeyAlgorithm::MlDsa65,
+            b"key-1",
+            image,
+            |attrs| {
+                ML_DSA_65
+                    .sign(ML_DSA_65.signing_key(&keypair), attrs, CONTEXT)
+                    .map(|signature| ML_DSA_65.encode_signature(&signature))
+            },
+        )
+        .expect("signing should succeed");
+        let der = signed_data.to_der().expect("should encode");
+        let decoded = CmsSignedData::from_der(&der).expect("should decode");
+        assert_eq!(decoded, signed_data);
+        assert_eq!(decoded.algorithm(), KeyAlgorithm::MlDsa65);
+        assert_eq!(decoded.subject_key_id(), b"key-1");
+        assert_eq!(decoded.content(), None);
+
+        let verifier = |attrs: &[u8], signature: &[u8]| {
+            ML_DSA_65
+                .decode_signature(signature)
+                .is_ok_and(|signature| {
+                    ML_DSA_65.verify(
+                        ML_DSA_65.verifying_key(&keypair),
+                        attrs,
+                        CONTEXT,
+                        &signature,
+                    )
+                })
+        };
+        assert!(decoded.verify_detached(image, verifier).is_ok());
+        assert!(matches!(
+            decoded.verify_detached(b"tampered", verifier),
+            Err(CmsError::DigestMismatch)
+        ));
+        assert!(matches!(
+            decoded.verify(verifier),
+            Err(CmsError::DetachedContent)
+        ));
+    }
+
+    #[test]
+    fn sphincs_plus_encapsulated_signed_data_verifies() {
+        let (public_key, secret_key) = SPHINCS_PLUS_SHAKE_128F_SIMPLE.keypair();
+        let signed_data = CmsSignedData::sign(
+            KeyAlgorithm::SphincsPlusShake128fSimple,
+            b"key-2",
+            b"kernel",
+            |attrs| {
+                let signature = detached_sign(attrs, &secret_key);
+                Ok::<_, Infallible>(signature.as_bytes().to_vec())
+            },
+        )
+        .expect("signing is infallible");
+        let decoded = CmsSignedData::from_der(&signed_data.to_der().unwrap())
+            .expect("should decode");
+        assert_eq!(decoded.content(), Some(b"kernel".as_slice()));
+        assert_eq!(decoded.algorithm().oid().to_string(), "1.3.9999.6.7.13");
+
+        let result = decoded.verify(|attrs, signature| {
+            DetachedSignature::from_bytes(signature).is_ok_and(|signature| {
+                verify_detached_signature(&signature, attrs, &public_key)
+                    .is_ok()
+            })
+        });
+        assert!(result.is_ok());
+        assert!(matches!(
+            decoded.verify(|_, _| false),
+            Err(CmsError::VerificationFailed)
+        ));
+    }
+
+    #[test]
+    fn signer_errors_are_reported() {
+        let result = CmsSignedData::sign_detached(
+            KeyAlgorithm::HssLms,
+            b"key-3",
+            b"payload",
+            |_| Err("key exhausted"),
+        );
+        assert!(matches!(result, Err(CmsError::Signer(_))));
+    }
+}
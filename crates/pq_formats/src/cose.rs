@@ -0,0 +1,568 @@
+//! COSE_Sign1 messages and COSE_Key public keys (RFC 9052) for the
+//! post-quantum schemes.
+//!
+//! Signing and verification are delegated to the caller, so any scheme
+//! that signs a byte string plugs in: this module only builds the CBOR
+//! `Sig_structure` to be signed and the envelope around the signature.
+//! All maps are written in RFC 8949 core deterministic order.
+
+use ciborium::value::{Integer, Value};
+use std::error::Error;
+use std::fmt;
+
+const COSE_SIGN1_TAG: u64 = 18;
+const SIGNATURE1_CONTEXT: &str = "Signature1";
+
+const HEADER_ALG: i64 = 1;
+const HEADER_KID: i64 = 4;
+
+const KEY_KTY: i64 = 1;
+const KEY_KID: i64 = 2;
+const KEY_ALG: i64 = 3;
+const KEY_PUB: i64 = -1;
+
+/// RFC 8778 key type for HSS/LMS public keys.
+const KTY_HSS_LMS: i64 = 5;
+/// Algorithm Key Pair key type used by ML-DSA, SLH-DSA and SPHINCS+.
+const KTY_AKP: i64 = 7;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoseAlgorithm {
+    /// `-49`, draft-ietf-cose-dilithium (IANA early allocation).
+    MlDsa65,
+    /// No COSE identifier is requested for this parameter set yet, so it
+    /// uses the private-use value `-65537`. Only for FIPS 205 signatures.
+    SlhDsaShake128f,
+    /// `-65538`, private use: the round-3 SPHINCS+ submission that the
+    /// `sphincs_plus` crate wraps. Its signatures are not SLH-DSA
+    /// signatures, so they must not carry an SLH-DSA identifier.
+    SphincsPlusShake128fSimple,
+    /// `-46`, RFC 8778.
+    HssLms,
+}
+
+impl CoseAlgorithm {
+    pub const ALL: [Self; 4] = [
+        Self::MlDsa65,
+        Self::SlhDsaShake128f,
+        Self::SphincsPlusShake128fSimple,
+        Self::HssLms,
+    ];
+
+    pub fn id(self) -> i64 {
+        match self {
+            Self::MlDsa65 => -49,
+            Self::SlhDsaShake128f => -65537,
+            Self::SphincsPlusShake128fSimple => -65538,
+            Self::HssLms => -46,
+        }
+    }
+
+    pub fn from_id(id: i64) -> Option<Self> {
+        Self::ALL.into_iter().find(|algorithm| algorithm.id() == id)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::MlDsa65 => "ML-DSA-65",
+            Self::SlhDsaShake128f => "SLH-DSA-SHAKE-128f",
+            Self::SphincsPlusShake128fSimple => "SPHINCS+-SHAKE-128f-simple",
+            Self::HssLms => "HSS-LMS",
+        }
+    }
+
+    fn key_type(self) -> i64 {
+        match self {
+            Self::MlDsa65
+            | Self::SlhDsaShake128f
+            | Self::SphincsPlusShake128fSimple => KTY_AKP,
+            Self::HssLms => KTY_HSS_LMS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoseError {
+    Cbor(String),
+    Malformed(&'static str),
+    UnsupportedAlgorithm(i64),
+    KeyTypeMismatch {
+        algorithm: CoseAlgorithm,
+        key_type: i64,
+    },
+    DetachedPayload,
+    VerificationFailed,
+}
+
+impl fmt::Display for CoseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cbor(err) => write!(f, "CBOR error: {err}"),
+            Self::Malformed(what) => {
+                write!(f, "malformed COSE structure: {what}")
+            }
+            Self::UnsupportedAlgorithm(id) => {
+                write!(f, "unsupported COSE algorithm {id}")
+            }
+            Self::KeyTypeMismatch {
+                algorithm,
+                key_type,
+            } => write!(
+                f,
+                "key type {key_type} does not match algorithm {}",
+                algorithm.name()
+            ),
+            Self::DetachedPayload => {
+                write!(f, "payload is detached; supply it to verify")
+            }
+            Self::VerificationFailed => {
+                write!(f, "signature verification failed")
+            }
+        }
+    }
+}
+
+impl Error for CoseError {}
+
+/// A COSE_Sign1 message with an embedded or detached payload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoseSign1 {
+    algorithm: CoseAlgorithm,
+    protected: Vec<u8>,
+    key_id: Option<Vec<u8>>,
+    payload: Option<Vec<u8>>,
+    signature: Vec<u8>,
+}
+
+impl CoseSign1 {
+    /// Signs `payload` and embeds it in the message.
+    ///
+    /// `signer` receives the encoded `Sig_structure` and returns the raw
+    /// signature bytes of `algorithm`.
+    pub fn sign<E>(
+        algorithm: CoseAlgorithm,
+        key_id: Option<&[u8]>,
+        payload: &[u8],
+        external_aad: &[u8],
+        signer: impl FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+    ) -> Result<Self, E> {
+        let mut message = Self::sign_detached(
+            algorithm,
+            key_id,
+            payload,
+            external_aad,
+            signer,
+        )?;
+        message.payload = Some(payload.to_vec());
+        Ok(message)
+    }
+
+    /// Signs `payload` without embedding it (`payload` is `nil`).
+    pub fn sign_detached<E>(
+        algorithm: CoseAlgorithm,
+        key_id: Option<&[u8]>,
+        payload: &[u8],
+        external_aad: &[u8],
+        signer: impl FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+    ) -> Result<Self, E> {
+        let protected =
+            to_cbor(&Value::Map(vec![(int(HEADER_ALG), int(algorithm.id()))]));
+        let signature =
+            signer(&sig_structure(&protected, external_aad, payload))?;
+        Ok(Self {
+            algorithm,
+            protected,
+            key_id: key_id.map(<[u8]>::to_vec),
+            payload: None,
+            signature,
+        })
+    }
+
+    pub fn algorithm(&self) -> CoseAlgorithm {
+        self.algorithm
+    }
+
+    pub fn key_id(&self) -> Option<&[u8]> {
+        self.key_id.as_deref()
+    }
+
+    pub fn payload(&self) -> Option<&[u8]> {
+        self.payload.as_deref()
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Verifies an embedded payload. `verifier` receives the encoded
+    /// `Sig_structure` and the signature.
+    pub fn verify(
+        &self,
+        external_aad: &[u8],
+        verifier: impl FnOnce(&[u8], &[u8]) -> bool,
+    ) -> Result<(), CoseError> {
+        let payload =
+            self.payload.as_deref().ok_or(CoseError::DetachedPayload)?;
+        self.verify_detached(payload, external_aad, verifier)
+    }
+
+    pub fn verify_detached(
+        &self,
+        payload: &[u8],
+        external_aad: &[u8],
+        verifier: impl FnOnce(&[u8], &[u8]) -> bool,
+    ) -> Result<(), CoseError> {
+        let tbs = sig_structure(&self.protected, external_aad, payload);
+        if verifier(&tbs, &self.signature) {
+            Ok(())
+        } else {
+            Err(CoseError::VerificationFailed)
+        }
+    }
+
+    /// Encodes the message as a tagged (18) COSE_Sign1.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let unprotected = match &self.key_id {
+            Some(key_id) => {
+                vec![(int(HEADER_KID), Value::Bytes(key_id.clone()))]
+            }
+            None => Vec::new(),
+        };
+        let payload = match &self.payload {
+            Some(payload) => Value::Bytes(payload.clone()),
+            None => Value::Null,
+        };
+        to_cbor(&Value::Tag(
+            COSE_SIGN1_TAG,
+            Box::new(Value::Array(vec![
+                Value::Bytes(self.protected.clone()),
+                Value::Map(unprotected),
+                payload,
+                Value::Bytes(self.signature.clone()),
+            ])),
+        ))
+    }
+
+    /// Decodes a tagged or untagged COSE_Sign1.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, CoseError> {
+        let value = from_cbor(bytes)?;
+        let value = match value {
+            Value::Tag(COSE_SIGN1_TAG, inner) => *inner,
+            Value::Tag(..) => {
+                return Err(CoseError::Malformed("unexpected tag"))
+            }
+            value => value,
+        };
+        let [protected, unprotected, payload, signature] =
+            <[Value; 4]>::try_from(value.into_array().map_err(|_| {
+                CoseError::Malformed("COSE_Sign1 is not an array")
+            })?)
+            .map_err(|_| {
+                CoseError::Malformed("COSE_Sign1 needs four elements")
+            })?;
+
+        let protected = into_bytes(protected, "protected header")?;
+        let header = from_cbor(&protected)?.into_map().map_err(|_| {
+            CoseError::Malformed("protected header is not a map")
+        })?;
+        let algorithm = algorithm_from(&header, HEADER_ALG)?;
+
+        let unprotected = unprotected.into_map().map_err(|_| {
+            CoseError::Malformed("unprotected header is not a map")
+        })?;
+        let key_id = lookup(&unprotected, HEADER_KID)
+            .map(|value| into_bytes(value.clone(), "kid"))
+            .transpose()?;
+
+        let payload = match payload {
+            Value::Null => None,
+            payload => Some(into_bytes(payload, "payload")?),
+        };
+
+        Ok(Self {
+            algorithm,
+            protected,
+            key_id,
+            payload,
+            signature: into_bytes(signature, "signature")?,
+        })
+    }
+
+    /// Encoded size minus the signature and any embedded payload: the bytes
+    /// COSE adds on top of the raw signature.
+    pub fn envelope_overhead(&self) -> usize {
+        let payload_len = self.payload.as_ref().map_or(0, Vec::len);
+        self.to_vec().len() - self.signature.len() - payload_len
+    }
+}
+
+/// A COSE_Key holding a public key: kty AKP (7) for ML-DSA and SPHINCS+,
+/// HSS-LMS (5) for HSS/LMS, with the key bytes under label `-1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CosePublicKey {
+    pub algorithm: CoseAlgorithm,
+    pub key_id: Option<Vec<u8>>,
+    pub public_key: Vec<u8>,
+}
+
+impl CosePublicKey {
+    pub fn new(algorithm: CoseAlgorithm, public_key: &[u8]) -> Self {
+        Self {
+            algorithm,
+            key_id: None,
+            public_key: public_key.to_vec(),
+        }
+    }
+
+    pub fn with_key_id(mut self, key_id: &[u8]) -> Self {
+        self.key_id = Some(key_id.to_vec());
+        self
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut map = vec![(int(KEY_KTY), int(self.algorithm.key_type()))];
+        if let Some(key_id) = &self.key_id {
+            map.push((int(KEY_KID), Value::Bytes(key_id.clone())));
+        }
+        map.push((int(KEY_ALG), int(self.algorithm.id())));
+        map.push((int(KEY_PUB), Value::Bytes(self.public_key.clone())));
+        to_cbor(&Value::Map(map))
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, CoseError> {
+        let map = from_cbor(bytes)?
+            .into_map()
+            .map_err(|_| CoseError::Malformed("COSE_Key is not a map"))?;
+        let algorithm = algorithm_from(&map, KEY_ALG)?;
+        let key_type = lookup(&map, KEY_KTY)
+            .and_then(as_i64)
+            .ok_or(CoseError::Malformed("missing kty"))?;
+        if key_type != algorithm.key_type() {
+            return Err(CoseError::KeyTypeMismatch {
+                algorithm,
+                key_type,
+            });
+        }
+        let key_id = lookup(&map, KEY_KID)
+            .map(|value| into_bytes(value.clone(), "kid"))
+            .transpose()?;
+        let public_key = lookup(&map, KEY_PUB)
+            .ok_or(CoseError::Malformed("missing public key"))
+            .and_then(|value| into_bytes(value.clone(), "public key"))?;
+
+        Ok(Self {
+            algorithm,
+            key_id,
+            public_key,
+        })
+    }
+}
+
+/// `Sig_structure = ["Signature1", body_protected, external_aad, payload]`.
+fn sig_structure(
+    protected: &[u8],
+    external_aad: &[u8],
+    payload: &[u8],
+) -> Vec<u8> {
+    to_cbor(&Value::Array(vec![
+        Value::Text(SIGNATURE1_CONTEXT.to_string()),
+        Value::Bytes(protected.to_vec()),
+        Value::Bytes(external_aad.to_vec()),
+        Value::Bytes(payload.to_vec()),
+    ]))
+}
+
+fn algorithm_from(
+    map: &[(Value, Value)],
+    label: i64,
+) -> Result<CoseAlgorithm, CoseError> {
+    let id = lookup(map, label)
+        .and_then(as_i64)
+        .ok_or(CoseError::Malformed("missing alg"))?;
+    CoseAlgorithm::from_id(id).ok_or(CoseError::UnsupportedAlgorithm(id))
+}
+
+fn lookup(map: &[(Value, Value)], label: i64) -> Option<&Value> {
+    map.iter()
+        .find(|(key, _)| as_i64(key) == Some(label))
+        .map(|(_, value)| value)
+}
+
+fn as_i64(value: &Value) -> Option<i64> {
+    value
+        .as_integer()
+        .and_then(|integer| i64::try_from(integer).ok())
+}
+
+fn into_bytes(value: Value, what: &'static str) -> Result<Vec<u8>, CoseError> {
+    value.into_bytes().map_err(|_| CoseError::Malformed(what))
+}
+
+fn int(value: i64) -> Value {
+    Value::Integer(Integer::from(value))
+}
+
+fn to_cbor(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::into_writer(value, &mut out)
+        .expect("writing CBOR to a Vec cannot fail");
+    out
+}
+
+fn from_cbor(bytes: &[u8]) -> Result<Value, CoseError> {
+    ciborium::from_reader(bytes).map_err(|err| CoseError::Cbor(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoseAlgorithm, CoseError, CosePublicKey, CoseSign1};
+    use dilithium::{SignatureScheme as _, ML_DSA_65};
+    use pqcrypto_sphincsplus::sphincsshake128fsimple::{
+        detached_sign, verify_detached_signature, DetachedSignature,
+    };
+    use pqcrypto_traits::sign::{DetachedSignature as _, PublicKey};
+    use sphincs_plus::{SignatureScheme as _, SPHINCS_PLUS_SHAKE_128F_SIMPLE};
+    use std::convert::Infallible;
+
+    const CONTEXT: &[u8] = &[];
+
+    #[test]
+    fn ml_dsa_sign1_round_trips_and_verifies() {
+        let keypair = ML_DSA_65.keypair(&dilithium::default_seed());
+        let payload = b"sensor reading";
+
+        let message = CoseSign1::sign(
+            CoseAlgorithm::MlDsa65,
+            Some(b"device-1"),
+            payload,
+            b"aad",
+            |tbs| {
+                ML_DSA_65
+                    .sign(ML_DSA_65.signing_key(&keypair), tbs, CONTEXT)
+                    .map(|signature| ML_DSA_65.encode_signature(&signature))
+            },
+        )
+        .expect("signing should succeed");
+        let encoded = message.to_vec();
+        // Tag 18, four-element array, protected header { 1: -49 }.
+        assert_eq!(&encoded[..6], &[0xd2, 0x84, 0x44, 0xa1, 0x01, 0x38]);
+        assert_eq!(encoded[6], 0x30);
+
+        let decoded = CoseSign1::from_slice(&encoded).expect("should decode");
+        assert_eq!(decoded, message);
+        assert_eq!(decoded.key_id(), Some(b"device-1".as_slice()));
+        assert_eq!(decoded.signature().len(), ML_DSA_65.signature_size());
+
+        let verify = |aad: &[u8]| {
+            decoded.verify(aad, |tbs, signature| {
+                ML_DSA_65
+                    .decode_signature(signature)
+                    .is_ok_and(|signature| {
+                        ML_DSA_65.verify(
+                            ML_DSA_65.verifying_key(&keypair),
+                            tbs,
+                            CONTEXT,
+                            &signature,
+                        )
+                    })
+            })
+        };
+        assert_eq!(verify(b"aad"), Ok(()));
+        assert_eq!(verify(b"other"), Err(CoseError::VerificationFailed));
+        assert_eq!(
+            decoded.envelope_overhead(),
+            encoded.len() - ML_DSA_65.signature_size() - payload.len()
+        );
+    }
+
+    #[test]
+    fn sphincs_plus_detached_sign1_verifies_with_the_payload() {
+        let (public_key, secret_key) = SPHINCS_PLUS_SHAKE_128F_SIMPLE.keypair();
+        let payload = b"firmware image";
+
+        let message = CoseSign1::sign_detached(
+            CoseAlgorithm::SphincsPlusShake128fSimple,
+            None,
+            payload,
+            &[],
+            |tbs| {
+                let signature = detached_sign(tbs, &secret_key);
+                Ok::<_, Infallible>(signature.as_bytes().to_vec())
+            },
+        )
+        .expect("signing is infallible");
+        let decoded =
+            CoseSign1::from_slice(&message.to_vec()).expect("should decode");
+
+        assert_eq!(
+            decoded.algorithm(),
+            CoseAlgorithm::SphincsPlusShake128fSimple
+        );
+        assert_eq!(CoseAlgorithm::from_id(-65538), Some(decoded.algorithm()));
+        assert_eq!(decoded.payload(), None);
+        assert_eq!(
+            decoded.verify(&[], |_, _| true),
+            Err(CoseError::DetachedPayload)
+        );
+        let verify = |payload: &[u8]| {
+            decoded.verify_detached(payload, &[], |tbs, signature| {
+                DetachedSignature::from_bytes(signature).is_ok_and(
+                    |signature| {
+                        verify_detached_signature(&signature, tbs, &public_key)
+                            .is_ok()
+                    },
+                )
+            })
+        };
+        assert_eq!(verify(payload), Ok(()));
+        assert_eq!(
+            verify(b"tampered image"),
+            Err(CoseError::VerificationFailed)
+        );
+    }
+
+    #[test]
+    fn cose_keys_round_trip_with_their_key_types() {
+        let keypair = ML_DSA_65.keypair(&dilithium::default_seed());
+        let public_key =
+            ML_DSA_65.encode_verifying_key(ML_DSA_65.verifying_key(&keypair));
+        let key = CosePublicKey::new(CoseAlgorithm::MlDsa65, &public_key)
+            .with_key_id(b"device-1");
+        let encoded = key.to_vec();
+        // { 1: 7, 2: h'..', 3: -49, -1: h'..' }
+        assert_eq!(&encoded[..3], &[0xa4, 0x01, 0x07]);
+        assert_eq!(CosePublicKey::from_slice(&encoded), Ok(key));
+
+        let (sphincs_public, _) = SPHINCS_PLUS_SHAKE_128F_SIMPLE.keypair();
+        let key = CosePublicKey::new(
+            CoseAlgorithm::SphincsPlusShake128fSimple,
+            sphincs_public.as_bytes(),
+        );
+        assert_eq!(CosePublicKey::from_slice(&key.to_vec()), Ok(key));
+
+        let hss = CosePublicKey::new(CoseAlgorithm::HssLms, &[0x11; 60]);
+        let encoded = hss.to_vec();
+        assert_eq!(&encoded[..3], &[0xa3, 0x01, 0x05]);
+        assert_eq!(CosePublicKey::from_slice(&encoded), Ok(hss));
+    }
+
+    #[test]
+    fn mismatched_key_types_and_unknown_algorithms_are_rejected() {
+        // { 1: 5 (HSS-LMS), 3: -49 (ML-DSA-65), -1: h'00' }
+        let mismatched = [0xa3, 0x01, 0x05, 0x03, 0x38, 0x30, 0x20, 0x41, 0x00];
+        assert_eq!(
+            CosePublicKey::from_slice(&mismatched),
+            Err(CoseError::KeyTypeMismatch {
+                algorithm: CoseAlgorithm::MlDsa65,
+                key_type: 5,
+            })
+        );
+
+        // { 1: 1 (OKP), 3: -8 (EdDSA), -1: h'00' }
+        let eddsa = [0xa3, 0x01, 0x01, 0x03, 0x27, 0x20, 0x41, 0x00];
+        assert_eq!(
+            CosePublicKey::from_slice(&eddsa),
+            Err(CoseError::UnsupportedAlgorithm(-8))
+        );
+    }
+}
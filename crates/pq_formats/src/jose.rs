@@ -0,0 +1,462 @@
+//! JWS compact serialization (RFC 7515) and JWK public keys (RFC 7517) for
+//! the post-quantum schemes.
+//!
+//! Algorithm names and the `AKP` key type follow the JOSE drafts for ML-DSA
+//! (draft-ietf-cose-dilithium) and SLH-DSA (draft-ietf-cose-sphincs-plus).
+//! Round-3 SPHINCS+ signatures are not SLH-DSA signatures, so they carry a
+//! private algorithm name instead.
+//! As in [`crate::cose`], signing and verification are delegated to the
+//! caller: the signer receives the ASCII JWS signing input
+//! `BASE64URL(header) '.' BASE64URL(payload)`. ML-DSA and SLH-DSA sign it
+//! with an empty context string.
+//!
+//! Headers and keys are flat JSON objects with string members, which is all
+//! this module writes or accepts.
+
+use crate::json::{self, JsonError, JsonValue};
+use base64ct::{Base64UrlUnpadded, Encoding};
+use std::error::Error;
+use std::fmt;
+
+const KTY_AKP: &str = "AKP";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JwsAlgorithm {
+    /// `ML-DSA-65`, draft-ietf-cose-dilithium.
+    MlDsa65,
+    /// `SLH-DSA-SHAKE-128f`, following the naming of
+    /// draft-ietf-cose-sphincs-plus. Not registered yet. Only for FIPS 205
+    /// signatures.
+    SlhDsaShake128f,
+    /// `SPHINCS+-SHAKE-128f-simple`, a private name for the round-3
+    /// submission that the `sphincs_plus` crate wraps.
+    SphincsPlusShake128fSimple,
+}
+
+impl JwsAlgorithm {
+    pub const ALL: [Self; 3] = [
+        Self::MlDsa65,
+        Self::SlhDsaShake128f,
+        Self::SphincsPlusShake128fSimple,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::MlDsa65 => "ML-DSA-65",
+            Self::SlhDsaShake128f => "SLH-DSA-SHAKE-128f",
+            Self::SphincsPlusShake128fSimple => "SPHINCS+-SHAKE-128f-simple",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JoseError {
+    Base64(&'static str),
+    Json(&'static str),
+    Malformed(&'static str),
+    UnsupportedAlgorithm(String),
+    KeyTypeMismatch {
+        algorithm: JwsAlgorithm,
+        key_type: String,
+    },
+    VerificationFailed,
+}
+
+impl fmt::Display for JoseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Base64(what) => write!(f, "invalid base64url in {what}"),
+            Self::Json(what) => write!(f, "invalid JSON: {what}"),
+            Self::Malformed(what) => {
+                write!(f, "malformed JOSE structure: {what}")
+            }
+            Self::UnsupportedAlgorithm(name) => {
+                write!(f, "unsupported JOSE algorithm {name:?}")
+            }
+            Self::KeyTypeMismatch {
+                algorithm,
+                key_type,
+            } => write!(
+                f,
+                "key type {key_type:?} does not match algorithm {}",
+                algorithm.name()
+            ),
+            Self::VerificationFailed => {
+                write!(f, "signature verification failed")
+            }
+        }
+    }
+}
+
+impl Error for JoseError {}
+
+pub fn base64url_encode(bytes: &[u8]) -> String {
+    Base64UrlUnpadded::encode_string(bytes)
+}
+
+pub fn base64url_decode(
+    encoded: &str,
+    what: &'static str,
+) -> Result<Vec<u8>, JoseError> {
+    Base64UrlUnpadded::decode_vec(encoded).map_err(|_| JoseError::Base64(what))
+}
+
+/// Length of `len` bytes in unpadded base64url: `ceil(4 * len / 3)`.
+pub fn base64url_len(len: usize) -> usize {
+    (len * 4).div_ceil(3)
+}
+
+/// A JWS in compact serialization with an embedded payload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Jws {
+    algorithm: JwsAlgorithm,
+    key_id: Option<String>,
+    header: String,
+    payload: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+impl Jws {
+    /// Signs `payload` under a protected header holding `alg` and the
+    /// optional `kid`.
+    ///
+    /// `signer` receives the JWS signing input and returns the raw
+    /// signature bytes of `algorithm`.
+    pub fn sign<E>(
+        algorithm: JwsAlgorithm,
+        key_id: Option<&str>,
+        payload: &[u8],
+        signer: impl FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+    ) -> Result<Self, E> {
+        let mut members = vec![("alg", algorithm.name())];
+        if let Some(key_id) = key_id {
+            members.push(("kid", key_id));
+        }
+        let header = base64url_encode(to_json(&members).as_bytes());
+        let signature = signer(signing_input(&header, payload).as_bytes())?;
+        Ok(Self {
+            algorithm,
+            key_id: key_id.map(str::to_owned),
+            header,
+            payload: payload.to_vec(),
+            signature,
+        })
+    }
+
+    pub fn algorithm(&self) -> JwsAlgorithm {
+        self.algorithm
+    }
+
+    pub fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// `verifier` receives the JWS signing input and the signature.
+    pub fn verify(
+        &self,
+        verifier: impl FnOnce(&[u8], &[u8]) -> bool,
+    ) -> Result<(), JoseError> {
+        let input = signing_input(&self.header, &self.payload);
+        if verifier(input.as_bytes(), &self.signature) {
+            Ok(())
+        } else {
+            Err(JoseError::VerificationFailed)
+        }
+    }
+
+    /// `header.payload.signature`, each part base64url without padding.
+    pub fn to_compact(&self) -> String {
+        format!(
+            "{}.{}",
+            signing_input(&self.header, &self.payload),
+            base64url_encode(&self.signature)
+        )
+    }
+
+    pub fn from_compact(compact: &str) -> Result<Self, JoseError> {
+        let mut parts = compact.split('.');
+        let (Some(header), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(JoseError::Malformed("JWS needs three parts"));
+        };
+
+        let header_json =
+            String::from_utf8(base64url_decode(header, "header")?)
+                .map_err(|_| JoseError::Json("header is not UTF-8"))?;
+        let members = from_json(&header_json)?;
+        if lookup(&members, "crit").is_some() {
+            return Err(JoseError::Malformed("critical header parameters"));
+        }
+        let algorithm = algorithm_from(&members)?;
+
+        Ok(Self {
+            algorithm,
+            key_id: lookup(&members, "kid").map(str::to_owned),
+            header: header.to_owned(),
+            payload: base64url_decode(payload, "payload")?,
+            signature: base64url_decode(signature, "signature")?,
+        })
+    }
+
+    /// Base64url length of the signature: what it costs in a token.
+    pub fn encoded_signature_len(&self) -> usize {
+        base64url_len(self.signature.len())
+    }
+
+    /// Compact size minus the raw signature and payload: the bytes JWS adds
+    /// through the header and base64url expansion.
+    pub fn envelope_overhead(&self) -> usize {
+        self.to_compact().len() - self.signature.len() - self.payload.len()
+    }
+}
+
+fn signing_input(header: &str, payload: &[u8]) -> String {
+    format!("{header}.{}", base64url_encode(payload))
+}
+
+/// A JWK holding a public key: kty `AKP`, with the key bytes under `pub`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Jwk {
+    pub algorithm: JwsAlgorithm,
+    pub key_id: Option<String>,
+    pub public_key: Vec<u8>,
+}
+
+impl Jwk {
+    pub fn new(algorithm: JwsAlgorithm, public_key: &[u8]) -> Self {
+        Self {
+            algorithm,
+            key_id: None,
+            public_key: public_key.to_vec(),
+        }
+    }
+
+    pub fn with_key_id(mut self, key_id: &str) -> Self {
+        self.key_id = Some(key_id.to_owned());
+        self
+    }
+
+    /// Members are written in lexicographic order.
+    pub fn to_json(&self) -> String {
+        let public_key = base64url_encode(&self.public_key);
+        let mut members = vec![("alg", self.algorithm.name())];
+        if let Some(key_id) = &self.key_id {
+            members.push(("kid", key_id));
+        }
+        members.push(("kty", KTY_AKP));
+        members.push(("pub", &public_key));
+        to_json(&members)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, JoseError> {
+        let members = from_json(json)?;
+        let algorithm = algorithm_from(&members)?;
+        let key_type = lookup(&members, "kty")
+            .ok_or(JoseError::Malformed("missing kty"))?;
+        if key_type != KTY_AKP {
+            return Err(JoseError::KeyTypeMismatch {
+                algorithm,
+                key_type: key_type.to_owned(),
+            });
+        }
+        if lookup(&members, "priv").is_some() {
+            return Err(JoseError::Malformed("JWK holds a private key"));
+        }
+        let public_key = lookup(&members, "pub")
+            .ok_or(JoseError::Malformed("missing pub"))?;
+        Ok(Self {
+            algorithm,
+            key_id: lookup(&members, "kid").map(str::to_owned),
+            public_key: base64url_decode(public_key, "pub")?,
+        })
+    }
+}
+
+fn algorithm_from(
+    members: &[(String, String)],
+) -> Result<JwsAlgorithm, JoseError> {
+    let name =
+        lookup(members, "alg").ok_or(JoseError::Malformed("missing alg"))?;
+    JwsAlgorithm::from_name(name)
+        .ok_or_else(|| JoseError::UnsupportedAlgorithm(name.to_owned()))
+}
+
+fn lookup<'a>(members: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    members
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn to_json(members: &[(&str, &str)]) -> String {
+    let mut json = String::from("{");
+    for (index, (key, value)) in members.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        json::push_string(&mut json, key);
+        json.push(':');
+        json::push_string(&mut json, value);
+    }
+    json.push('}');
+    json
+}
+
+/// Parses a JSON object whose members are all strings.
+fn from_json(json: &str) -> Result<Vec<(String, String)>, JoseError> {
+    json::parse_flat_object(json)
+        .map_err(|JsonError(what)| JoseError::Json(what))?
+        .into_iter()
+        .map(|(key, value)| match value {
+            JsonValue::String(value) => Ok((key, value)),
+            JsonValue::Number(_) => {
+                Err(JoseError::Json("only string members are supported"))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{base64url_len, JoseError, Jwk, Jws, JwsAlgorithm};
+    use dilithium::{SignatureScheme as _, ML_DSA_65};
+    use pqcrypto_sphincsplus::sphincsshake128fsimple::{
+        detached_sign, verify_detached_signature, DetachedSignature,
+    };
+    use pqcrypto_traits::sign::{DetachedSignature as _, PublicKey};
+    use sphincs_plus::{SignatureScheme as _, SPHINCS_PLUS_SHAKE_128F_SIMPLE};
+    use std::convert::Infallible;
+
+    const CONTEXT: &[u8] = &[];
+
+    #[test]
+    fn ml_dsa_compact_jws_round_trips_and_verifies() {
+        let keypair = ML_DSA_65.keypair(&dilithium::default_seed());
+        let claims = br#"{"sub":"gateway","exp":1900000000}"#;
+
+        let jws =
+            Jws::sign(JwsAlgorithm::MlDsa65, Some("key-1"), claims, |input| {
+                ML_DSA_65
+                    .sign(ML_DSA_65.signing_key(&keypair), input, CONTEXT)
+                    .map(|signature| ML_DSA_65.encode_signature(&signature))
+            })
+            .expect("signing should succeed");
+        let compact = jws.to_compact();
+        // BASE64URL({"alg":"ML-DSA-65","kid":"key-1"})
+        assert!(compact
+            .starts_with("eyJhbGciOiJNTC1EU0EtNjUiLCJraWQiOiJrZXktMSJ9."));
+
+        let decoded = Jws::from_compact(&compact).expect("should decode");
+        assert_eq!(decoded, jws);
+        assert_eq!(decoded.key_id(), Some("key-1"));
+        assert_eq!(
+            decoded.encoded_signature_len(),
+            base64url_len(ML_DSA_65.signature_size())
+        );
+        assert_eq!(
+            decoded.envelope_overhead(),
+            compact.len() - ML_DSA_65.signature_size() - claims.len()
+        );
+
+        let verifier = |input: &[u8], signature: &[u8]| {
+            ML_DSA_65
+                .decode_signature(signature)
+                .is_ok_and(|signature| {
+                    ML_DSA_65.verify(
+                        ML_DSA_65.verifying_key(&keypair),
+                        input,
+                        CONTEXT,
+                        &signature,
+                    )
+                })
+        };
+        assert_eq!(decoded.verify(verifier), Ok(()));
+
+        let (header, rest) = compact.split_once('.').unwrap();
+        let (_, signature) = rest.rsplit_once('.').unwrap();
+        let tampered = format!("{header}.e30.{signature}");
+        let tampered = Jws::from_compact(&tampered).expect("should decode");
+        assert_eq!(
+            tampered.verify(verifier),
+            Err(JoseError::VerificationFailed)
+        );
+    }
+
+    #[test]
+    fn sphincs_plus_jws_verifies() {
+        let (public_key, secret_key) = SPHINCS_PLUS_SHAKE_128F_SIMPLE.keypair();
+        let algorithm = JwsAlgorithm::SphincsPlusShake128fSimple;
+        let jws = Jws::sign(algorithm, None, b"token", |input| {
+            let signature = detached_sign(input, &secret_key);
+            Ok::<_, Infallible>(signature.as_bytes().to_vec())
+        })
+        .expect("signing is infallible");
+        let decoded =
+            Jws::from_compact(&jws.to_compact()).expect("should decode");
+        assert_eq!(decoded.algorithm(), algorithm);
+        assert_eq!(
+            JwsAlgorithm::from_name("SPHINCS+-SHAKE-128f-simple"),
+            Some(algorithm)
+        );
+
+        let result = decoded.verify(|input, signature| {
+            DetachedSignature::from_bytes(signature).is_ok_and(|signature| {
+                verify_detached_signature(&signature, input, &public_key)
+                    .is_ok()
+            })
+        });
+        assert_eq!(result, Ok(()));
+
+        let jwk = Jwk::new(algorithm, public_key.as_bytes());
+        assert_eq!(Jwk::from_json(&jwk.to_json()), Ok(jwk));
+    }
+
+    #[test]
+    fn jwk_round_trips_and_rejects_foreign_keys() {
+        let keypair = ML_DSA_65.keypair(&dilithium::default_seed());
+        let public_key =
+            ML_DSA_65.encode_verifying_key(ML_DSA_65.verifying_key(&keypair));
+        let jwk =
+            Jwk::new(JwsAlgorithm::MlDsa65, &public_key).with_key_id("k\"1");
+        let json = jwk.to_json();
+        assert!(json.starts_with(
+            r#"{"alg":"ML-DSA-65","kid":"k\"1","kty":"AKP","pub":""#
+        ));
+        assert_eq!(Jwk::from_json(&json), Ok(jwk));
+
+        assert_eq!(
+            Jwk::from_json(r#"{"alg":"ML-DSA-65","kty":"OKP","pub":"AA"}"#),
+            Err(JoseError::KeyTypeMismatch {
+                algorithm: JwsAlgorithm::MlDsa65,
+                key_type: "OKP".into(),
+            })
+        );
+        assert_eq!(
+            Jwk::from_json(r#"{"alg":"EdDSA","kty":"OKP"}"#),
+            Err(JoseError::UnsupportedAlgorithm("EdDSA".into()))
+        );
+        assert_eq!(
+            Jws::from_compact("e30.e30"),
+            Err(JoseError::Malformed("JWS needs three parts"))
+        );
+        assert_eq!(
+            Jwk::from_json(r#"{"alg":"ML-DSA-65","alg":"ML-DSA-65"}"#),
+            Err(JoseError::Json("duplicate member name"))
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! The flat JSON objects the workspace reads and writes: JWS headers and
+//! JWKs in [`crate::jose`], and the benchmark history lines of
+//! `pq_bench_core`.
+//!
+//! Member values are strings or unsigned integers. Nesting, arrays, signs,
+//! fractions and the `true`/`false`/`null` literals are rejected.
+
+use std::error::Error;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsonValue {
+    String(String),
+    Number(u128),
+}
+
+/// What made the input fall outside the accepted subset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JsonError(pub &'static str);
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid JSON: {}", self.0)
+    }
+}
+
+impl Error for JsonError {}
+
+/// Parses one object, keeping its members in order. Duplicate member names
+/// are rejected, as RFC 7515 §4 allows.
+pub fn parse_flat_object(
+    json: &str,
+) -> Result<Vec<(String, JsonValue)>, JsonError> {
+    let mut chars = json.chars().peekable();
+    let mut members: Vec<(String, JsonValue)> = Vec::new();
+    skip_whitespace(&mut chars);
+    if chars.next() != Some('{') {
+        return Err(JsonError("expected an object"));
+    }
+    skip_whitespace(&mut chars);
+    if chars.peek() == Some(&'}') {
+        chars.next();
+    } else {
+        loop {
+            skip_whitespace(&mut chars);
+            let key = parse_string(&mut chars)?;
+            skip_whitespace(&mut chars);
+            if chars.next() != Some(':') {
+                return Err(JsonError("expected ':'"));
+            }
+            skip_whitespace(&mut chars);
+            let value = match chars.peek() {
+                Some('"') => JsonValue::String(parse_string(&mut chars)?),
+                Some(c) if c.is_ascii_digit() => {
+                    JsonValue::Number(parse_number(&mut chars)?)
+                }
+                _ => {
+                    return Err(JsonError(
+                        "only string and unsigned integer members are \
+                         supported",
+                    ))
+                }
+            };
+            if members.iter().any(|(existing, _)| *existing == key) {
+                return Err(JsonError("duplicate member name"));
+            }
+            members.push((key, value));
+            skip_whitespace(&mut chars);
+            match chars.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                _ => return Err(JsonError("expected ',' or '}'")),
+            }
+        }
+    }
+    skip_whitespace(&mut chars);
+    if chars.next().is_some() {
+        return Err(JsonError("trailing characters"));
+    }
+    Ok(members)
+}
+
+/// Appends `value` as a JSON string literal.
+pub fn push_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c < ' ' => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars<'_>>) {
+    while chars
+        .peek()
+        .is_some_and(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+    {
+        chars.next();
+    }
+}
+
+fn parse_number(chars: &mut Peekable<Chars<'_>>) -> Result<u128, JsonError> {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    if digits.len() > 1 && digits.starts_with('0') {
+        return Err(JsonError("leading zero in number"));
+    }
+    digits.parse().map_err(|_| JsonError("number out of range"))
+}
+
+fn parse_string(chars: &mut Peekable<Chars<'_>>) -> Result<String, JsonError> {
+    if chars.next() != Some('"') {
+        return Err(JsonError("expected a string"));
+    }
+    let mut value = String::new();
+    loop {
+        match chars.next().ok_or(JsonError("unterminated string"))? {
+            '"' => return Ok(value),
+            '\\' => {
+                let c = match chars.next() {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('/') => '/',
+                    Some('b') => '\u{8}',
+                    Some('f') => '\u{c}',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('u') => parse_unicode_escape(chars)?,
+                    _ => return Err(JsonError("invalid escape")),
+                };
+                value.push(c);
+            }
+            c if c < ' ' => {
+                return Err(JsonError("control character in string"))
+            }
+            c => value.push(c),
+        }
+    }
+}
+
+fn parse_unicode_escape(
+    chars: &mut Peekable<Chars<'_>>,
+) -> Result<char, JsonError> {
+    let hex = |chars: &mut Peekable<Chars<'_>>| {
+        let digits: String = chars.take(4).collect();
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .filter(|_| digits.len() == 4)
+            .ok_or(JsonError("invalid \\u escape"))
+    };
+    let high = hex(chars)?;
+    let code = if (0xd800..0xdc00).contains(&high) {
+        if chars.next() != Some('\\') || chars.next() != Some('u') {
+            return Err(JsonError("unpaired surrogate"));
+        }
+        let low = hex(chars)?;
+        if !(0xdc00..0xe000).contains(&low) {
+            return Err(JsonError("unpaired surrogate"));
+        }
+        0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+    } else {
+        high
+    };
+    char::from_u32(code).ok_or(JsonError("invalid \\u escape"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_flat_object, push_string, JsonError, JsonValue};
+
+    #[test]
+    fn flat_objects_round_trip_strings_and_numbers() {
+        let mut json = String::from("{");
+        push_string(&mut json, "cpu");
+        json.push_str(": ");
+        push_string(&mut json, "EPYC \"Genoa\"\t\u{1}\u{1f600}");
+        json.push_str(", \"cores\": 96}");
+
+        assert_eq!(
+            parse_flat_object(&json),
+            Ok(vec![
+                (
+                    "cpu".into(),
+                    JsonValue::String("EPYC \"Genoa\"\t\u{1}\u{1f600}".into())
+                ),
+                ("cores".into(), JsonValue::Number(96)),
+            ])
+        );
+        assert_eq!(
+            parse_flat_object(r#" { "a" : "😀" } "#),
+            Ok(vec![("a".into(), JsonValue::String("\u{1f600}".into()))])
+        );
+        assert_eq!(parse_flat_object("{}"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn inputs_outside_the_subset_are_rejected() {
+        for (json, reason) in [
+            (r#"{"a":1,"a":2}"#, "duplicate member name"),
+            (
+                r#"{"a":-1}"#,
+                "only string and unsigned integer members are supported",
+            ),
+            (
+                r#"{"a":true}"#,
+                "only string and unsigned integer members are supported",
+            ),
+            (r#"{"a":01}"#, "leading zero in number"),
+            (r#"{"a":"\ud83d"}"#, "unpaired surrogate"),
+            (r#"{"a":"b"} x"#, "trailing characters"),
+            (r#"["a"]"#, "expected an object"),
+        ] {
+            assert_eq!(
+                parse_flat_object(json),
+                Err(JsonError(reason)),
+                "{json}"
+            );
+        }
+    }
+}
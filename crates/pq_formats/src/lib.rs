@@ -0,0 +1,7 @@
+//! Standard key and signature containers for the schemes in this workspace.
+
+pub mod cms;
+pub mod cose;
+pub mod jose;
+pub mod json;
+pub mod pkix;
@@ -0,0 +1,629 @@
+//! PKCS#8 private keys (RFC 5958) and SubjectPublicKeyInfo public keys
+//! (RFC 5280), as DER or PEM.
+//!
+//! Keys go in and come out as the raw bytes each scheme already exports:
+//! the encoded ML-DSA verifying key and 32-byte seed, the FIPS 205 SLH-DSA
+//! keys, and the RFC 8554 / RFC 8391 HSS and XMSS key encodings. Algorithm
+//! parameters are always absent, as the IETF profiles require.
+//!
+//! HSS/LMS and XMSS/XMSS^MT have no standardized private key format. Their
+//! PKCS#8 documents carry the raw private key bytes and only round-trip
+//! through this module.
+
+use der::asn1::{AnyRef, BitStringRef, ContextSpecificRef, OctetStringRef};
+use der::pem::LineEnding;
+use der::{Decode, Encode, EncodePem, Tag, TagMode, TagNumber, Tagged};
+use ml_dsa::{KeyGen, MlDsa65, B32};
+use pkcs8::{ObjectIdentifier, PrivateKeyInfo};
+use spki::{AlgorithmIdentifierRef, SubjectPublicKeyInfoRef};
+use std::error::Error;
+use std::fmt;
+use zeroize::{Zeroize, Zeroizing};
+
+const PUBLIC_KEY_PEM_LABEL: &str = "PUBLIC KEY";
+const PRIVATE_KEY_PEM_LABEL: &str = "PRIVATE KEY";
+const ML_DSA_SEED_SIZE: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyAlgorithm {
+    MlDsa65,
+    SlhDsaShake128f,
+    /// Round-3 SPHINCS+, as wrapped by the `sphincs_plus` crate. Its keys
+    /// have the FIPS 205 layout but its signatures are not SLH-DSA ones.
+    SphincsPlusShake128fSimple,
+    HssLms,
+    Xmss,
+    XmssMt,
+}
+
+impl KeyAlgorithm {
+    pub const ALL: [Self; 6] = [
+        Self::MlDsa65,
+        Self::SlhDsaShake128f,
+        Self::SphincsPlusShake128fSimple,
+        Self::HssLms,
+        Self::Xmss,
+        Self::XmssMt,
+    ];
+
+    pub const fn oid(self) -> ObjectIdentifier {
+        match self {
+            // FIPS 204 / RFC 9881
+            Self::MlDsa65 => {
+                ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.3.18")
+            }
+            // FIPS 205 / RFC 9909
+            Self::SlhDsaShake128f => {
+                ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.3.27")
+            }
+            // Open Quantum Safe experimental arc (1.3.9999), as used by
+            // oqs-provider for round-3 sphincsshake128fsimple
+            Self::SphincsPlusShake128fSimple => {
+                ObjectIdentifier::new_unwrap("1.3.9999.6.7.13")
+            }
+            // RFC 9708 id-alg-hss-lms-hashsig
+            Self::HssLms => {
+                ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.3.17")
+            }
+            // RFC 9802 id-alg-xmss-hashsig / id-alg-xmssmt-hashsig
+            Self::Xmss => ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.6.34"),
+            Self::XmssMt => ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.6.35"),
+        }
+    }
+
+    pub fn from_oid(oid: &ObjectIdentifier) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.oid() == *oid)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::MlDsa65 => "ML-DSA-65",
+            Self::SlhDsaShake128f => "SLH-DSA-SHAKE-128f",
+            Self::SphincsPlusShake128fSimple => "SPHINCS+-SHAKE-128f-simple",
+            Self::HssLms => "HSS/LMS",
+            Self::Xmss => "XMSS",
+            Self::XmssMt => "XMSS^MT",
+        }
+    }
+
+    /// Fixed public key length, or `None` when it depends on the HSS/XMSS
+    /// parameter set encoded in the key itself.
+    pub fn public_key_len(self) -> Option<usize> {
+        match self {
+            Self::MlDsa65 => Some(1952),
+            Self::SlhDsaShake128f | Self::SphincsPlusShake128fSimple => {
+                Some(32)
+            }
+            Self::HssLms | Self::Xmss | Self::XmssMt => None,
+        }
+    }
+
+    /// Length of the private key bytes this module accepts: the keygen seed
+    /// for ML-DSA, `SK.seed || SK.prf || PK.seed || PK.root` for SLH-DSA
+    /// and SPHINCS+.
+    pub fn private_key_len(self) -> Option<usize> {
+        match self {
+            Self::MlDsa65 => Some(ML_DSA_SEED_SIZE),
+            Self::SlhDsaShake128f | Self::SphincsPlusShake128fSimple => {
+                Some(64)
+            }
+            Self::HssLms | Self::Xmss | Self::XmssMt => None,
+        }
+    }
+
+    fn algorithm_identifier(self) -> AlgorithmIdentifierRef<'static> {
+        AlgorithmIdentifierRef {
+            oid: self.oid(),
+            parameters: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PkixError {
+    Der(der::Error),
+    UnknownAlgorithm(ObjectIdentifier),
+    UnexpectedParameters {
+        algorithm: KeyAlgorithm,
+    },
+    InvalidKeyLength {
+        algorithm: KeyAlgorithm,
+        expected: usize,
+        actual: usize,
+    },
+    /// An ML-DSA private key holding only the expanded key; the seed cannot
+    /// be recovered from it.
+    UnsupportedPrivateKeyForm {
+        algorithm: KeyAlgorithm,
+    },
+    /// An ML-DSA `both` private key whose expanded key is not the one its
+    /// seed expands to.
+    MismatchedExpandedKey {
+        algorithm: KeyAlgorithm,
+    },
+    PemLabel {
+        expected: &'static str,
+        actual: String,
+    },
+}
+
+impl fmt::Display for PkixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Der(err) => write!(f, "DER error: {err}"),
+            Self::UnknownAlgorithm(oid) => {
+                write!(f, "unknown key algorithm OID {oid}")
+            }
+            Self::UnexpectedParameters { algorithm } => write!(
+                f,
+                "{} algorithm identifier must not carry parameters",
+                algorithm.name()
+            ),
+            Self::InvalidKeyLength {
+                algorithm,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "invalid {} key length: expected {expected}, got {actual}",
+                algorithm.name()
+            ),
+            Self::UnsupportedPrivateKeyForm { algorithm } => write!(
+                f,
+                "{} private key has no seed; only the seed form is supported",
+                algorithm.name()
+            ),
+            Self::MismatchedExpandedKey { algorithm } => write!(
+                f,
+                "{} private key expanded key does not match its seed",
+                algorithm.name()
+            ),
+            Self::PemLabel { expected, actual } => write!(
+                f,
+                "unexpected PEM label: expected {expected}, got {actual}"
+            ),
+        }
+    }
+}
+
+impl Error for PkixError {}
+
+impl From<der::Error> for PkixError {
+    fn from(err: der::Error) -> Self {
+        Self::Der(err)
+    }
+}
+
+pub fn public_key_to_der(
+    algorithm: KeyAlgorithm,
+    public_key: &[u8],
+) -> Result<Vec<u8>, PkixError> {
+    check_len(algorithm, algorithm.public_key_len(), public_key)?;
+    Ok(public_key_info(algorithm, public_key)?.to_der()?)
+}
+
+pub fn public_key_to_pem(
+    algorithm: KeyAlgorithm,
+    public_key: &[u8],
+) -> Result<String, PkixError> {
+    check_len(algorithm, algorithm.public_key_len(), public_key)?;
+    Ok(public_key_info(algorithm, public_key)?.to_pem(LineEnding::LF)?)
+}
+
+pub fn public_key_from_der(
+    der: &[u8],
+) -> Result<(KeyAlgorithm, Vec<u8>), PkixError> {
+    let info = SubjectPublicKeyInfoRef::from_der(der)?;
+    let algorithm = decode_algorithm(&info.algorithm)?;
+    let public_key = info.subject_public_key.as_bytes().ok_or(
+        der::Error::from(der::ErrorKind::Noncanonical {
+            tag: Tag::BitString,
+        }),
+    )?;
+    check_len(algorithm, algorithm.public_key_len(), public_key)?;
+    Ok((algorithm, public_key.to_vec()))
+}
+
+pub fn public_key_from_pem(
+    pem: &str,
+) -> Result<(KeyAlgorithm, Vec<u8>), PkixError> {
+    let (label, der) =
+        der::pem::decode_vec(pem.as_bytes()).map_err(der::Error::from)?;
+    check_label(PUBLIC_KEY_PEM_LABEL, label)?;
+    public_key_from_der(&der)
+}
+
+pub fn private_key_to_der(
+    algorithm: KeyAlgorithm,
+    private_key: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, PkixError> {
+    let content = private_key_content(algorithm, private_key)?;
+    let info = PrivateKeyInfo::new(algorithm.algorithm_identifier(), &content);
+    Ok(Zeroizing::new(info.to_der()?))
+}
+
+pub fn private_key_to_pem(
+    algorithm: KeyAlgorithm,
+    private_key: &[u8],
+) -> Result<Zeroizing<String>, PkixError> {
+    let content = private_key_content(algorithm, private_key)?;
+    let info = PrivateKeyInfo::new(algorithm.algorithm_identifier(), &content);
+    Ok(Zeroizing::new(info.to_pem(LineEnding::LF)?))
+}
+
+pub fn private_key_from_der(
+    der: &[u8],
+) -> Result<(KeyAlgorithm, Zeroizing<Vec<u8>>), PkixError> {
+    let info = PrivateKeyInfo::from_der(der)?;
+    let algorithm = decode_algorithm(&info.algorithm)?;
+    let private_key = match algorithm {
+        KeyAlgorithm::MlDsa65 => ml_dsa_seed(info.private_key)?,
+        _ => info.private_key,
+    };
+    check_len(algorithm, algorithm.private_key_len(), private_key)?;
+    Ok((algorithm, Zeroizing::new(private_key.to_vec())))
+}
+
+pub fn private_key_from_pem(
+    pem: &str,
+) -> Result<(KeyAlgorithm, Zeroizing<Vec<u8>>), PkixError> {
+    let (label, der) =
+        der::pem::decode_vec(pem.as_bytes()).map_err(der::Error::from)?;
+    let der = Zeroizing::new(der);
+    check_label(PRIVATE_KEY_PEM_LABEL, label)?;
+    private_key_from_der(&der)
+}
+
+fn public_key_info(
+    algorithm: KeyAlgorithm,
+    public_key: &[u8],
+) -> Result<SubjectPublicKeyInfoRef<'_>, PkixError> {
+    Ok(SubjectPublicKeyInfoRef {
+        algorithm: algorithm.algorithm_identifier(),
+        subject_public_key: BitStringRef::from_bytes(public_key)?,
+    })
+}
+
+/// The `privateKey` OCTET STRING contents. ML-DSA uses the `seed [0]`
+/// alternative of the RFC 9881 `ML-DSA-PrivateKey` CHOICE; the other
+/// schemes store their raw key.
+fn private_key_content(
+    algorithm: KeyAlgorithm,
+    private_key: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, PkixError> {
+    check_len(algorithm, algorithm.private_key_len(), private_key)?;
+    match algorithm {
+        KeyAlgorithm::MlDsa65 => {
+            let seed = OctetStringRef::new(private_key)?;
+            let choice = ContextSpecificRef {
+                tag_number: TagNumber::N0,
+                tag_mode: TagMode::Implicit,
+                value: &seed,
+            };
+            Ok(Zeroizing::new(choice.to_der()?))
+        }
+        _ => Ok(Zeroizing::new(private_key.to_vec())),
+    }
+}
+
+/// Extracts the seed from an `ML-DSA-PrivateKey`: `seed [0]`, or the
+/// `both` SEQUENCE. A bare 32-byte seed, as written by `ml-dsa` 0.0.4's
+/// own PKCS#8 support, is accepted as well.
+fn ml_dsa_seed(content: &[u8]) -> Result<&[u8], PkixError> {
+    if content.len() == ML_DSA_SEED_SIZE {
+        return Ok(content);
+    }
+
+    let choice = AnyRef::from_der(content)?;
+    match choice.tag() {
+        Tag::ContextSpecific {
+            constructed: false,
+            number: TagNumber::N0,
+        } => Ok(choice.value()),
+        Tag::Sequence => {
+            let (seed, expanded) = choice.sequence(|reader| {
+                let seed = OctetStringRef::decode(reader)?;
+                let expanded = OctetStringRef::decode(reader)?;
+                Ok((seed.as_bytes(), expanded.as_bytes()))
+            })?;
+            check_expanded_key(seed, expanded)?;
+            Ok(seed)
+        }
+        Tag::OctetString => Err(PkixError::UnsupportedPrivateKeyForm {
+            algorithm: KeyAlgorithm::MlDsa65,
+        }),
+        tag => Err(tag.unexpected_error(None).into()),
+    }
+}
+
+/// Re-expands the seed of a `both` private key and rejects the key unless
+/// the carried expanded key is the one the seed produces. Otherwise a
+/// tampered expandedKey would be dropped silently here, but used by any
+/// consumer that reads it instead of the seed.
+fn check_expanded_key(seed: &[u8], expanded: &[u8]) -> Result<(), PkixError> {
+    let algorithm = KeyAlgorithm::MlDsa65;
+    let seed =
+        B32::try_from(seed).map_err(|_| PkixError::InvalidKeyLength {
+            algorithm,
+            expected: ML_DSA_SEED_SIZE,
+            actual: seed.len(),
+        })?;
+    let mut expected = MlDsa65::key_gen_internal(&seed).signing_key().encode();
+    let diff = expected
+        .iter()
+        .zip(expanded)
+        .fold(0_u8, |diff, (a, b)| diff | (a ^ b));
+    let matches = diff == 0 && expected.len() == expanded.len();
+    expected.as_mut_slice().zeroize();
+    if matches {
+        Ok(())
+    } else {
+        Err(PkixError::MismatchedExpandedKey { algorithm })
+    }
+}
+
+fn decode_algorithm(
+    identifier: &AlgorithmIdentifierRef<'_>,
+) -> Result<KeyAlgorithm, PkixError> {
+    let algorithm = KeyAlgorithm::from_oid(&identifier.oid)
+        .ok_or(PkixError::UnknownAlgorithm(identifier.oid))?;
+    if identifier.parameters.is_some() {
+        return Err(PkixError::UnexpectedParameters { algorithm });
+    }
+    Ok(algorithm)
+}
+
+fn check_len(
+    algorithm: KeyAlgorithm,
+    expected: Option<usize>,
+    key: &[u8],
+) -> Result<(), PkixError> {
+    match expected {
+        Some(expected) if expected != key.len() => {
+            Err(PkixError::InvalidKeyLength {
+                algorithm,
+                expected,
+                actual: key.len(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_label(expected: &'static str, actual: &str) -> Result<(), PkixError> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(PkixError::PemLabel {
+            expected,
+            actual: actual.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        private_key_from_der, private_key_from_pem, private_key_to_der,
+        private_key_to_pem, public_key_from_der, public_key_from_pem,
+        public_key_to_der, public_key_to_pem, KeyAlgorithm, PkixError,
+    };
+    use der::Encode;
+    use dilithium::{SeedKeyPair, SignatureScheme as _, ML_DSA_65};
+    use ml_dsa::{KeyGen, MlDsa65};
+    use pkcs8::{EncodePrivateKey, EncodePublicKey, PrivateKeyInfo};
+    use pqcrypto_traits::sign::{PublicKey, SecretKey};
+    use sphincs_plus::{SignatureScheme as _, SPHINCS_PLUS_SHAKE_128F_SIMPLE};
+    use spki::AlgorithmIdentifierRef;
+
+    #[test]
+    fn ml_dsa_public_key_matches_the_backend_spki() {
+        let seed = dilithium::default_seed();
+        let keypair = ML_DSA_65.keypair(&seed);
+        let public_key =
+            ML_DSA_65.encode_verifying_key(ML_DSA_65.verifying_key(&keypair));
+
+        let der = public_key_to_der(KeyAlgorithm::MlDsa65, &public_key)
+            .expect("public key should encode");
+        let backend = MlDsa65::key_gen_internal(&seed)
+            .verifying_key()
+            .to_public_key_der()
+            .expect("backend should encode");
+        assert_eq!(der, backend.as_bytes());
+
+        let pem = public_key_to_pem(KeyAlgorithm::MlDsa65, &public_key)
+            .expect("public key should encode");
+        assert!(pem.starts_with("-----BEGIN PUBLIC KEY-----\n"));
+        let (algorithm, decoded) =
+            public_key_from_pem(&pem).expect("PEM should decode");
+        assert_eq!(algorithm, KeyAlgorithm::MlDsa65);
+        assert_eq!(decoded, public_key);
+    }
+
+    #[test]
+    fn ml_dsa_private_key_uses_the_seed_choice() {
+        let keypair = SeedKeyPair::generate().expect("OS RNG should work");
+        let seed = keypair.seed_bytes();
+
+        let der = private_key_to_der(KeyAlgorithm::MlDsa65, &seed)
+            .expect("seed should encode");
+        // SEQUENCE { INTEGER 0, SEQUENCE { OID id-ml-dsa-65 },
+        //            OCTET STRING { [0] seed } }
+        assert_eq!(
+            &der[..22],
+            &[
+                0x30, 0x34, 0x02, 0x01, 0x00, 0x30, 0x0b, 0x06, 0x09, 0x60,
+                0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x03, 0x12, 0x04, 0x22,
+                0x80, 0x20,
+            ]
+        );
+
+        let pem = private_key_to_pem(KeyAlgorithm::MlDsa65, &seed)
+            .expect("seed should encode");
+        let (algorithm, decoded) =
+            private_key_from_pem(&pem).expect("PEM should decode");
+        assert_eq!(algorithm, KeyAlgorithm::MlDsa65);
+        let restored = SeedKeyPair::from_seed_bytes(&decoded)
+            .expect("decoded seed should load");
+        assert_eq!(
+            ML_DSA_65.encode_verifying_key(restored.verifying_key()),
+            ML_DSA_65.encode_verifying_key(keypair.verifying_key())
+        );
+    }
+
+    #[test]
+    fn ml_dsa_private_key_accepts_the_backend_bare_seed() {
+        let seed = dilithium::default_seed();
+        let backend = MlDsa65::key_gen_internal(&seed)
+            .to_pkcs8_der()
+            .expect("backend should encode");
+
+        let (algorithm, decoded) = private_key_from_der(backend.as_bytes())
+            .expect("bare seed should decode");
+        assert_eq!(algorithm, KeyAlgorithm::MlDsa65);
+        assert_eq!(decoded.as_slice(), seed.as_slice());
+    }
+
+    /// A `both` SEQUENCE { seed OCTET STRING, expandedKey OCTET STRING },
+    /// as written by OpenSSL 3.5.
+    fn both_form_der(seed: &[u8], expanded: &[u8]) -> Vec<u8> {
+        let mut content = vec![0x30, 0x82, 0x0f, 0xe6, 0x04, 0x20];
+        content.extend_from_slice(seed);
+        content.extend_from_slice(&[0x04, 0x82, 0x0f, 0xc0]);
+        content.extend_from_slice(expanded);
+        PrivateKeyInfo::new(
+            AlgorithmIdentifierRef {
+                oid: KeyAlgorithm::MlDsa65.oid(),
+                parameters: None,
+            },
+            &content,
+        )
+        .to_der()
+        .expect("PKCS#8 should encode")
+    }
+
+    #[test]
+    fn ml_dsa_private_key_accepts_the_both_form() {
+        let keypair = ML_DSA_65.keypair(&dilithium::default_seed());
+        let expanded =
+            ML_DSA_65.encode_signing_key(ML_DSA_65.signing_key(&keypair));
+        let der = both_form_der(&keypair.seed_bytes(), &expanded);
+
+        let (_, seed) =
+            private_key_from_der(&der).expect("both form should decode");
+        assert_eq!(seed.as_slice(), keypair.seed_bytes());
+    }
+
+    #[test]
+    fn ml_dsa_both_form_with_a_foreign_expanded_key_is_rejected() {
+        let keypair = ML_DSA_65.keypair(&dilithium::default_seed());
+        let mut expanded =
+            ML_DSA_65.encode_signing_key(ML_DSA_65.signing_key(&keypair));
+        let last = expanded.len() - 1;
+        expanded[last] ^= 1;
+        let der = both_form_der(&keypair.seed_bytes(), &expanded);
+
+        assert!(matches!(
+            private_key_from_der(&der),
+            Err(PkixError::MismatchedExpandedKey {
+                algorithm: KeyAlgorithm::MlDsa65
+            })
+        ));
+    }
+
+    #[test]
+    fn ml_dsa_expanded_only_private_key_is_rejected() {
+        let mut der = vec![
+            0x30, 0x17, 0x02, 0x01, 0x00, 0x30, 0x0b, 0x06, 0x09, 0x60, 0x86,
+            0x48, 0x01, 0x65, 0x03, 0x04, 0x03, 0x12, 0x04, 0x05, 0x04, 0x03,
+        ];
+        der.extend_from_slice(&[1, 2, 3]);
+
+        assert!(matches!(
+            private_key_from_der(&der),
+            Err(PkixError::UnsupportedPrivateKeyForm {
+                algorithm: KeyAlgorithm::MlDsa65
+            })
+        ));
+    }
+
+    #[test]
+    fn sphincs_plus_keys_round_trip() {
+        let (public_key, secret_key) = SPHINCS_PLUS_SHAKE_128F_SIMPLE.keypair();
+        let algorithm = KeyAlgorithm::SphincsPlusShake128fSimple;
+
+        let der = public_key_to_der(algorithm, public_key.as_bytes())
+            .expect("public key should encode");
+        assert_eq!(
+            public_key_from_der(&der).expect("public key should decode"),
+            (algorithm, public_key.as_bytes().to_vec())
+        );
+
+        let der = private_key_to_der(algorithm, secret_key.as_bytes())
+            .expect("secret key should encode");
+        let (decoded_algorithm, decoded) =
+            private_key_from_der(&der).expect("secret key should decode");
+        assert_eq!(decoded_algorithm, algorithm);
+        assert_eq!(decoded.as_slice(), secret_key.as_bytes());
+
+        assert!(matches!(
+            public_key_to_der(algorithm, &[0; 31]),
+            Err(PkixError::InvalidKeyLength {
+                expected: 32,
+                actual: 31,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn stateful_hash_based_keys_carry_their_oids() {
+        for algorithm in [
+            KeyAlgorithm::HssLms,
+            KeyAlgorithm::Xmss,
+            KeyAlgorithm::XmssMt,
+        ] {
+            let public_key = [0x5a_u8; 60];
+            let pem = public_key_to_pem(algorithm, &public_key)
+                .expect("public key should encode");
+            assert_eq!(
+                public_key_from_pem(&pem).expect("public key should decode"),
+                (algorithm, public_key.to_vec())
+            );
+
+            let private_key = [0xa5_u8; 100];
+            let der = private_key_to_der(algorithm, &private_key)
+                .expect("private key should encode");
+            let (decoded_algorithm, decoded) =
+                private_key_from_der(&der).expect("private key should decode");
+            assert_eq!(decoded_algorithm, algorithm);
+            assert_eq!(decoded.as_slice(), private_key);
+        }
+    }
+
+    #[test]
+    fn foreign_algorithms_and_labels_are_rejected() {
+        // Ed25519 SubjectPublicKeyInfo (RFC 8410).
+        let mut ed25519 = vec![
+            0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21,
+            0x00,
+        ];
+        ed25519.extend_from_slice(&[0; 32]);
+        assert!(matches!(
+            public_key_from_der(&ed25519),
+            Err(PkixError::UnknownAlgorithm(_))
+        ));
+
+        let private_pem =
+            private_key_to_pem(KeyAlgorithm::Xmss, &[1, 2, 3]).expect("encode");
+        assert!(matches!(
+            public_key_from_pem(&private_pem),
+            Err(PkixError::PemLabel {
+                expected: "PUBLIC KEY",
+                ..
+            })
+        ));
+    }
+}
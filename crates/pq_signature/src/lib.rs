@@ -0,0 +1,334 @@
+//! One signature interface over every scheme in the workspace.
+//!
+//! [`Scheme`] is the typed form each scheme crate implements: keygen,
+//! detached sign and verify, sizes, whether keys carry signing state, and
+//! the [`SecurityProfile`] the parameter set claims.
+//! [`DynScheme`] is its object-safe form, implemented for every `Scheme`, so
+//! a harness can hold `Vec<Box<dyn DynScheme>>` and drive all schemes the
+//! same way. Keys and signatures cross the dynamic interface as opaque
+//! [`Handle`]s and are never re-encoded, so timings through it match the
+//! typed calls.
+
+use std::any::{type_name, Any};
+use std::error::Error;
+use std::fmt;
+
+/// Key and signature sizes in bytes. For schemes with variable-length
+/// signatures, `signature_bytes` is the maximum. `secret_key_bytes` is what
+/// has to be stored; a loaded key may hold more in memory, such as the
+/// cached tree of an MSS key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SchemeSizes {
+    pub public_key_bytes: usize,
+    pub secret_key_bytes: usize,
+    pub signature_bytes: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Signing updates the secret key, which must be persisted before the
+    /// signature is released.
+    pub stateful: bool,
+    /// `None` when the scheme has no practical per-key limit.
+    pub max_signatures_per_key: Option<u64>,
+}
+
+impl Capabilities {
+    pub const STATELESS: Self = Self {
+        stateful: false,
+        max_signatures_per_key: None,
+    };
+
+    pub const fn stateful(max_signatures_per_key: u64) -> Self {
+        Self {
+            stateful: true,
+            max_signatures_per_key: Some(max_signatures_per_key),
+        }
+    }
+
+    pub fn is_one_time(&self) -> bool {
+        self.max_signatures_per_key == Some(1)
+    }
+}
+
+/// A published standard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Document {
+    Fips(u16),
+    Rfc(u16),
+    /// A NIST Special Publication, e.g. `"800-208"`.
+    Sp(&'static str),
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fips(number) => write!(f, "FIPS {number}"),
+            Self::Rfc(number) => write!(f, "RFC {number}"),
+            Self::Sp(number) => write!(f, "SP {number}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Standardization {
+    /// Implemented as the document specifies.
+    Standardized(Document),
+    /// The competition submission the document was derived from. Keys and
+    /// signatures do not interoperate with implementations of the standard.
+    Precursor(Document),
+    Unstandardized,
+}
+
+impl Standardization {
+    pub fn is_standardized(&self) -> bool {
+        matches!(self, Self::Standardized(_))
+    }
+}
+
+impl fmt::Display for Standardization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Standardized(document) => write!(f, "{document}"),
+            Self::Precursor(document) => write!(f, "pre-{document}"),
+            Self::Unstandardized => f.write_str("none"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssumptionFamily {
+    Lattice,
+    HashBased,
+}
+
+impl fmt::Display for AssumptionFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Lattice => "lattice",
+            Self::HashBased => "hash-based",
+        })
+    }
+}
+
+/// What a parameter set's security rests on and how it is known to break in
+/// practice. Statefulness and the per-key limit are in [`Capabilities`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SecurityProfile {
+    /// NIST security category, 1 to 5: the parameter set is at least as
+    /// hard to break as AES-128 key search (1), SHA-256 collision search
+    /// (2), AES-192 (3), SHA-384 (4) or AES-256 (5).
+    pub nist_category: u8,
+    /// Estimated cost of the best known classical attack, in bits. Core-SVP
+    /// estimates from the round-3 submissions for lattice schemes.
+    pub classical_bits: u16,
+    /// The same against a quantum attacker: Grover for preimages,
+    /// Brassard–Høyer–Tapp for collisions.
+    pub quantum_bits: u16,
+    pub family: AssumptionFamily,
+    pub assumption: &'static str,
+    pub standardization: Standardization,
+    /// Known ways implementations of the scheme go wrong, one sentence
+    /// each.
+    pub pitfalls: &'static [&'static str],
+}
+
+pub trait Scheme {
+    type PublicKey: Send + 'static;
+    type SecretKey: Send + 'static;
+    type Signature: Send + 'static;
+    type Error: Error + Send + Sync + 'static;
+
+    fn algorithm_name(&self) -> &'static str;
+    fn param_set_name(&self) -> &'static str;
+    fn backend_name(&self) -> &'static str;
+    fn sizes(&self) -> SchemeSizes;
+    fn capabilities(&self) -> Capabilities;
+    fn security(&self) -> SecurityProfile;
+
+    fn keypair(
+        &self,
+    ) -> Result<(Self::PublicKey, Self::SecretKey), Self::Error>;
+
+    /// Signs `message`, advancing `secret_key` for stateful schemes.
+    fn sign(
+        &self,
+        message: &[u8],
+        secret_key: &mut Self::SecretKey,
+    ) -> Result<Self::Signature, Self::Error>;
+
+    fn verify(
+        &self,
+        message: &[u8],
+        signature: &Self::Signature,
+        public_key: &Self::PublicKey,
+    ) -> Result<bool, Self::Error>;
+
+    /// Encoded length of this particular signature.
+    fn signature_len(&self, signature: &Self::Signature) -> usize;
+}
+
+/// A key or signature owned by a [`DynScheme`]. Pass it back as
+/// `&*handle` / `&mut *handle`, not as the box itself.
+pub type Handle = Box<dyn Any + Send>;
+
+pub struct DynKeyPair {
+    pub public_key: Handle,
+    pub secret_key: Handle,
+}
+
+impl fmt::Debug for DynKeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynKeyPair").finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+pub enum SchemeError {
+    /// A handle created by a different scheme (or a boxed handle passed by
+    /// reference) was given to `param_set`.
+    HandleMismatch {
+        param_set: &'static str,
+        expected: &'static str,
+    },
+    Scheme(Box<dyn Error + Send + Sync>),
+}
+
+impl fmt::Display for SchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HandleMismatch {
+                param_set,
+                expected,
+            } => write!(f, "{param_set} expected a {expected} handle"),
+            Self::Scheme(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl Error for SchemeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::HandleMismatch { .. } => None,
+            Self::Scheme(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+/// Object-safe form of [`Scheme`].
+pub trait DynScheme: Send + Sync {
+    fn algorithm_name(&self) -> &'static str;
+    fn param_set_name(&self) -> &'static str;
+    fn backend_name(&self) -> &'static str;
+    fn sizes(&self) -> SchemeSizes;
+    fn capabilities(&self) -> Capabilities;
+    fn security(&self) -> SecurityProfile;
+
+    fn keypair(&self) -> Result<DynKeyPair, SchemeError>;
+
+    fn sign(
+        &self,
+        message: &[u8],
+        secret_key: &mut (dyn Any + Send),
+    ) -> Result<Handle, SchemeError>;
+
+    fn verify(
+        &self,
+        message: &[u8],
+        signature: &(dyn Any + Send),
+        public_key: &(dyn Any + Send),
+    ) -> Result<bool, SchemeError>;
+
+    fn signature_len(
+        &self,
+        signature: &(dyn Any + Send),
+    ) -> Result<usize, SchemeError>;
+}
+
+impl<S: Scheme + Send + Sync> DynScheme for S {
+    fn algorithm_name(&self) -> &'static str {
+        Scheme::algorithm_name(self)
+    }
+
+    fn param_set_name(&self) -> &'static str {
+        Scheme::param_set_name(self)
+    }
+
+    fn backend_name(&self) -> &'static str {
+        Scheme::backend_name(self)
+    }
+
+    fn sizes(&self) -> SchemeSizes {
+        Scheme::sizes(self)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Scheme::capabilities(self)
+    }
+
+    fn security(&self) -> SecurityProfile {
+        Scheme::security(self)
+    }
+
+    fn keypair(&self) -> Result<DynKeyPair, SchemeError> {
+        let (public_key, secret_key) =
+            Scheme::keypair(self).map_err(scheme_error)?;
+        Ok(DynKeyPair {
+            public_key: Box::new(public_key),
+            secret_key: Box::new(secret_key),
+        })
+    }
+
+    fn sign(
+        &self,
+        message: &[u8],
+        secret_key: &mut (dyn Any + Send),
+    ) -> Result<Handle, SchemeError> {
+        let secret_key = secret_key
+            .downcast_mut::<S::SecretKey>()
+            .ok_or_else(|| mismatch::<S, S::SecretKey>(self))?;
+        let signature =
+            Scheme::sign(self, message, secret_key).map_err(scheme_error)?;
+        Ok(Box::new(signature))
+    }
+
+    fn verify(
+        &self,
+        message: &[u8],
+        signature: &(dyn Any + Send),
+        public_key: &(dyn Any + Send),
+    ) -> Result<bool, SchemeError> {
+        let signature = downcast::<S, S::Signature>(self, signature)?;
+        let public_key = downcast::<S, S::PublicKey>(self, public_key)?;
+        Scheme::verify(self, message, signature, public_key)
+            .map_err(scheme_error)
+    }
+
+    fn signature_len(
+        &self,
+        signature: &(dyn Any + Send),
+    ) -> Result<usize, SchemeError> {
+        let signature = downcast::<S, S::Signature>(self, signature)?;
+        Ok(Scheme::signature_len(self, signature))
+    }
+}
+
+fn downcast<'a, S: Scheme, T: 'static>(
+    scheme: &S,
+    handle: &'a (dyn Any + Send),
+) -> Result<&'a T, SchemeError> {
+    handle
+        .downcast_ref::<T>()
+        .ok_or_else(|| mismatch::<S, T>(scheme))
+}
+
+fn mismatch<S: Scheme, T>(scheme: &S) -> SchemeError {
+    SchemeError::HandleMismatch {
+        param_set: scheme.param_set_name(),
+        expected: type_name::<T>(),
+    }
+}
+
+fn scheme_error(err: impl Error + Send + Sync + 'static) -> SchemeError {
+    SchemeError::Scheme(Box::new(err))
+}
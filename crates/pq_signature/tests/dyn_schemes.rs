@@ -0,0 +1,111 @@
+use dilithium::ML_DSA_65;
+use falcon::FALCON512;
+use lamport_ots::mss::{MssHeight, MssScheme};
+use lamport_ots::{
+    LamportVariant, LAMPORT_OTS_RANDOMIZED_SCHEME, LAMPORT_OTS_SCHEME,
+};
+use pq_signature::{
+    AssumptionFamily, Capabilities, Document, DynScheme, SchemeError,
+    Standardization,
+};
+use sphincs_plus::SPHINCS_PLUS_SHAKE_128F_SIMPLE;
+
+fn all_schemes() -> Vec<Box<dyn DynScheme>> {
+    vec![
+        Box::new(FALCON512),
+        Box::new(ML_DSA_65),
+        Box::new(SPHINCS_PLUS_SHAKE_128F_SIMPLE),
+        Box::new(LAMPORT_OTS_SCHEME),
+        Box::new(MssScheme::new(MssHeight::H5, LamportVariant::Plain)),
+    ]
+}
+
+#[test]
+fn every_scheme_signs_and_verifies_through_the_dyn_trait() {
+    let message = b"unified harness";
+    for scheme in all_schemes() {
+        let name = scheme.param_set_name();
+        let mut keypair = scheme.keypair().expect(name);
+        let signature =
+            scheme.sign(message, &mut *keypair.secret_key).expect(name);
+
+        let len = scheme.signature_len(&*signature).expect(name);
+        assert!(len > 0 && len <= scheme.sizes().signature_bytes, "{name}");
+        assert!(
+            scheme
+                .verify(message, &*signature, &*keypair.public_key)
+                .expect(name),
+            "{name}"
+        );
+        assert!(
+            !scheme
+                .verify(b"other", &*signature, &*keypair.public_key)
+                .expect(name),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn capabilities_flag_stateful_and_one_time_schemes() {
+    let flags: Vec<_> = all_schemes()
+        .iter()
+        .map(|scheme| scheme.capabilities())
+        .collect();
+    assert_eq!(flags[..3], [Capabilities::STATELESS; 3]);
+    assert!(flags[3].stateful && flags[3].is_one_time());
+    assert_eq!(flags[4], Capabilities::stateful(32));
+    assert!(!flags[4].is_one_time());
+}
+
+#[test]
+fn security_profiles_are_consistent() {
+    for scheme in all_schemes() {
+        let name = scheme.param_set_name();
+        let security = scheme.security();
+        assert!((1..=5).contains(&security.nist_category), "{name}");
+        assert!(security.quantum_bits <= security.classical_bits, "{name}");
+        assert!(!security.pitfalls.is_empty(), "{name}");
+    }
+    assert_eq!(
+        ML_DSA_65.security().standardization,
+        Standardization::Standardized(Document::Fips(204))
+    );
+    assert_eq!(FALCON512.security().family, AssumptionFamily::Lattice);
+    // The untweaked tree hashes cap MSS at collision resistance, even over
+    // randomized leaves.
+    for ots in [LamportVariant::Plain, LamportVariant::Randomized] {
+        let mss = MssScheme::new(MssHeight::H5, ots).security();
+        assert_eq!(mss.assumption, "SHA-256 collision resistance");
+        assert_eq!((mss.nist_category, mss.quantum_bits), (2, 85));
+        assert_ne!(mss.pitfalls, LAMPORT_OTS_SCHEME.security().pitfalls);
+    }
+    assert_eq!(LAMPORT_OTS_RANDOMIZED_SCHEME.security().nist_category, 5);
+}
+
+#[test]
+fn one_time_keys_refuse_a_second_signature() {
+    let scheme: Box<dyn DynScheme> = Box::new(LAMPORT_OTS_SCHEME);
+    let mut keypair = scheme.keypair().unwrap();
+    scheme.sign(b"first", &mut *keypair.secret_key).unwrap();
+    assert!(matches!(
+        scheme.sign(b"second", &mut *keypair.secret_key),
+        Err(SchemeError::Scheme(_))
+    ));
+}
+
+#[test]
+fn handles_from_another_scheme_are_rejected() {
+    let falcon: Box<dyn DynScheme> = Box::new(FALCON512);
+    let lamport: Box<dyn DynScheme> = Box::new(LAMPORT_OTS_SCHEME);
+    let mut keypair = lamport.keypair().unwrap();
+    assert!(matches!(
+        falcon.sign(b"message", &mut *keypair.secret_key),
+        Err(SchemeError::HandleMismatch {
+            param_set: "Falcon-512",
+            ..
+        })
+    ));
+    // The box itself is not the handle.
+    assert!(lamport.sign(b"message", &mut keypair.secret_key).is_err());
+}
@@ -0,0 +1,55 @@
+use divan::{black_box, Bencher};
+use sha2::{Digest, Sha256};
+use sha256_multilane::{
+    hash_batch_with, hash_x4, hash_x8, Backend, DIGEST_SIZE, MESSAGE_SIZE,
+};
+
+// One Lamport keygen hashes this many 32-byte secrets.
+const BATCH: usize = 512;
+
+fn inputs(count: usize) -> Vec<[u8; MESSAGE_SIZE]> {
+    (0..count)
+        .map(|i| [(i % 251) as u8; MESSAGE_SIZE])
+        .collect()
+}
+
+#[divan::bench]
+fn sha2_digest_per_message(bencher: Bencher) {
+    let inputs = inputs(BATCH);
+    let mut outputs = vec![[0_u8; DIGEST_SIZE]; BATCH];
+
+    bencher.bench_local(|| {
+        for (input, output) in inputs.iter().zip(outputs.iter_mut()) {
+            *output = Sha256::digest(black_box(input)).into();
+        }
+        black_box(&outputs);
+    });
+}
+
+#[divan::bench(args = [Backend::Portable, Backend::Sha2Compress])]
+fn batch(bencher: Bencher, backend: Backend) {
+    let inputs = inputs(BATCH);
+    let mut outputs = vec![[0_u8; DIGEST_SIZE]; BATCH];
+
+    bencher.bench_local(|| {
+        hash_batch_with(backend, black_box(&inputs), &mut outputs);
+        black_box(&outputs);
+    });
+}
+
+#[divan::bench]
+fn x4(bencher: Bencher) {
+    let inputs: [_; 4] = inputs(4).try_into().expect("four inputs");
+    bencher.bench(|| black_box(hash_x4(black_box(&inputs))));
+}
+
+#[divan::bench]
+fn x8(bencher: Bencher) {
+    let inputs: [_; 8] = inputs(8).try_into().expect("eight inputs");
+    bencher.bench(|| black_box(hash_x8(black_box(&inputs))));
+}
+
+fn main() {
+    println!("detected backend: {}", Backend::detect().name());
+    divan::main();
+}
@@ -0,0 +1,347 @@
+//! Batched SHA-256 over independent 32-byte messages.
+//!
+//! Hash-based schemes spend most of their time hashing 32-byte values
+//! (Lamport secrets, Winternitz chain links, Merkle nodes). A 32-byte
+//! message always fits a single padded block, so this crate skips the
+//! per-call hasher setup and either
+//!
+//! - runs 4 or 8 messages through one compression in lockstep, with each
+//!   state word stored as a `[u32; LANES]` array so the compiler can keep
+//!   every lane in one SIMD register (`Backend::Portable`), or
+//! - feeds pre-padded blocks straight into `sha2::compress256`, which uses
+//!   SHA-NI / ARMv8 SHA2 instructions when present (`Backend::Sha2Compress`).
+//!
+//! `Backend::detect` picks the hardware path when `cpufeatures` reports SHA
+//! instructions and the portable lanes otherwise.
+//!
+//! The crate is `no_std` and allocation-free, so zkVM guests can use it.
+
+#![cfg_attr(not(test), no_std)]
+
+use sha2::digest::generic_array::GenericArray;
+
+pub const MESSAGE_SIZE: usize = 32;
+pub const DIGEST_SIZE: usize = 32;
+pub const MAX_LANES: usize = 8;
+
+const BLOCK_SIZE: usize = 64;
+const MESSAGE_WORDS: usize = MESSAGE_SIZE / 4;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+cpufeatures::new!(sha_hw, "sha", "sse2", "ssse3", "sse4.1");
+
+#[cfg(target_arch = "aarch64")]
+cpufeatures::new!(sha_hw, "sha2");
+
+const H0: [u32; 8] = [
+    0x6a09_e667,
+    0xbb67_ae85,
+    0x3c6e_f372,
+    0xa54f_f53a,
+    0x510e_527f,
+    0x9b05_688c,
+    0x1f83_d9ab,
+    0x5be0_cd19,
+];
+
+#[rustfmt::skip]
+const K: [u32; 64] = [
+    0x428a_2f98, 0x7137_4491, 0xb5c0_fbcf, 0xe9b5_dba5,
+    0x3956_c25b, 0x59f1_11f1, 0x923f_82a4, 0xab1c_5ed5,
+    0xd807_aa98, 0x1283_5b01, 0x2431_85be, 0x550c_7dc3,
+    0x72be_5d74, 0x80de_b1fe, 0x9bdc_06a7, 0xc19b_f174,
+    0xe49b_69c1, 0xefbe_4786, 0x0fc1_9dc6, 0x240c_a1cc,
+    0x2de9_2c6f, 0x4a74_84aa, 0x5cb0_a9dc, 0x76f9_88da,
+    0x983e_5152, 0xa831_c66d, 0xb003_27c8, 0xbf59_7fc7,
+    0xc6e0_0bf3, 0xd5a7_9147, 0x06ca_6351, 0x1429_2967,
+    0x27b7_0a85, 0x2e1b_2138, 0x4d2c_6dfc, 0x5338_0d13,
+    0x650a_7354, 0x766a_0abb, 0x81c2_c92e, 0x9272_2c85,
+    0xa2bf_e8a1, 0xa81a_664b, 0xc24b_8b70, 0xc76c_51a3,
+    0xd192_e819, 0xd699_0624, 0xf40e_3585, 0x106a_a070,
+    0x19a4_c116, 0x1e37_6c08, 0x2748_774c, 0x34b0_bcb5,
+    0x391c_0cb3, 0x4ed8_aa4a, 0x5b9c_ca4f, 0x682e_6ff3,
+    0x748f_82ee, 0x78a5_636f, 0x84c8_7814, 0x8cc7_0208,
+    0x90be_fffa, 0xa450_6ceb, 0xbef9_a3f7, 0xc671_78f2,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    Portable,
+    Sha2Compress,
+}
+
+impl Backend {
+    pub fn detect() -> Self {
+        if has_sha_instructions() {
+            Self::Sha2Compress
+        } else {
+            Self::Portable
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Portable => "portable-8-lane",
+            Self::Sha2Compress => "sha2-compress256",
+        }
+    }
+}
+
+pub fn hash_x4(inputs: &[[u8; MESSAGE_SIZE]; 4]) -> [[u8; DIGEST_SIZE]; 4] {
+    let mut outputs = [[0_u8; DIGEST_SIZE]; 4];
+    compress_lanes::<4>(inputs, &mut outputs);
+    outputs
+}
+
+pub fn hash_x8(inputs: &[[u8; MESSAGE_SIZE]; 8]) -> [[u8; DIGEST_SIZE]; 8] {
+    let mut outputs = [[0_u8; DIGEST_SIZE]; 8];
+    compress_lanes::<8>(inputs, &mut outputs);
+    outputs
+}
+
+/// Hashes every input into the matching output with the detected backend.
+///
+/// # Panics
+///
+/// Panics if `inputs` and `outputs` have different lengths.
+pub fn hash_batch(
+    inputs: &[[u8; MESSAGE_SIZE]],
+    outputs: &mut [[u8; DIGEST_SIZE]],
+) {
+    hash_batch_with(Backend::detect(), inputs, outputs);
+}
+
+/// Hashes every input into the matching output with `backend`.
+///
+/// # Panics
+///
+/// Panics if `inputs` and `outputs` have different lengths.
+pub fn hash_batch_with(
+    backend: Backend,
+    inputs: &[[u8; MESSAGE_SIZE]],
+    outputs: &mut [[u8; DIGEST_SIZE]],
+) {
+    assert_eq!(
+        inputs.len(),
+        outputs.len(),
+        "every input needs exactly one output"
+    );
+    match backend {
+        Backend::Portable => hash_portable(inputs, outputs),
+        Backend::Sha2Compress => hash_sha2_compress(inputs, outputs),
+    }
+}
+
+fn hash_portable(
+    inputs: &[[u8; MESSAGE_SIZE]],
+    outputs: &mut [[u8; DIGEST_SIZE]],
+) {
+    let mut input_chunks = inputs.chunks_exact(MAX_LANES);
+    let mut output_chunks = outputs.chunks_exact_mut(MAX_LANES);
+    for (input, output) in input_chunks.by_ref().zip(output_chunks.by_ref()) {
+        compress_lanes::<MAX_LANES>(input, output);
+    }
+
+    let input_rest = input_chunks.remainder();
+    let output_rest = output_chunks.into_remainder();
+    let split = input_rest.len() - input_rest.len() % 4;
+    let (input_x4, input_x1) = input_rest.split_at(split);
+    let (output_x4, output_x1) = output_rest.split_at_mut(split);
+    if !input_x4.is_empty() {
+        compress_lanes::<4>(input_x4, output_x4);
+    }
+    for (input, output) in input_x1.iter().zip(output_x1.iter_mut()) {
+        compress_lanes::<1>(
+            core::slice::from_ref(input),
+            core::slice::from_mut(output),
+        );
+    }
+}
+
+fn hash_sha2_compress(
+    inputs: &[[u8; MESSAGE_SIZE]],
+    outputs: &mut [[u8; DIGEST_SIZE]],
+) {
+    let mut block = padded_block();
+    for (input, output) in inputs.iter().zip(outputs.iter_mut()) {
+        block[..MESSAGE_SIZE].copy_from_slice(input);
+        let mut state = H0;
+        sha2::compress256(&mut state, &[block]);
+        write_digest(&state, output);
+    }
+}
+
+fn padded_block() -> GenericArray<u8, sha2::digest::consts::U64> {
+    let mut block = GenericArray::default();
+    block[MESSAGE_SIZE] = 0x80;
+    let bit_len = (MESSAGE_SIZE as u64 * 8).to_be_bytes();
+    block[BLOCK_SIZE - 8..].copy_from_slice(&bit_len);
+    block
+}
+
+// One padded block per lane; every state word holds that word for all lanes.
+fn compress_lanes<const N: usize>(
+    inputs: &[[u8; MESSAGE_SIZE]],
+    outputs: &mut [[u8; DIGEST_SIZE]],
+) {
+    let mut w = [[0_u32; N]; 64];
+    for (lane, input) in inputs.iter().enumerate().take(N) {
+        for (t, word) in input.chunks_exact(4).enumerate() {
+            w[t][lane] =
+                u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+    }
+    w[MESSAGE_WORDS] = [0x8000_0000; N];
+    w[15] = [(MESSAGE_SIZE as u32) * 8; N];
+    for t in 16..64 {
+        w[t] = core::array::from_fn(|lane| {
+            small_sigma1(w[t - 2][lane])
+                .wrapping_add(w[t - 7][lane])
+                .wrapping_add(small_sigma0(w[t - 15][lane]))
+                .wrapping_add(w[t - 16][lane])
+        });
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] =
+        H0.map(|word| [word; N]);
+    for t in 0..64 {
+        let t1: [u32; N] = core::array::from_fn(|lane| {
+            h[lane]
+                .wrapping_add(big_sigma1(e[lane]))
+                .wrapping_add(ch(e[lane], f[lane], g[lane]))
+                .wrapping_add(K[t])
+                .wrapping_add(w[t][lane])
+        });
+        let t2: [u32; N] = core::array::from_fn(|lane| {
+            big_sigma0(a[lane]).wrapping_add(maj(a[lane], b[lane], c[lane]))
+        });
+        h = g;
+        g = f;
+        f = e;
+        e = core::array::from_fn(|lane| d[lane].wrapping_add(t1[lane]));
+        d = c;
+        c = b;
+        b = a;
+        a = core::array::from_fn(|lane| t1[lane].wrapping_add(t2[lane]));
+    }
+
+    let words = [a, b, c, d, e, f, g, h];
+    for (lane, output) in outputs.iter_mut().enumerate().take(N) {
+        let mut state = H0;
+        for (word, lanes) in state.iter_mut().zip(words.iter()) {
+            *word = word.wrapping_add(lanes[lane]);
+        }
+        write_digest(&state, output);
+    }
+}
+
+fn write_digest(state: &[u32; 8], output: &mut [u8; DIGEST_SIZE]) {
+    for (chunk, word) in output.chunks_exact_mut(4).zip(state.iter()) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+}
+
+#[cfg(any(
+    target_arch = "x86",
+    target_arch = "x86_64",
+    target_arch = "aarch64"
+))]
+fn has_sha_instructions() -> bool {
+    sha_hw::get()
+}
+
+#[cfg(not(any(
+    target_arch = "x86",
+    target_arch = "x86_64",
+    target_arch = "aarch64"
+)))]
+fn has_sha_instructions() -> bool {
+    false
+}
+
+fn ch(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) ^ (!x & z)
+}
+
+fn maj(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) ^ (x & z) ^ (y & z)
+}
+
+fn big_sigma0(x: u32) -> u32 {
+    x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)
+}
+
+fn big_sigma1(x: u32) -> u32 {
+    x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)
+}
+
+fn small_sigma0(x: u32) -> u32 {
+    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+}
+
+fn small_sigma1(x: u32) -> u32 {
+    x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        hash_batch, hash_batch_with, hash_x4, hash_x8, Backend, DIGEST_SIZE,
+        MESSAGE_SIZE,
+    };
+    use sha2::{Digest, Sha256};
+
+    fn inputs(count: usize) -> Vec<[u8; MESSAGE_SIZE]> {
+        (0..count)
+            .map(|i| {
+                let mut input = [0_u8; MESSAGE_SIZE];
+                for (j, byte) in input.iter_mut().enumerate() {
+                    *byte = (i * 31 + j * 7) as u8;
+                }
+                input
+            })
+            .collect()
+    }
+
+    fn reference(inputs: &[[u8; MESSAGE_SIZE]]) -> Vec<[u8; DIGEST_SIZE]> {
+        inputs
+            .iter()
+            .map(|input| Sha256::digest(input).into())
+            .collect()
+    }
+
+    #[test]
+    fn fixed_lane_counts_match_sha2() {
+        let inputs = inputs(8);
+        let x8: [_; 8] = inputs.clone().try_into().expect("eight inputs");
+        let x4: [_; 4] = inputs[..4].try_into().expect("four inputs");
+
+        assert_eq!(hash_x8(&x8).to_vec(), reference(&inputs));
+        assert_eq!(hash_x4(&x4).to_vec(), reference(&inputs[..4]));
+    }
+
+    #[test]
+    fn every_backend_matches_sha2_for_ragged_batches() {
+        for count in [0, 1, 3, 4, 5, 8, 9, 13, 37, 512] {
+            let inputs = inputs(count);
+            let expected = reference(&inputs);
+
+            for backend in [Backend::Portable, Backend::Sha2Compress] {
+                let mut outputs = vec![[0_u8; DIGEST_SIZE]; count];
+                hash_batch_with(backend, &inputs, &mut outputs);
+                assert_eq!(outputs, expected, "{backend:?} with {count}");
+            }
+
+            let mut outputs = vec![[0_u8; DIGEST_SIZE]; count];
+            hash_batch(&inputs, &mut outputs);
+            assert_eq!(outputs, expected, "detected backend with {count}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "every input needs exactly one output")]
+    fn mismatched_lengths_panic() {
+        let mut outputs = vec![[0_u8; DIGEST_SIZE]; 2];
+        hash_batch(&inputs(3), &mut outputs);
+    }
+}
@@ -1,8 +1,10 @@
 use divan::{black_box, AllocProfiler, Bencher};
+use pq_bench_core::{
+    bench_message, memory, TrackingAllocator, BENCH_MESSAGE_SIZES,
+};
 use pqcrypto_traits::sign::{PublicKey, SecretKey};
 use sphincs_plus::{
-    bench_message, memory, signature_size, SignatureScheme, TrackingAllocator,
-    BENCH_MESSAGE_SIZES, SPHINCS_PLUS_SHAKE_128F_SIMPLE,
+    signature_size, SignatureScheme, SPHINCS_PLUS_SHAKE_128F_SIMPLE,
 };
 
 static DIVAN_ALLOC: AllocProfiler = AllocProfiler::system();
@@ -73,11 +75,11 @@ fn print_memory_usage() {
     for message_size in BENCH_MESSAGE_SIZES {
         let message = bench_message(message_size);
 
-        memory::reset_peak();
+        memory::reset();
         let signed_message = scheme.sign(&message, &secret_key);
         let sign_peak = memory::peak_bytes();
 
-        memory::reset_peak();
+        memory::reset();
         let _opened = scheme
             .open(&signed_message, &public_key)
             .expect("benchmark setup should verify the signed message");
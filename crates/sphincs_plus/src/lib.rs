@@ -1,79 +1,14 @@
+#[cfg(feature = "op-count")]
+use pq_bench_core::zkvm::{self, OpCounts, SHAKE256_RATE};
+use pq_signature::{
+    AssumptionFamily, Capabilities, Document, Scheme, SchemeSizes,
+    SecurityProfile, Standardization,
+};
 use pqcrypto_sphincsplus::sphincsshake128fsimple;
-use pqcrypto_traits::sign::{PublicKey, SecretKey, SignedMessage};
-use std::alloc::{GlobalAlloc, Layout};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::{Duration, Instant};
-
-pub const BENCH_MESSAGE_SIZES: [usize; 4] = [32, 256, 1024, 4096];
-pub const BENCH_MESSAGE_BYTE: u8 = 0x42;
-
-static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
-static PEAK_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
-static BASELINE: AtomicUsize = AtomicUsize::new(0);
-
-pub struct TrackingAllocator<A: GlobalAlloc + Sync + 'static> {
-    inner: &'static A,
-}
-
-impl<A: GlobalAlloc + Sync + 'static> TrackingAllocator<A> {
-    pub const fn new(inner: &'static A) -> Self {
-        Self { inner }
-    }
-}
-
-unsafe impl<A: GlobalAlloc + Sync + 'static> GlobalAlloc
-    for TrackingAllocator<A>
-{
-    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        let ptr = unsafe { self.inner.alloc(layout) };
-        if !ptr.is_null() {
-            track_alloc(layout.size());
-        }
-        ptr
-    }
-
-    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
-        unsafe { self.inner.dealloc(ptr, layout) };
-        track_dealloc(layout.size());
-    }
-}
-
-fn track_alloc(size: usize) {
-    let current = ALLOCATED.fetch_add(size, Ordering::SeqCst) + size;
-    let baseline = BASELINE.load(Ordering::SeqCst);
-    let relative_current = current.saturating_sub(baseline);
-    let mut peak = PEAK_ALLOCATED.load(Ordering::SeqCst);
-
-    while relative_current > peak {
-        match PEAK_ALLOCATED.compare_exchange_weak(
-            peak,
-            relative_current,
-            Ordering::SeqCst,
-            Ordering::SeqCst,
-        ) {
-            Ok(_) => break,
-            Err(observed) => peak = observed,
-        }
-    }
-}
-
-fn track_dealloc(size: usize) {
-    ALLOCATED.fetch_sub(size, Ordering::SeqCst);
-}
-
-pub mod memory {
-    use super::{Ordering, ALLOCATED, BASELINE, PEAK_ALLOCATED};
-
-    pub fn reset_peak() {
-        let current = ALLOCATED.load(Ordering::SeqCst);
-        BASELINE.store(current, Ordering::SeqCst);
-        PEAK_ALLOCATED.store(0, Ordering::SeqCst);
-    }
-
-    pub fn peak_bytes() -> usize {
-        PEAK_ALLOCATED.load(Ordering::SeqCst)
-    }
-}
+use pqcrypto_traits::sign::{
+    DetachedSignature, PublicKey, SecretKey, SignedMessage,
+};
+use std::convert::Infallible;
 
 pub trait SignatureScheme {
     type PublicKey: PublicKey;
@@ -100,6 +35,58 @@ pub struct SphincsPlusShake128fSimpleScheme;
 pub const SPHINCS_PLUS_SHAKE_128F_SIMPLE: SphincsPlusShake128fSimpleScheme =
     SphincsPlusShake128fSimpleScheme;
 
+impl SphincsPlusShake128fSimpleScheme {
+    /// Primitive operations of one verify. Each WOTS+ chain is assumed to
+    /// take the expected (w - 1) / 2 steps; the real number depends on the
+    /// message digest.
+    #[cfg(feature = "op-count")]
+    pub fn verify_ops(&self, message_len: usize) -> OpCounts {
+        const N: usize = 16;
+        const ADDRESS_BYTES: usize = 32;
+        const DIGEST_BYTES: usize = 34;
+        const LAYERS: usize = 22;
+        const TREE_HEIGHT: usize = 3;
+        const FORS_TREES: usize = 33;
+        const FORS_HEIGHT: usize = 6;
+        const WOTS_LEN: usize = 35;
+        const WOTS_W: usize = 16;
+        let tweak = |input_bytes| {
+            OpCounts::shake(N + ADDRESS_BYTES + input_bytes, SHAKE256_RATE, N)
+        };
+
+        let digest =
+            OpCounts::shake(3 * N + message_len, SHAKE256_RATE, DIGEST_BYTES);
+        let fors = (tweak(N) + tweak(2 * N).times(FORS_HEIGHT))
+            .times(FORS_TREES)
+            + tweak(FORS_TREES * N);
+        let layer = tweak(N).times(WOTS_LEN * (WOTS_W - 1) / 2)
+            + tweak(WOTS_LEN * N)
+            + tweak(2 * N).times(TREE_HEIGHT);
+        digest + fors + layer.times(LAYERS)
+    }
+
+    /// The round-3 submission; SLH-DSA (FIPS 205) changes the message
+    /// hashing and address encoding.
+    pub const fn security(&self) -> SecurityProfile {
+        SecurityProfile {
+            nist_category: 1,
+            classical_bits: 128,
+            quantum_bits: 64,
+            family: AssumptionFamily::HashBased,
+            assumption: "SHAKE256 preimage and second-preimage resistance",
+            standardization: Standardization::Precursor(Document::Fips(205)),
+            pitfalls: &[
+                "A fault during signing can leak a one-time WOTS+ \
+                 signature that lets an attacker graft a forged subtree.",
+                "The simple variant's security argument relies on a \
+                 heuristic; the robust variant does not.",
+                "Signatures are 17 KB; length-limited transports truncate \
+                 them.",
+            ],
+        }
+    }
+}
+
 impl SignatureScheme for SphincsPlusShake128fSimpleScheme {
     type PublicKey = sphincsshake128fsimple::PublicKey;
     type SecretKey = sphincsshake128fsimple::SecretKey;
@@ -130,8 +117,71 @@ impl SignatureScheme for SphincsPlusShake128fSimpleScheme {
     }
 }
 
-pub fn bench_message(size: usize) -> Vec<u8> {
-    vec![BENCH_MESSAGE_BYTE; size]
+impl Scheme for SphincsPlusShake128fSimpleScheme {
+    type PublicKey = sphincsshake128fsimple::PublicKey;
+    type SecretKey = sphincsshake128fsimple::SecretKey;
+    type Signature = sphincsshake128fsimple::DetachedSignature;
+    type Error = Infallible;
+
+    fn algorithm_name(&self) -> &'static str {
+        "SPHINCS+"
+    }
+
+    fn param_set_name(&self) -> &'static str {
+        "SPHINCS+-SHAKE-128f-simple"
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "pqcrypto-sphincsplus"
+    }
+
+    fn sizes(&self) -> SchemeSizes {
+        SchemeSizes {
+            public_key_bytes: sphincsshake128fsimple::public_key_bytes(),
+            secret_key_bytes: sphincsshake128fsimple::secret_key_bytes(),
+            signature_bytes: sphincsshake128fsimple::signature_bytes(),
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::STATELESS
+    }
+
+    fn security(&self) -> SecurityProfile {
+        SphincsPlusShake128fSimpleScheme::security(self)
+    }
+
+    fn keypair(
+        &self,
+    ) -> Result<(Self::PublicKey, Self::SecretKey), Self::Error> {
+        Ok(sphincsshake128fsimple::keypair())
+    }
+
+    fn sign(
+        &self,
+        message: &[u8],
+        secret_key: &mut Self::SecretKey,
+    ) -> Result<Self::Signature, Self::Error> {
+        Ok(sphincsshake128fsimple::detached_sign(message, secret_key))
+    }
+
+    fn verify(
+        &self,
+        message: &[u8],
+        signature: &Self::Signature,
+        public_key: &Self::PublicKey,
+    ) -> Result<bool, Self::Error> {
+        #[cfg(feature = "op-count")]
+        zkvm::estimate(self.verify_ops(message.len()));
+        Ok(sphincsshake128fsimple::verify_detached_signature(
+            signature, message, public_key,
+        )
+        .is_ok())
+    }
+
+    fn signature_len(&self, signature: &Self::Signature) -> usize {
+        signature.as_bytes().len()
+    }
 }
 
 pub fn signature_size<S: SignedMessage>(
@@ -141,29 +191,12 @@ pub fn signature_size<S: SignedMessage>(
     signed_message.as_bytes().len().saturating_sub(message_len)
 }
 
-pub fn measure_time<T, F>(operation: F) -> (T, Duration)
-where
-    F: FnOnce() -> T,
-{
-    let start = Instant::now();
-    let value = operation();
-    (value, start.elapsed())
-}
-
 #[cfg(test)]
 mod tests {
     use super::{
-        bench_message, signature_size, SignatureScheme, BENCH_MESSAGE_BYTE,
-        SPHINCS_PLUS_SHAKE_128F_SIMPLE,
+        signature_size, SignatureScheme, SPHINCS_PLUS_SHAKE_128F_SIMPLE,
     };
 
-    #[test]
-    fn bench_message_uses_expected_fill_byte() {
-        let message = bench_message(16);
-        assert_eq!(message.len(), 16);
-        assert!(message.iter().all(|&byte| byte == BENCH_MESSAGE_BYTE));
-    }
-
     #[test]
     fn signature_size_subtracts_message_length() {
         struct FakeSigned(Vec<u8>);
@@ -1,10 +1,20 @@
-use pqcrypto_traits::sign::{PublicKey, SecretKey, SignedMessage};
+use pq_bench_core::console::{
+    memory_summary, or_exit, print_memory, print_timing,
+};
+use pq_bench_core::harness::{measure, records};
+use pq_bench_core::report::{self, OutputFormat};
+use pq_bench_core::stats::{sample, SampleConfig};
+use pq_bench_core::TrackingAllocator;
+use pq_formats::jose::{Jws, JwsAlgorithm};
+use pqcrypto_sphincsplus::sphincsshake128fsimple;
+use pqcrypto_traits::sign::{
+    DetachedSignature, PublicKey, SecretKey, SignedMessage,
+};
 use sphincs_plus::{
-    measure_time, memory, signature_size, SignatureScheme, TrackingAllocator,
-    SPHINCS_PLUS_SHAKE_128F_SIMPLE,
+    signature_size, SignatureScheme, SPHINCS_PLUS_SHAKE_128F_SIMPLE,
 };
 use std::alloc::System;
-use std::time::Duration;
+use std::convert::Infallible;
 
 static SYSTEM_ALLOC: System = System;
 
@@ -15,35 +25,38 @@ static GLOBAL: TrackingAllocator<System> =
 const MESSAGE: &[u8] =
     b"This is a test message for SPHINCS+ signature scheme benchmarking";
 
-fn print_timing(label: &str, duration: Duration) {
-    println!("Time to {label}: {duration:?}");
-    println!("Time to {label} (ns): {}", duration.as_nanos());
-}
-
 fn main() {
+    let format = or_exit(OutputFormat::from_env());
+    if format != OutputFormat::Text {
+        print_records(format);
+        return;
+    }
+    let config = or_exit(SampleConfig::from_env());
     let scheme = SPHINCS_PLUS_SHAKE_128F_SIMPLE;
     println!("=== {} Benchmark ===\n", scheme.algorithm_name());
+    println!(
+        "Sampling: {} warmup runs, {} timed runs per operation\n",
+        config.warmup, config.samples
+    );
 
     println!("--- Key Generation ---");
-    let ((public_key, secret_key), keygen_duration) =
-        measure_time(|| scheme.keypair());
-    print_timing("generate keys", keygen_duration);
+    let (keygen_summary, _) = sample(&config, || scheme.keypair());
+    print_timing("generate keys", keygen_summary);
+    let (public_key, secret_key) = scheme.keypair();
 
     println!("\n--- Signing ---");
-    memory::reset_peak();
-    let (signed_message, sign_duration) =
-        measure_time(|| scheme.sign(MESSAGE, &secret_key));
-    print_timing("sign", sign_duration);
-    let sign_peak_mem = memory::peak_bytes();
-    println!("Peak memory during signing: {sign_peak_mem} bytes");
+    let (sign_summary, sign_mem) =
+        sample(&config, || scheme.sign(MESSAGE, &secret_key));
+    print_timing("sign", sign_summary);
+    print_memory("signing", sign_mem);
+    let signed_message = scheme.sign(MESSAGE, &secret_key);
 
     println!("\n--- Verification ---");
-    memory::reset_peak();
-    let (opened_message, verify_duration) =
-        measure_time(|| scheme.open(&signed_message, &public_key));
-    print_timing("verify", verify_duration);
-    let verify_peak_mem = memory::peak_bytes();
-    println!("Peak memory during verification: {verify_peak_mem} bytes");
+    let (verify_summary, verify_mem) =
+        sample(&config, || scheme.open(&signed_message, &public_key));
+    print_timing("verify", verify_summary);
+    print_memory("verification", verify_mem);
+    let opened_message = scheme.open(&signed_message, &public_key);
 
     match opened_message {
         Some(message) if message == MESSAGE => {
@@ -55,6 +68,21 @@ fn main() {
         None => println!("Signature verification: FAILED"),
     }
 
+    let jws = Jws::sign(
+        JwsAlgorithm::SphincsPlusShake128fSimple,
+        None,
+        MESSAGE,
+        |input| {
+            let signature =
+                sphincsshake128fsimple::detached_sign(input, &secret_key);
+            Ok::<_, Infallible>(signature.as_bytes().to_vec())
+        },
+    )
+    .expect("JWS signing is infallible");
+    let jws_size = jws.to_compact().len();
+    let jws_sig_size = jws.encoded_signature_len();
+    let jws_overhead = jws.envelope_overhead();
+
     println!("\n--- Size Measurements ---");
     println!("Public key size: {} bytes", public_key.as_bytes().len());
     println!("Secret key size: {} bytes", secret_key.as_bytes().len());
@@ -66,24 +94,27 @@ fn main() {
         "Signed message size: {} bytes",
         signed_message.as_bytes().len()
     );
+    println!(
+        "JWS compact size: {jws_size} bytes (base64url signature {jws_sig_size} bytes, overhead {jws_overhead} bytes)"
+    );
 
     println!("\n=== Summary ===");
     println!("Algorithm: {}", scheme.algorithm_name());
-    println!("\nTiming:");
+    println!("\nTiming (median):");
     println!(
         "  Key Generation: {:?} ({} ns)",
-        keygen_duration,
-        keygen_duration.as_nanos()
+        keygen_summary.median,
+        keygen_summary.median.as_nanos()
     );
     println!(
         "  Signing:        {:?} ({} ns)",
-        sign_duration,
-        sign_duration.as_nanos()
+        sign_summary.median,
+        sign_summary.median.as_nanos()
     );
     println!(
         "  Verification:   {:?} ({} ns)",
-        verify_duration,
-        verify_duration.as_nanos()
+        verify_summary.median,
+        verify_summary.median.as_nanos()
     );
     println!("\nSizes:");
     println!("  Public Key:  {} bytes", public_key.as_bytes().len());
@@ -92,7 +123,21 @@ fn main() {
         "  Signature:   {} bytes",
         signature_size(&signed_message, MESSAGE.len())
     );
+    println!("  JWS compact: {jws_size} bytes (+{jws_overhead} header and base64url)");
     println!("\nMemory Usage (heap allocations):");
-    println!("  Signing:      {sign_peak_mem} bytes");
-    println!("  Verification: {verify_peak_mem} bytes");
+    println!("  Signing:      {}", memory_summary(sign_mem));
+    println!("  Verification: {}", memory_summary(verify_mem));
+}
+
+/// `BENCH_FORMAT=json|csv`: records of every operation, sampled as
+/// configured by the `BENCH_*` variables, instead of the human-readable
+/// report.
+fn print_records(format: OutputFormat) {
+    let config = or_exit(SampleConfig::from_env());
+    let measurements = or_exit(measure(
+        &SPHINCS_PLUS_SHAKE_128F_SIMPLE,
+        &[MESSAGE.len()],
+        &config,
+    ));
+    report::print(format, &records(&measurements));
 }
@@ -0,0 +1,428 @@
+//! RV32IM interpreter. Every executed instruction counts as one, which is
+//! how RISC-V zkVMs without precompiles price a guest.
+
+use crate::{EmuError, SYS_HALT, SYS_READ};
+use std::ops::Range;
+
+const A0: usize = 10;
+const A1: usize = 11;
+const A7: usize = 17;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Exit {
+    /// `a0` when the guest halted.
+    pub code: u32,
+    /// Instructions executed, including the halting `ecall`.
+    pub instructions: u64,
+}
+
+/// Flat little-endian memory from address 0, 32 registers and the input
+/// stream `SYS_READ` hands out.
+pub struct Machine {
+    regs: [u32; 32],
+    pc: u32,
+    memory: Vec<u8>,
+    input: Vec<u8>,
+    input_offset: usize,
+    instructions: u64,
+}
+
+impl Machine {
+    pub fn new(memory_size: usize) -> Self {
+        Self {
+            regs: [0; 32],
+            pc: 0,
+            memory: vec![0; memory_size],
+            input: Vec::new(),
+            input_offset: 0,
+            instructions: 0,
+        }
+    }
+
+    pub fn set_pc(&mut self, pc: u32) {
+        self.pc = pc;
+    }
+
+    pub fn set_input(&mut self, input: Vec<u8>) {
+        self.input = input;
+        self.input_offset = 0;
+    }
+
+    pub fn reg(&self, index: usize) -> u32 {
+        self.regs[index]
+    }
+
+    /// Copies `bytes` into memory at `address`.
+    pub fn write(
+        &mut self,
+        address: u32,
+        bytes: &[u8],
+    ) -> Result<(), EmuError> {
+        let range = self.range(address, bytes.len())?;
+        self.memory[range].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    pub fn read(&self, address: u32, len: usize) -> Result<&[u8], EmuError> {
+        Ok(&self.memory[self.range(address, len)?])
+    }
+
+    /// Runs until the guest halts or `max_instructions` have executed.
+    pub fn run(&mut self, max_instructions: u64) -> Result<Exit, EmuError> {
+        loop {
+            if self.instructions == max_instructions {
+                return Err(EmuError::InstructionLimit {
+                    limit: max_instructions,
+                });
+            }
+            if let Some(code) = self.step()? {
+                return Ok(Exit {
+                    code,
+                    instructions: self.instructions,
+                });
+            }
+        }
+    }
+
+    // Executes one instruction. Returns the exit code if it halted.
+    fn step(&mut self) -> Result<Option<u32>, EmuError> {
+        let pc = self.pc;
+        if !pc.is_multiple_of(4) {
+            return Err(EmuError::MisalignedPc { pc });
+        }
+        let word = self.load(pc, 4)?;
+        self.instructions += 1;
+
+        let rd = ((word >> 7) & 0x1f) as usize;
+        let funct3 = (word >> 12) & 0x7;
+        let rs1 = self.regs[((word >> 15) & 0x1f) as usize];
+        let rs2 = self.regs[((word >> 20) & 0x1f) as usize];
+        let funct7 = word >> 25;
+        let imm_i = ((word as i32) >> 20) as u32;
+        let illegal = EmuError::IllegalInstruction { pc, word };
+
+        let mut next_pc = pc.wrapping_add(4);
+        let value = match word & 0x7f {
+            // LUI
+            0x37 => Some(word & 0xffff_f000),
+            // AUIPC
+            0x17 => Some(pc.wrapping_add(word & 0xffff_f000)),
+            // JAL
+            0x6f => {
+                next_pc = pc.wrapping_add(imm_j(word));
+                Some(pc.wrapping_add(4))
+            }
+            // JALR
+            0x67 if funct3 == 0 => {
+                next_pc = rs1.wrapping_add(imm_i) & !1;
+                Some(pc.wrapping_add(4))
+            }
+            0x63 => {
+                let taken = match funct3 {
+                    0 => rs1 == rs2,
+                    1 => rs1 != rs2,
+                    4 => (rs1 as i32) < (rs2 as i32),
+                    5 => (rs1 as i32) >= (rs2 as i32),
+                    6 => rs1 < rs2,
+                    7 => rs1 >= rs2,
+                    _ => return Err(illegal),
+                };
+                if taken {
+                    next_pc = pc.wrapping_add(imm_b(word));
+                }
+                None
+            }
+            0x03 => {
+                let address = rs1.wrapping_add(imm_i);
+                Some(match funct3 {
+                    0 => self.load(address, 1)? as i8 as u32,
+                    1 => self.load(address, 2)? as i16 as u32,
+                    2 => self.load(address, 4)?,
+                    4 => self.load(address, 1)?,
+                    5 => self.load(address, 2)?,
+                    _ => return Err(illegal),
+                })
+            }
+            0x23 => {
+                let address = rs1.wrapping_add(imm_s(word));
+                let len = match funct3 {
+                    0 => 1,
+                    1 => 2,
+                    2 => 4,
+                    _ => return Err(illegal),
+                };
+                self.store(address, len, rs2)?;
+                None
+            }
+            0x13 => {
+                let shamt = imm_i & 0x1f;
+                Some(match (funct3, funct7) {
+                    (0, _) => rs1.wrapping_add(imm_i),
+                    (2, _) => ((rs1 as i32) < (imm_i as i32)) as u32,
+                    (3, _) => (rs1 < imm_i) as u32,
+                    (4, _) => rs1 ^ imm_i,
+                    (6, _) => rs1 | imm_i,
+                    (7, _) => rs1 & imm_i,
+                    (1, 0x00) => rs1 << shamt,
+                    (5, 0x00) => rs1 >> shamt,
+                    (5, 0x20) => ((rs1 as i32) >> shamt) as u32,
+                    _ => return Err(illegal),
+                })
+            }
+            0x33 => Some(match (funct7, funct3) {
+                (0x00, 0) => rs1.wrapping_add(rs2),
+                (0x20, 0) => rs1.wrapping_sub(rs2),
+                (0x00, 1) => rs1 << (rs2 & 0x1f),
+                (0x00, 2) => ((rs1 as i32) < (rs2 as i32)) as u32,
+                (0x00, 3) => (rs1 < rs2) as u32,
+                (0x00, 4) => rs1 ^ rs2,
+                (0x00, 5) => rs1 >> (rs2 & 0x1f),
+                (0x20, 5) => ((rs1 as i32) >> (rs2 & 0x1f)) as u32,
+                (0x00, 6) => rs1 | rs2,
+                (0x00, 7) => rs1 & rs2,
+                (0x01, _) => mul_div(funct3, rs1, rs2),
+                _ => return Err(illegal),
+            }),
+            // FENCE: a single hart has nothing to order.
+            0x0f => None,
+            0x73 => match word {
+                0x0000_0073 => return self.ecall(pc),
+                0x0010_0073 => return Err(EmuError::Breakpoint { pc }),
+                _ => return Err(illegal),
+            },
+            _ => return Err(illegal),
+        };
+
+        if let Some(value) = value {
+            self.regs[rd] = value;
+            self.regs[0] = 0;
+        }
+        self.pc = next_pc;
+        Ok(None)
+    }
+
+    fn ecall(&mut self, pc: u32) -> Result<Option<u32>, EmuError> {
+        match self.regs[A7] {
+            SYS_HALT => return Ok(Some(self.regs[A0])),
+            SYS_READ => {
+                let remaining = self.input.len() - self.input_offset;
+                let len = (self.regs[A1] as usize).min(remaining);
+                let range = self.range(self.regs[A0], len)?;
+                let input = &self.input[self.input_offset..][..len];
+                self.memory[range].copy_from_slice(input);
+                self.input_offset += len;
+                self.regs[A0] = len as u32;
+            }
+            number => return Err(EmuError::UnknownSyscall { pc, number }),
+        }
+        self.pc = pc.wrapping_add(4);
+        Ok(None)
+    }
+
+    fn range(
+        &self,
+        address: u32,
+        len: usize,
+    ) -> Result<Range<usize>, EmuError> {
+        let start = address as usize;
+        match start.checked_add(len) {
+            Some(end) if end <= self.memory.len() => Ok(start..end),
+            _ => Err(EmuError::MemoryFault {
+                pc: self.pc,
+                address,
+            }),
+        }
+    }
+
+    // Misaligned accesses are allowed, as the RISC-V EEI may choose.
+    fn load(&self, address: u32, len: usize) -> Result<u32, EmuError> {
+        let mut bytes = [0_u8; 4];
+        bytes[..len].copy_from_slice(&self.memory[self.range(address, len)?]);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn store(
+        &mut self,
+        address: u32,
+        len: usize,
+        value: u32,
+    ) -> Result<(), EmuError> {
+        let range = self.range(address, len)?;
+        self.memory[range].copy_from_slice(&value.to_le_bytes()[..len]);
+        Ok(())
+    }
+}
+
+// The M extension. Division by zero and signed overflow produce the values
+// the spec defines instead of trapping.
+fn mul_div(funct3: u32, a: u32, b: u32) -> u32 {
+    let (signed_a, signed_b) = (a as i32 as i64, b as i32 as i64);
+    match funct3 {
+        0 => a.wrapping_mul(b),
+        1 => ((signed_a * signed_b) >> 32) as u32,
+        2 => ((signed_a * b as i64) >> 32) as u32,
+        3 => ((a as u64 * b as u64) >> 32) as u32,
+        4 if b == 0 => u32::MAX,
+        4 => (a as i32).wrapping_div(b as i32) as u32,
+        5 => a.checked_div(b).unwrap_or(u32::MAX),
+        6 if b == 0 => a,
+        6 => (a as i32).wrapping_rem(b as i32) as u32,
+        _ => a.checked_rem(b).unwrap_or(a),
+    }
+}
+
+fn imm_s(word: u32) -> u32 {
+    (((word as i32) >> 25) << 5) as u32 | ((word >> 7) & 0x1f)
+}
+
+fn imm_b(word: u32) -> u32 {
+    (((word as i32) >> 31) << 12) as u32
+        | ((word >> 7) & 0x1) << 11
+        | ((word >> 25) & 0x3f) << 5
+        | ((word >> 8) & 0xf) << 1
+}
+
+fn imm_j(word: u32) -> u32 {
+    (((word as i32) >> 31) << 20) as u32
+        | (word & 0x000f_f000)
+        | ((word >> 20) & 0x1) << 11
+        | ((word >> 21) & 0x3ff) << 1
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{mul_div, Exit, Machine};
+    use crate::{EmuError, SYS_HALT, SYS_READ};
+
+    // Just enough of an assembler for the tests.
+    pub(crate) fn addi(rd: u32, rs1: u32, imm: i32) -> u32 {
+        ((imm as u32) << 20) | (rs1 << 15) | (rd << 7) | 0x13
+    }
+
+    fn add(rd: u32, rs1: u32, rs2: u32) -> u32 {
+        (rs2 << 20) | (rs1 << 15) | (rd << 7) | 0x33
+    }
+
+    fn bne(rs1: u32, rs2: u32, offset: i32) -> u32 {
+        let imm = offset as u32;
+        ((imm >> 12) & 1) << 31
+            | ((imm >> 5) & 0x3f) << 25
+            | (rs2 << 20)
+            | (rs1 << 15)
+            | (1 << 12)
+            | ((imm >> 1) & 0xf) << 8
+            | ((imm >> 11) & 1) << 7
+            | 0x63
+    }
+
+    pub(crate) fn lbu(rd: u32, rs1: u32, imm: i32) -> u32 {
+        ((imm as u32) << 20) | (rs1 << 15) | (4 << 12) | (rd << 7) | 0x03
+    }
+
+    pub(crate) const ECALL: u32 = 0x73;
+
+    pub(crate) fn assemble(program: &[u32]) -> Vec<u8> {
+        program.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    fn run(program: &[u32], input: &[u8]) -> Result<Exit, EmuError> {
+        let mut machine = Machine::new(1 << 16);
+        machine.write(0x1000, &assemble(program))?;
+        machine.set_pc(0x1000);
+        machine.set_input(input.to_vec());
+        machine.run(1_000)
+    }
+
+    #[test]
+    fn loops_and_counts_every_instruction() {
+        // Sums 10 + 9 + ... + 1 and halts with the sum.
+        let program = [
+            addi(5, 0, 0),
+            addi(6, 0, 10),
+            add(5, 5, 6),
+            addi(6, 6, -1),
+            bne(6, 0, -8),
+            addi(10, 5, 0),
+            addi(17, 0, SYS_HALT as i32),
+            ECALL,
+        ];
+        let exit = run(&program, &[]).unwrap();
+
+        assert_eq!(exit.code, 55);
+        assert_eq!(exit.instructions, 2 + 10 * 3 + 3);
+    }
+
+    #[test]
+    fn reads_input_into_memory() {
+        // Reads up to 8 input bytes to 0x2000 and halts with the count
+        // plus the last byte read.
+        let program = [
+            addi(10, 0, 0x400),
+            add(10, 10, 10),
+            add(10, 10, 10),
+            add(10, 10, 10),
+            addi(11, 0, 8),
+            addi(17, 0, SYS_READ as i32),
+            ECALL,
+            addi(5, 0, 0x400),
+            add(5, 5, 5),
+            add(5, 5, 5),
+            add(5, 5, 5),
+            add(5, 5, 10),
+            lbu(6, 5, -1),
+            add(10, 10, 6),
+            addi(17, 0, SYS_HALT as i32),
+            ECALL,
+        ];
+
+        assert_eq!(run(&program, &[1, 2, 40]).unwrap().code, 3 + 40);
+        assert_eq!(run(&program, &[7; 20]).unwrap().code, 8 + 7);
+    }
+
+    #[test]
+    fn faults_are_reported_with_their_pc() {
+        assert_eq!(
+            run(&[0], &[]),
+            Err(EmuError::IllegalInstruction {
+                pc: 0x1000,
+                word: 0
+            })
+        );
+        assert_eq!(
+            run(&[addi(17, 0, 99), ECALL], &[]),
+            Err(EmuError::UnknownSyscall {
+                pc: 0x1004,
+                number: 99
+            })
+        );
+        // A jump to itself never halts.
+        assert_eq!(
+            run(&[0x0000_006f], &[]),
+            Err(EmuError::InstructionLimit { limit: 1_000 })
+        );
+        assert_eq!(
+            run(&[lbu(5, 0, -1)], &[]),
+            Err(EmuError::MemoryFault {
+                pc: 0x1000,
+                address: u32::MAX
+            })
+        );
+    }
+
+    #[test]
+    fn division_edge_cases_follow_the_spec() {
+        let min = i32::MIN as u32;
+        let minus_one = u32::MAX;
+        assert_eq!(mul_div(4, 7, 0), u32::MAX);
+        assert_eq!(mul_div(5, 7, 0), u32::MAX);
+        assert_eq!(mul_div(6, 7, 0), 7);
+        assert_eq!(mul_div(7, 7, 0), 7);
+        assert_eq!(mul_div(4, min, minus_one), min);
+        assert_eq!(mul_div(6, min, minus_one), 0);
+        assert_eq!(mul_div(4, -7_i32 as u32, 2), -3_i32 as u32);
+        assert_eq!(mul_div(6, -7_i32 as u32, 2), -1_i32 as u32);
+        assert_eq!(mul_div(1, minus_one, minus_one), 0);
+        assert_eq!(mul_div(2, minus_one, minus_one), minus_one);
+        assert_eq!(mul_div(3, minus_one, minus_one), minus_one - 1);
+    }
+}
@@ -0,0 +1,158 @@
+//! Loads the `PT_LOAD` segments of a 32-bit little-endian RISC-V ELF
+//! executable. Sections, symbols and relocations are ignored: guests are
+//! statically linked at their final addresses.
+
+use crate::EmuError;
+
+const MAGIC: [u8; 4] = *b"\x7fELF";
+const CLASS_32: u8 = 1;
+const DATA_LITTLE_ENDIAN: u8 = 1;
+const TYPE_EXECUTABLE: u16 = 2;
+const MACHINE_RISCV: u16 = 0xf3;
+const PT_LOAD: u32 = 1;
+const HEADER_SIZE: usize = 52;
+const PROGRAM_HEADER_SIZE: usize = 32;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub address: u32,
+    /// The file bytes; the rest of the segment's memory size is zeroed.
+    pub data: Vec<u8>,
+    pub memory_size: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Program {
+    pub entry: u32,
+    pub segments: Vec<Segment>,
+}
+
+impl Program {
+    pub fn parse(bytes: &[u8]) -> Result<Self, EmuError> {
+        let header = bytes.get(..HEADER_SIZE).ok_or_else(truncated)?;
+        if header[..4] != MAGIC {
+            return Err(EmuError::Elf("not an ELF file"));
+        }
+        if header[4] != CLASS_32 || header[5] != DATA_LITTLE_ENDIAN {
+            return Err(EmuError::Elf("not a 32-bit little-endian ELF"));
+        }
+        if u16_at(header, 16) != TYPE_EXECUTABLE {
+            return Err(EmuError::Elf("not an executable"));
+        }
+        if u16_at(header, 18) != MACHINE_RISCV {
+            return Err(EmuError::Elf("not a RISC-V ELF"));
+        }
+
+        let entry = u32_at(header, 24);
+        let table = u32_at(header, 28) as usize;
+        let entry_size = u16_at(header, 42) as usize;
+        let entries = u16_at(header, 44) as usize;
+        if entries > 0 && entry_size < PROGRAM_HEADER_SIZE {
+            return Err(EmuError::Elf("program headers too small"));
+        }
+
+        let mut segments = Vec::new();
+        for index in 0..entries {
+            let start = table + index * entry_size;
+            let program_header = bytes
+                .get(start..start + PROGRAM_HEADER_SIZE)
+                .ok_or_else(truncated)?;
+            if u32_at(program_header, 0) != PT_LOAD {
+                continue;
+            }
+            let offset = u32_at(program_header, 4) as usize;
+            let address = u32_at(program_header, 8);
+            let file_size = u32_at(program_header, 16) as usize;
+            let memory_size = u32_at(program_header, 20);
+            if file_size > memory_size as usize {
+                return Err(EmuError::Elf(
+                    "segment larger on file than in memory",
+                ));
+            }
+            let data = bytes
+                .get(offset..offset + file_size)
+                .ok_or_else(truncated)?
+                .to_vec();
+            segments.push(Segment {
+                address,
+                data,
+                memory_size,
+            });
+        }
+        Ok(Self { entry, segments })
+    }
+}
+
+fn truncated() -> EmuError {
+    EmuError::Elf("truncated")
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    let mut word = [0_u8; 4];
+    word.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(word)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{Program, Segment};
+    use crate::EmuError;
+
+    /// A minimal executable with one `PT_LOAD` segment holding `code` at
+    /// `address`, entered at its start.
+    pub(crate) fn executable(address: u32, code: &[u8]) -> Vec<u8> {
+        let mut elf = vec![0_u8; 52 + 32];
+        elf[..8].copy_from_slice(b"\x7fELF\x01\x01\x01\x00");
+        elf[16..18].copy_from_slice(&2_u16.to_le_bytes());
+        elf[18..20].copy_from_slice(&0xf3_u16.to_le_bytes());
+        elf[20..24].copy_from_slice(&1_u32.to_le_bytes());
+        elf[24..28].copy_from_slice(&address.to_le_bytes());
+        elf[28..32].copy_from_slice(&52_u32.to_le_bytes());
+        elf[40..42].copy_from_slice(&52_u16.to_le_bytes());
+        elf[42..44].copy_from_slice(&32_u16.to_le_bytes());
+        elf[44..46].copy_from_slice(&1_u16.to_le_bytes());
+
+        let segment = &mut elf[52..];
+        segment[..4].copy_from_slice(&1_u32.to_le_bytes());
+        segment[4..8].copy_from_slice(&84_u32.to_le_bytes());
+        segment[8..12].copy_from_slice(&address.to_le_bytes());
+        segment[12..16].copy_from_slice(&address.to_le_bytes());
+        segment[16..20].copy_from_slice(&(code.len() as u32).to_le_bytes());
+        // 16 bytes of .bss after the code.
+        segment[20..24]
+            .copy_from_slice(&(code.len() as u32 + 16).to_le_bytes());
+        elf.extend_from_slice(code);
+        elf
+    }
+
+    #[test]
+    fn load_segments_are_read() {
+        let elf = executable(0x1_0000, &[1, 2, 3, 4]);
+        assert_eq!(
+            Program::parse(&elf).unwrap(),
+            Program {
+                entry: 0x1_0000,
+                segments: vec![Segment {
+                    address: 0x1_0000,
+                    data: vec![1, 2, 3, 4],
+                    memory_size: 20,
+                }],
+            }
+        );
+
+        let mut not_riscv = elf.clone();
+        not_riscv[18] = 0x3e;
+        assert_eq!(
+            Program::parse(&not_riscv),
+            Err(EmuError::Elf("not a RISC-V ELF"))
+        );
+        assert_eq!(
+            Program::parse(&elf[..elf.len() - 1]),
+            Err(EmuError::Elf("truncated"))
+        );
+    }
+}
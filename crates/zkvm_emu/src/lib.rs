@@ -0,0 +1,109 @@
+//! Host-side RV32IM emulator that counts the instructions a zkVM guest
+//! executes, so verifier cost can be measured locally without a zkVM
+//! service or prover.
+//!
+//! Guests are statically linked `riscv32im-unknown-none-elf` executables
+//! with RAM from address 0 up to [`MEMORY_SIZE`]. They reach the host with
+//! `ecall`, passing the syscall number in `a7`:
+//!
+//! - [`SYS_HALT`] stops the guest with exit code `a0`.
+//! - [`SYS_READ`] copies up to `a1` input bytes to address `a0` and returns
+//!   the number copied in `a0`.
+//!
+//! Real zkVMs price memory paging, precompiles and proof segments on top of
+//! the executed instructions; the count here is their common base.
+
+pub mod cpu;
+pub mod elf;
+
+pub use cpu::{Exit, Machine};
+pub use elf::Program;
+
+use std::error::Error;
+use std::fmt;
+
+pub const SYS_HALT: u32 = 0;
+pub const SYS_READ: u32 = 1;
+
+pub const MEMORY_SIZE: usize = 16 << 20;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EmuError {
+    Elf(&'static str),
+    MemoryFault { pc: u32, address: u32 },
+    MisalignedPc { pc: u32 },
+    IllegalInstruction { pc: u32, word: u32 },
+    UnknownSyscall { pc: u32, number: u32 },
+    Breakpoint { pc: u32 },
+    InstructionLimit { limit: u64 },
+}
+
+impl fmt::Display for EmuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Elf(reason) => write!(f, "invalid guest ELF: {reason}"),
+            Self::MemoryFault { pc, address } => {
+                write!(f, "memory fault at {address:#010x} (pc {pc:#010x})")
+            }
+            Self::MisalignedPc { pc } => {
+                write!(f, "misaligned pc {pc:#010x}")
+            }
+            Self::IllegalInstruction { pc, word } => {
+                write!(f, "illegal instruction {word:#010x} at {pc:#010x}")
+            }
+            Self::UnknownSyscall { pc, number } => {
+                write!(f, "unknown syscall {number} at {pc:#010x}")
+            }
+            Self::Breakpoint { pc } => write!(f, "ebreak at {pc:#010x}"),
+            Self::InstructionLimit { limit } => {
+                write!(f, "guest did not halt within {limit} instructions")
+            }
+        }
+    }
+}
+
+impl Error for EmuError {}
+
+/// Loads `program` into a fresh machine, feeds it `input` and runs it until
+/// it halts.
+pub fn run(
+    program: &Program,
+    input: Vec<u8>,
+    max_instructions: u64,
+) -> Result<Exit, EmuError> {
+    let mut machine = Machine::new(MEMORY_SIZE);
+    for segment in &program.segments {
+        // Memory starts zeroed, so checking the bounds covers .bss.
+        machine.read(segment.address, segment.memory_size as usize)?;
+        machine.write(segment.address, &segment.data)?;
+    }
+    machine.set_pc(program.entry);
+    machine.set_input(input);
+    machine.run(max_instructions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, Program, SYS_HALT, SYS_READ};
+    use crate::cpu::tests::{addi, assemble, lbu, ECALL};
+    use crate::elf::tests::executable;
+
+    #[test]
+    fn guests_run_from_their_elf() {
+        // Reads one input byte into its .bss and halts with it.
+        let code = assemble(&[
+            addi(10, 0, 0x400),
+            addi(11, 0, 1),
+            addi(17, 0, SYS_READ as i32),
+            ECALL,
+            lbu(10, 0, 0x400),
+            addi(17, 0, SYS_HALT as i32),
+            ECALL,
+        ]);
+        let program = Program::parse(&executable(0x100, &code)).unwrap();
+
+        let exit = run(&program, vec![42], 100).unwrap();
+        assert_eq!(exit.code, 42);
+        assert_eq!(exit.instructions, 7);
+    }
+}
@@ -0,0 +1,265 @@
+use lamport_ots::guest::{encode_input, Verifier};
+use pq_bench_core::{BENCH_MESSAGE_BYTE, BENCH_MESSAGE_SIZES};
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::{env, fs};
+use zkvm_emu::Program;
+
+const USAGE: &str = "\
+Usage: zkvm-emu [guest.elf] [options]
+
+Runs the Lamport/MSS verify guest on a valid and a tampered signature per
+parameter set and message size, and prints the instructions each valid
+verify executed.
+
+Options:
+  --scheme <list>      Comma-separated parameter sets (default: all)
+  --sizes <list>       Comma-separated message sizes in bytes
+  --help               Show this message
+";
+
+const DEFAULT_GUEST: &str =
+    "guests/lamport_verify/target/riscv32im-unknown-none-elf/release/lamport_verify";
+const BUILD_HINT: &str = "cd guests/lamport_verify && cargo build --release";
+
+// Exit codes of the guest.
+const EXIT_VALID: u32 = 0;
+const EXIT_INVALID: u32 = 1;
+
+const MAX_INSTRUCTIONS: u64 = 1 << 36;
+const KEY_SEED: u64 = 0x5a4b_564d;
+
+struct Options {
+    guest: PathBuf,
+    verifiers: Vec<Verifier>,
+    message_sizes: Vec<usize>,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(
+    mut args: impl Iterator<Item = String>,
+) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        guest: PathBuf::from(DEFAULT_GUEST),
+        verifiers: Vec::new(),
+        message_sizes: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        if arg == "--help" {
+            return Ok(None);
+        }
+        if !arg.starts_with("--") {
+            options.guest = PathBuf::from(arg);
+            continue;
+        }
+        let value =
+            args.next().ok_or_else(|| format!("{arg} needs a value"))?;
+        for item in value.split(',').map(str::trim) {
+            match arg.as_str() {
+                "--scheme" => options.verifiers.push(
+                    Verifier::from_param_set_name(item).ok_or_else(|| {
+                        format!("unknown parameter set {item}")
+                    })?,
+                ),
+                "--sizes" => options.message_sizes.push(
+                    item.parse()
+                        .map_err(|_| format!("invalid message size {item}"))?,
+                ),
+                _ => return Err(format!("unknown option {arg}")),
+            }
+        }
+    }
+    if options.verifiers.is_empty() {
+        options.verifiers = Verifier::ALL.to_vec();
+    }
+    if options.message_sizes.is_empty() {
+        options.message_sizes = BENCH_MESSAGE_SIZES.to_vec();
+    }
+    Ok(Some(options))
+}
+
+fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let elf = fs::read(&options.guest).map_err(|err| {
+        format!(
+            "cannot read guest {}: {err}\nbuild it with `{BUILD_HINT}`",
+            options.guest.display()
+        )
+    })?;
+    let program = Program::parse(&elf)?;
+
+    println!("| Param set | Message bytes | Input bytes | Instructions |");
+    println!("|---|---:|---:|---:|");
+    for verifier in &options.verifiers {
+        let name = verifier.param_set_name();
+        for row in run_guest(&program, verifier, &options.message_sizes)? {
+            println!(
+                "| {name} | {} | {} | {} |",
+                row.message_bytes, row.input_bytes, row.instructions
+            );
+        }
+    }
+    Ok(())
+}
+
+struct Row {
+    message_bytes: usize,
+    input_bytes: usize,
+    instructions: u64,
+}
+
+/// Runs the guest on a valid and a tampered signature per message size,
+/// failing unless it accepts the first and rejects the second.
+fn run_guest(
+    program: &Program,
+    verifier: &Verifier,
+    message_sizes: &[usize],
+) -> Result<Vec<Row>, Box<dyn Error>> {
+    let name = verifier.param_set_name();
+    let messages: Vec<Vec<u8>> = message_sizes
+        .iter()
+        .map(|&size| vec![BENCH_MESSAGE_BYTE; size])
+        .collect();
+    let mut rows = Vec::new();
+    for (message, (signature, public_key)) in
+        messages.iter().zip(sign_all(verifier, &messages))
+    {
+        let input = encode_input(name, message, &signature, &public_key);
+        let input_bytes = input.len();
+        let exit = zkvm_emu::run(program, input, MAX_INSTRUCTIONS)?;
+        if exit.code != EXIT_VALID {
+            return Err(format!(
+                "{name}: guest exited with {} on a valid signature",
+                exit.code
+            )
+            .into());
+        }
+
+        let mut tampered = signature;
+        if let Some(last) = tampered.last_mut() {
+            *last ^= 1;
+        }
+        let input = encode_input(name, message, &tampered, &public_key);
+        let rejected = zkvm_emu::run(program, input, MAX_INSTRUCTIONS)?;
+        if rejected.code != EXIT_INVALID {
+            return Err(format!(
+                "{name}: guest exited with {} on a tampered signature",
+                rejected.code
+            )
+            .into());
+        }
+
+        rows.push(Row {
+            message_bytes: message.len(),
+            input_bytes,
+            instructions: exit.instructions,
+        });
+    }
+    Ok(rows)
+}
+
+/// Encoded signatures and public keys for `messages`: a fresh key per
+/// message for Lamport, one leaf per message for MSS, moving on to a new
+/// tree whenever one runs out of leaves.
+fn sign_all(
+    verifier: &Verifier,
+    messages: &[Vec<u8>],
+) -> Vec<(Vec<u8>, Vec<u8>)> {
+    match verifier {
+        Verifier::Ots(scheme) => messages
+            .iter()
+            .enumerate()
+            .map(|(index, message)| {
+                let (public_key, mut secret_key) =
+                    scheme.keypair_with_seed(KEY_SEED + index as u64);
+                let signature = scheme
+                    .sign(message, &mut secret_key)
+                    .expect("fresh keys sign once");
+                (signature.to_bytes(), public_key.to_bytes())
+            })
+            .collect(),
+        Verifier::Mss(scheme) => {
+            let mut tree = 0;
+            let (mut public_key, mut secret_key) =
+                scheme.keypair_with_seed(KEY_SEED);
+            messages
+                .iter()
+                .map(|message| {
+                    if secret_key.remaining_signatures() == 0 {
+                        tree += 1;
+                        (public_key, secret_key) =
+                            scheme.keypair_with_seed(KEY_SEED + tree);
+                    }
+                    let signature = scheme
+                        .sign(message, &mut secret_key)
+                        .expect("the tree has a leaf left");
+                    (signature.to_bytes(), public_key.root().to_vec())
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_guest, sign_all, BUILD_HINT, DEFAULT_GUEST};
+    use lamport_ots::guest::Verifier;
+    use lamport_ots::mss::{MssHeight, MssScheme};
+    use lamport_ots::LamportVariant;
+    use std::fs;
+    use std::path::Path;
+    use zkvm_emu::Program;
+
+    #[test]
+    fn mss_moves_to_a_new_tree_when_one_runs_out() {
+        let scheme = MssScheme::new(MssHeight::H5, LamportVariant::Plain);
+        let messages = vec![vec![0x42; 32]; 33];
+        let signed = sign_all(&Verifier::Mss(scheme), &messages);
+
+        assert_eq!(signed.len(), 33);
+        assert_eq!(signed[0].1, signed[31].1);
+        assert_ne!(signed[31].1, signed[32].1);
+    }
+
+    #[test]
+    #[ignore = "needs the RISC-V guest; run `make guest-test`"]
+    fn guest_accepts_valid_and_rejects_tampered_signatures() {
+        let guest = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../..")
+            .join(DEFAULT_GUEST);
+        let elf = fs::read(&guest).unwrap_or_else(|err| {
+            panic!(
+                "cannot read guest {}: {err}\nbuild it with `{BUILD_HINT}`",
+                guest.display()
+            )
+        });
+        let program = Program::parse(&elf).unwrap();
+        for verifier in [
+            Verifier::from_param_set_name("Lamport-OTS-256").unwrap(),
+            Verifier::Mss(MssScheme::new(MssHeight::H5, LamportVariant::Plain)),
+        ] {
+            let rows = run_guest(&program, &verifier, &[32]).unwrap();
+            assert!(rows[0].instructions > 0);
+        }
+    }
+}
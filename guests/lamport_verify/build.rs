@@ -0,0 +1,7 @@
+use std::env;
+
+fn main() {
+    let dir = env::var("CARGO_MANIFEST_DIR").expect("set by cargo");
+    println!("cargo:rustc-link-arg=-T{dir}/link.x");
+    println!("cargo:rerun-if-changed=link.x");
+}
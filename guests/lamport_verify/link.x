@@ -0,0 +1,17 @@
+/* zkvm_emu maps 16 MiB of RAM from address 0. The guest loads at 64 KiB
+   and its stack grows down from the end of RAM. */
+ENTRY(_start)
+
+MEMORY
+{
+  RAM : ORIGIN = 0x00010000, LENGTH = 0x00ff0000
+}
+
+SECTIONS
+{
+  .text : { KEEP(*(.text.start)) *(.text .text.*) } > RAM
+  .rodata : ALIGN(4) { *(.srodata .srodata.*) *(.rodata .rodata.*) } > RAM
+  .data : ALIGN(4) { *(.sdata .sdata.*) *(.data .data.*) } > RAM
+  .bss (NOLOAD) : ALIGN(4) { *(.sbss .sbss.*) *(.bss .bss.*) } > RAM
+  __stack_top = ORIGIN(RAM) + LENGTH(RAM);
+}
@@ -0,0 +1,85 @@
+//! zkVM guest that reads one framed input (see `lamport_ots::guest`),
+//! verifies it without allocating and halts with exit code 0 (valid), 1
+//! (invalid), 2 (malformed input) or 3 (panic).
+
+#![no_std]
+#![no_main]
+
+use core::arch::{asm, global_asm};
+use core::panic::PanicInfo;
+use core::ptr::addr_of_mut;
+
+// The syscall numbers of `zkvm_emu`.
+const SYS_HALT: u32 = 0;
+const SYS_READ: u32 = 1;
+
+const EXIT_VALID: u32 = 0;
+const EXIT_INVALID: u32 = 1;
+const EXIT_MALFORMED: u32 = 2;
+const EXIT_PANIC: u32 = 3;
+
+// Holds the largest MSS signature with a 1 MiB message.
+const INPUT_CAPACITY: usize = 1 << 21;
+
+static mut INPUT: [u8; INPUT_CAPACITY] = [0; INPUT_CAPACITY];
+
+global_asm!(
+    ".section .text.start, \"ax\"",
+    ".global _start",
+    "_start:",
+    "la sp, __stack_top",
+    "call main",
+);
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+    // SAFETY: the only reference to `INPUT`, taken once on the only hart.
+    let buffer = unsafe { &mut *addr_of_mut!(INPUT) };
+    let mut len = 0;
+    loop {
+        let read = read(&mut buffer[len..]);
+        if read == 0 {
+            break;
+        }
+        len += read;
+    }
+
+    let code = match lamport_ots::guest::verify_input(&buffer[..len]) {
+        Ok(true) => EXIT_VALID,
+        Ok(false) => EXIT_INVALID,
+        Err(_) => EXIT_MALFORMED,
+    };
+    halt(code)
+}
+
+fn read(buffer: &mut [u8]) -> usize {
+    let read: usize;
+    // SAFETY: the host writes at most `buffer.len()` bytes into `buffer`.
+    unsafe {
+        asm!(
+            "ecall",
+            inlateout("a0") buffer.as_mut_ptr() => read,
+            in("a1") buffer.len(),
+            in("a7") SYS_READ,
+            options(nostack),
+        );
+    }
+    read
+}
+
+fn halt(code: u32) -> ! {
+    // SAFETY: the host stops the guest; control never returns.
+    unsafe {
+        asm!(
+            "ecall",
+            in("a0") code,
+            in("a7") SYS_HALT,
+            options(noreturn, nostack),
+        );
+    }
+}
+
+#[panic_handler]
+fn panic(_: &PanicInfo) -> ! {
+    halt(EXIT_PANIC)
+}